@@ -0,0 +1,89 @@
+use clap::ValueEnum;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// How warnings and errors are additionally surfaced to a CI system
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// GitHub Actions workflow commands (`::warning file=...::message`)
+    Github,
+    /// No annotations
+    None,
+}
+
+static FORMAT: OnceLock<AnnotationFormat> = OnceLock::new();
+
+/// Pick the annotation format for this run; GitHub Actions is detected
+/// from `GITHUB_ACTIONS=true` unless a format was given explicitly
+pub fn init(format: Option<AnnotationFormat>) {
+    let format = format.unwrap_or_else(|| {
+        if std::env::var("GITHUB_ACTIONS").as_deref() == Ok("true") {
+            AnnotationFormat::Github
+        } else {
+            AnnotationFormat::None
+        }
+    });
+    let _ = FORMAT.set(format);
+}
+
+pub fn warning(file: Option<&Path>, message: &str) {
+    emit("warning", file, message);
+}
+
+pub fn error(file: Option<&Path>, message: &str) {
+    emit("error", file, message);
+}
+
+/// Print a workflow command; unlike progress output it is kept with `-q`,
+/// and goes to stderr in JSON mode so stdout stays parseable
+fn emit(level: &str, file: Option<&Path>, message: &str) {
+    if FORMAT.get() != Some(&AnnotationFormat::Github) {
+        return;
+    }
+    let command = github_command(level, file, message);
+    if crate::output::is_json() {
+        eprintln!("{command}");
+    } else {
+        println!("{command}");
+    }
+}
+
+fn github_command(level: &str, file: Option<&Path>, message: &str) -> String {
+    let message = escape_data(message);
+    match file {
+        Some(file) => format!(
+            "::{level} file={}::{message}",
+            escape_property(&file.display().to_string())
+        ),
+        None => format!("::{level}::{message}"),
+    }
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_command() {
+        assert_eq!(
+            github_command(
+                "warning",
+                Some(Path::new("a,b/pyproject.toml")),
+                "50% done\nnext"
+            ),
+            "::warning file=a%2Cb/pyproject.toml::50%25 done%0Anext"
+        );
+        assert_eq!(github_command("error", None, "boom"), "::error::boom");
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::layout::SrcMove;
+
+/// Environment variable naming the backup store instead of the data directory
+pub const BACKUP_DIR_ENV_VAR: &str = "POST_INIT_BACKUP_DIR";
+
+/// Name of the saved content inside a backup entry
+const ORIGINAL_FILE_NAME: &str = "original";
+/// Name of the file recording which path an entry belongs to
+const PATH_FILE_NAME: &str = "path";
+/// Name of the file holding the hash of the content written over the original
+const WRITTEN_FILE_NAME: &str = "written";
+/// Name of the file recording a package move made along with the write
+const MOVED_FILE_NAME: &str = "moved.json";
+
+/// Hex SHA-256 of `content`, as recorded for the content written over a backup
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// A backup entry and the file it was taken from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub file: PathBuf,
+    pub backup: PathBuf,
+}
+
+/// Pre-edit copies of modified files, one entry per file under `<root>/<hash>`
+///
+/// Keeping them out of the project stops backups from showing up in
+/// `git status` or being picked up as project files.
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+impl BackupStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The store in POST_INIT_BACKUP_DIR, or `~/.local/share/post-init/backups`
+    pub fn open() -> Result<Self> {
+        if let Some(dir) = std::env::var_os(BACKUP_DIR_ENV_VAR).filter(|dir| !dir.is_empty()) {
+            return Ok(Self::new(PathBuf::from(dir)));
+        }
+        let data_dir = dirs::data_local_dir().ok_or_else(|| {
+            anyhow::anyhow!("Could not find a data directory for backups; set {BACKUP_DIR_ENV_VAR}")
+        })?;
+        Ok(Self::new(data_dir.join("post-init").join("backups")))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn entry_dir(&self, file: &Path) -> PathBuf {
+        let file = fs::canonicalize(file)
+            .or_else(|_| std::path::absolute(file))
+            .unwrap_or_else(|_| file.to_path_buf());
+        let digest = Sha256::digest(file.to_string_lossy().as_bytes());
+        let hash: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+        self.root.join(hash)
+    }
+
+    /// Path of the backup kept for `file`
+    pub fn backup_path(&self, file: &Path) -> PathBuf {
+        self.entry_dir(file).join(ORIGINAL_FILE_NAME)
+    }
+
+    /// The backup of `file`, if there is one
+    pub fn find(&self, file: &Path) -> Option<PathBuf> {
+        Some(self.backup_path(file)).filter(|backup| backup.is_file())
+    }
+
+    /// Save `original` as the backup of `file`, which is about to be rewritten
+    /// with `written`
+    ///
+    /// Call it before the write, so a failed backup can stop it. The hash of
+    /// `written` is kept with the backup, so a later edit of `file` is
+    /// detectable by comparing its content against it.
+    pub fn write(&self, file: &Path, original: &str, written: &str) -> Result<PathBuf> {
+        let entry = self.entry_dir(file);
+        fs::create_dir_all(&entry)
+            .with_context(|| format!("Failed to create backup directory: {}", entry.display()))?;
+        let backup = entry.join(ORIGINAL_FILE_NAME);
+
+        // An identical existing backup is kept rather than written again
+        if !fs::read_to_string(&backup).is_ok_and(|existing| existing == original) {
+            fs::write(&backup, original)
+                .with_context(|| format!("Failed to write backup: {}", backup.display()))?;
+        }
+        fs::write(entry.join(WRITTEN_FILE_NAME), content_hash(written))
+            .with_context(|| format!("Failed to write backup: {}", entry.display()))?;
+        // A move belongs to the write it was made with, not to later ones
+        let _ = fs::remove_file(entry.join(MOVED_FILE_NAME));
+
+        let real_path = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        fs::write(
+            entry.join(PATH_FILE_NAME),
+            real_path.to_string_lossy().as_bytes(),
+        )
+        .with_context(|| format!("Failed to write backup: {}", entry.display()))?;
+
+        Ok(backup)
+    }
+
+    /// Record `moved` with the backup of `file`, so restoring it moves the
+    /// package back; call it after [`BackupStore::write`]
+    pub fn record_move(&self, file: &Path, moved: &SrcMove) -> Result<()> {
+        let path = self.entry_dir(file).join(MOVED_FILE_NAME);
+        let json =
+            serde_json::to_string_pretty(moved).with_context(|| "Failed to serialize move")?;
+        fs::write(&path, json)
+            .with_context(|| format!("Failed to write backup: {}", path.display()))
+    }
+
+    /// The package move recorded with the backup of `file`, if any
+    pub fn moved(&self, file: &Path) -> Option<SrcMove> {
+        let json = fs::read_to_string(self.entry_dir(file).join(MOVED_FILE_NAME)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Hash of the content written over the backup of `file`; `None` for
+    /// backups taken before hashes were recorded
+    pub fn written_hash(&self, file: &Path) -> Option<String> {
+        fs::read_to_string(self.entry_dir(file).join(WRITTEN_FILE_NAME)).ok()
+    }
+
+    /// Every backup in the store, sorted by file path
+    pub fn list(&self) -> Result<Vec<Backup>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read backup store: {}", self.root.display()))?;
+        let mut backups = Vec::new();
+        for entry in entries {
+            let entry = entry.with_context(|| "Failed to read backup store")?;
+            let backup = entry.path().join(ORIGINAL_FILE_NAME);
+            let Ok(file) = fs::read_to_string(entry.path().join(PATH_FILE_NAME)) else {
+                continue;
+            };
+            if backup.is_file() {
+                backups.push(Backup {
+                    file: PathBuf::from(file),
+                    backup,
+                });
+            }
+        }
+        backups.sort_by(|a, b| a.file.cmp(&b.file));
+
+        Ok(backups)
+    }
+
+    /// Drop the backup of `file`
+    pub fn remove(&self, file: &Path) -> Result<()> {
+        let entry = self.entry_dir(file);
+        fs::remove_dir_all(&entry)
+            .with_context(|| format!("Failed to remove backup: {}", entry.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_backup() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = BackupStore::new(temp_dir.path().join("store"));
+        let path = temp_dir.path().join("pyproject.toml");
+        fs::write(&path, "old")?;
+
+        let backup = store.write(&path, "old", "new")?;
+        assert!(backup.starts_with(store.root()));
+        assert_eq!(store.find(&path), Some(backup.clone()));
+        assert_eq!(fs::read_to_string(&backup)?, "old");
+        assert_eq!(store.written_hash(&path), Some(content_hash("new")));
+        assert_eq!(store.moved(&path), None);
+
+        let moved = SrcMove {
+            from: temp_dir.path().join("pkg"),
+            to: temp_dir.path().join("src").join("pkg"),
+            rewritten: Vec::new(),
+        };
+        store.record_move(&path, &moved)?;
+        assert_eq!(store.moved(&path), Some(moved));
+
+        // The same original again only records the newly written content
+        store.write(&path, "old", "newer")?;
+        assert_eq!(fs::read_to_string(&backup)?, "old");
+        assert_eq!(store.written_hash(&path), Some(content_hash("newer")));
+        assert_eq!(store.moved(&path), None);
+
+        assert_eq!(
+            store.list()?,
+            vec![Backup {
+                file: fs::canonicalize(&path)?,
+                backup: backup.clone(),
+            }]
+        );
+
+        store.remove(&path)?;
+        assert_eq!(store.find(&path), None);
+        assert!(store.list()?.is_empty());
+
+        Ok(())
+    }
+}
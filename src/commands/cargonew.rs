@@ -1,5 +1,244 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml_edit::DocumentMut;
 
-pub fn run_cargonew(_name: &str, _template: &str) -> Result<()> {
-    Err(anyhow::anyhow!("cargonew command is not implemented yet"))
+use crate::config::*;
+use crate::files::{OverwritePolicy, write_file};
+use crate::infer::ProjectInfo;
+use crate::output::say;
+use crate::report::Change;
+use crate::templates;
+
+/// Options for a single cargonew run, collected from the command line
+#[derive(Debug, Default)]
+pub struct CargonewOptions {
+    /// `bin` or `lib`, overriding the configured default_template
+    pub template: Option<String>,
+    /// .gitignore template overriding the configured one
+    pub gitignore_template: Option<String>,
+}
+
+fn template_flag(template: &str) -> Result<&'static str> {
+    match template {
+        "bin" => Ok("--bin"),
+        "lib" => Ok("--lib"),
+        other => Err(anyhow::anyhow!(
+            "Unknown cargo template '{other}'. Expected 'bin' or 'lib'"
+        )),
+    }
+}
+
+/// Add the configured dev-dependencies and release profile to a new Cargo.toml
+///
+/// Entries cargo already wrote are left alone.
+fn apply_cargo_extras(manifest_path: &Path, config: &CargonewConfig) -> Result<Vec<Change>> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read file: {}", manifest_path.display()))?;
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .with_context(|| format!("Failed to parse TOML in: {}", manifest_path.display()))?;
+    let mut changes = Vec::new();
+
+    if !config.dev_dependencies.is_empty()
+        && let Some(dev_deps) = doc
+            .entry("dev-dependencies")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+    {
+        for (name, version) in &config.dev_dependencies {
+            if !dev_deps.contains_key(name) {
+                dev_deps.insert(name, toml_edit::value(version));
+                changes.push(Change::set(
+                    &format!("dev-dependencies.{name}"),
+                    &format!("\"{version}\""),
+                ));
+            }
+        }
+    }
+
+    if !config.release_profile.is_empty() {
+        let profile = doc
+            .entry("profile")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("'profile' in Cargo.toml is not a table"))?;
+        profile.set_implicit(true);
+        let release = profile
+            .entry("release")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("'profile.release' in Cargo.toml is not a table"))?;
+
+        for (key, value) in &config.release_profile {
+            if release.contains_key(key) {
+                continue;
+            }
+            let edit_value = value
+                .to_string()
+                .parse::<toml_edit::Value>()
+                .with_context(|| format!("Invalid release_profile value for '{key}'"))?;
+            release.insert(key, toml_edit::value(edit_value));
+            changes.push(Change::set(
+                &format!("profile.release.{key}"),
+                &value.to_string(),
+            ));
+        }
+    }
+
+    if !changes.is_empty() {
+        write_file(manifest_path, &doc.to_string(), OverwritePolicy::Force)?;
+    }
+    Ok(changes)
+}
+
+/// Run `cargo new` for `name` inside `parent` and apply the configured extras
+///
+/// Returns the new project directory.
+fn scaffold(parent: &Path, name: &str, template: &str, config: &CargonewConfig) -> Result<PathBuf> {
+    let template_flag = template_flag(template)?;
+    let project_dir = parent.join(name);
+    if project_dir.exists() {
+        return Err(anyhow::anyhow!(
+            "Destination {} already exists; choose another name or remove it first",
+            project_dir.display()
+        ));
+    }
+
+    let vcs = if config.init_git { "git" } else { "none" };
+    say!("📦 Creating Cargo project: {name}");
+
+    let output = Command::new("cargo")
+        .args(["new", name, template_flag, "--vcs", vcs])
+        .current_dir(parent)
+        .output()
+        .with_context(|| "Failed to run cargo; is it installed?")?;
+    log::info!(
+        "cargo new {} {} --vcs {} exited with {}",
+        name,
+        template_flag,
+        vcs,
+        output.status
+    );
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "cargo new failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let manifest_path = project_dir.join("Cargo.toml");
+    let changes = apply_cargo_extras(&manifest_path, config)?;
+    if !changes.is_empty() {
+        say!("  ✅ {}", manifest_path.display());
+        for change in &changes {
+            say!("      {change}");
+        }
+    }
+
+    Ok(project_dir)
+}
+
+pub fn run_cargonew(
+    project: &ProjectInfo,
+    options: &CargonewOptions,
+    config_args: &ConfigArgs,
+) -> Result<()> {
+    let config = load_config(config_args, Some("cargonew"))?;
+    let cargonew_config = &config.cargonew;
+
+    let template = options
+        .template
+        .as_deref()
+        .unwrap_or(&cargonew_config.default_template);
+    let gitignore_template = options
+        .gitignore_template
+        .as_deref()
+        .unwrap_or(&cargonew_config.gitignore_template);
+    // Resolve the templates before running cargo so a typo doesn't leave a half-made project
+    template_flag(template)?;
+    let gitignore = templates::gitignore(gitignore_template)?;
+
+    let project_dir = scaffold(Path::new("."), &project.name, template, cargonew_config)?;
+
+    // Cargo's own .gitignore is just `/target`, so the template replaces it
+    let gitignore_path = project_dir.join(".gitignore");
+    write_file(&gitignore_path, &gitignore, OverwritePolicy::Force)?;
+    say!("  ✅ {} ({})", gitignore_path.display(), gitignore_template);
+
+    say!("\n🎉 Done!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_template_flag() {
+        assert_eq!(template_flag("bin").unwrap(), "--bin");
+        assert_eq!(template_flag("lib").unwrap(), "--lib");
+        assert!(template_flag("proc-macro").is_err());
+    }
+
+    #[test]
+    fn test_scaffold_applies_extras() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut config = CargonewConfig {
+            init_git: false,
+            ..Default::default()
+        };
+        config
+            .dev_dependencies
+            .insert("pretty_assertions".to_string(), "1".to_string());
+
+        let project_dir = scaffold(temp_dir.path(), "demo", "lib", &config)?;
+
+        assert!(project_dir.join("src/lib.rs").is_file());
+        assert!(!project_dir.join(".git").exists());
+        let doc = fs::read_to_string(project_dir.join("Cargo.toml"))?.parse::<DocumentMut>()?;
+        assert_eq!(doc["package"]["name"].as_str(), Some("demo"));
+        assert_eq!(
+            doc["dev-dependencies"]["pretty_assertions"].as_str(),
+            Some("1")
+        );
+        assert_eq!(doc["profile"]["release"]["lto"].as_bool(), Some(true));
+        assert_eq!(
+            doc["profile"]["release"]["codegen-units"].as_integer(),
+            Some(1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scaffold_existing_destination() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir(temp_dir.path().join("demo"))?;
+
+        let error =
+            scaffold(temp_dir.path(), "demo", "bin", &CargonewConfig::default()).unwrap_err();
+        assert!(error.to_string().contains("already exists"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_cargo_extras_keeps_existing_entries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manifest_path = temp_dir.path().join("Cargo.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\nname = \"demo\"\n\n[profile.release]\nlto = \"thin\"\n",
+        )?;
+
+        let changes = apply_cargo_extras(&manifest_path, &CargonewConfig::default())?;
+
+        let doc = fs::read_to_string(&manifest_path)?.parse::<DocumentMut>()?;
+        assert_eq!(doc["profile"]["release"]["lto"].as_str(), Some("thin"));
+        assert_eq!(doc["profile"]["release"]["strip"].as_bool(), Some(true));
+        assert!(!changes.iter().any(|c| c.key() == "profile.release.lto"));
+        Ok(())
+    }
 }
@@ -1,21 +1,356 @@
 use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs;
+use toml_edit::DocumentMut;
 
 use crate::config::*;
+use crate::output::{self, JSON_SCHEMA_VERSION, say};
 
-pub fn show_config(show_path: bool) -> Result<()> {
-    let config_path = get_config_path()?;
+pub fn show_config(show_path: bool, config_args: &ConfigArgs) -> Result<()> {
+    let config_path = get_config_path(config_args)?;
 
     if show_path {
-        println!("📄 Config file: {}", config_path.display());
+        if output::is_json() {
+            println!(
+                "{}",
+                json!({ "version": JSON_SCHEMA_VERSION, "path": config_path })
+            );
+        } else {
+            println!("📄 Config file: {}", config_path.display());
+        }
+        return Ok(());
+    }
+
+    let config = load_config(config_args, None)?;
+    if output::is_json() {
+        let document = json!({ "version": JSON_SCHEMA_VERSION, "config": config });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&document)
+                .with_context(|| "Failed to serialize config")?
+        );
         return Ok(());
     }
 
-    let config = load_config()?;
     let config_str =
         toml::to_string_pretty(&config).with_context(|| "Failed to serialize config")?;
 
-    println!("📄 Current configuration:");
+    say!("📄 Current configuration:");
     println!("{config_str}");
 
     Ok(())
 }
+
+/// How `config set` applies its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigEdit {
+    Replace,
+    Append,
+    Remove,
+}
+
+fn lookup<'a>(root: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.').try_fold(root, |value, part| value.get(part))
+}
+
+fn lookup_mut<'a>(root: &'a mut toml::Value, key: &str) -> Option<&'a mut toml::Value> {
+    key.split('.')
+        .try_fold(root, |value, part| value.get_mut(part))
+}
+
+/// The typed config value at `key`, `None` for an optional key left unset,
+/// with a clear error for keys that don't exist
+///
+/// Keys are resolved against the JSON form of the config, which unlike TOML
+/// keeps unset optional keys, as nulls.
+fn known_value(config: &Config, key: &str) -> Result<Option<toml::Value>> {
+    let schema = serde_json::to_value(config).with_context(|| "Failed to serialize config")?;
+    match key
+        .split('.')
+        .try_fold(&schema, |value, part| value.get(part))
+    {
+        Some(serde_json::Value::Object(_)) => Err(anyhow::anyhow!(
+            "'{key}' is a section; use a dotted key such as '{key}.<name>'"
+        )),
+        Some(serde_json::Value::Null) => Ok(None),
+        Some(_) => {
+            let known =
+                toml::Value::try_from(config).with_context(|| "Failed to serialize config")?;
+            Ok(lookup(&known, key).cloned())
+        }
+        None => Err(anyhow::anyhow!(
+            "Unknown config key '{key}'; run `post-init config` to list keys"
+        )),
+    }
+}
+
+/// Readings of `input` for an optional key that is unset, most specific first;
+/// without a current value to take the type from, the config picks one
+fn untyped_values(key: &str, input: &str, edit: ConfigEdit) -> Result<Vec<toml::Value>> {
+    if edit != ConfigEdit::Replace {
+        return Err(anyhow::anyhow!(
+            "--append and --remove only apply to lists, but '{key}' is not a list"
+        ));
+    }
+    let mut values = Vec::new();
+    if let Ok(number) = input.parse() {
+        values.push(toml::Value::Integer(number));
+    }
+    if let Ok(flag) = input.parse() {
+        values.push(toml::Value::Boolean(flag));
+    }
+    values.push(toml::Value::String(input.to_string()));
+    Ok(values)
+}
+
+/// Check that `config` with `key` set to `value` still deserializes
+fn check_value(config: &Config, key: &str, value: &toml::Value) -> Result<()> {
+    let mut candidate =
+        toml::Value::try_from(config).with_context(|| "Failed to serialize config")?;
+    let (sections, name) = key.rsplit_once('.').unwrap_or(("", key));
+    let parent = if sections.is_empty() {
+        Some(&mut candidate)
+    } else {
+        lookup_mut(&mut candidate, sections)
+    };
+    if let Some(table) = parent.and_then(toml::Value::as_table_mut) {
+        table.insert(name.to_string(), value.clone());
+    }
+    candidate
+        .try_into::<Config>()
+        .map(|_| ())
+        .with_context(|| format!("Invalid value for '{key}': {value}"))
+}
+
+/// Parse `input` as the same type as `current`, applying `edit` to lists
+fn updated_value(
+    key: &str,
+    current: &toml::Value,
+    input: &str,
+    edit: ConfigEdit,
+) -> Result<toml::Value> {
+    let mismatch = |expected: &str| anyhow::anyhow!("'{key}' expects {expected}, got '{input}'");
+
+    match (current, edit) {
+        (toml::Value::Array(items), ConfigEdit::Append) => {
+            let mut items = items.clone();
+            let item = toml::Value::String(input.to_string());
+            if !items.contains(&item) {
+                items.push(item);
+            }
+            Ok(toml::Value::Array(items))
+        }
+        (toml::Value::Array(items), ConfigEdit::Remove) => {
+            let item = toml::Value::String(input.to_string());
+            if !items.contains(&item) {
+                return Err(anyhow::anyhow!("'{input}' is not in '{key}'"));
+            }
+            Ok(toml::Value::Array(
+                items.iter().filter(|v| **v != item).cloned().collect(),
+            ))
+        }
+        (toml::Value::Array(_), ConfigEdit::Replace) => Ok(toml::Value::Array(
+            input
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        )),
+        (_, ConfigEdit::Append | ConfigEdit::Remove) => Err(anyhow::anyhow!(
+            "--append and --remove only apply to lists, but '{key}' is not a list"
+        )),
+        (toml::Value::Boolean(_), _) => input
+            .parse()
+            .map(toml::Value::Boolean)
+            .map_err(|_| mismatch("true or false")),
+        (toml::Value::Integer(_), _) => input
+            .parse()
+            .map(toml::Value::Integer)
+            .map_err(|_| mismatch("an integer")),
+        (toml::Value::String(_), _) => Ok(toml::Value::String(input.to_string())),
+        _ => Err(anyhow::anyhow!(
+            "'{key}' can't be set from the command line"
+        )),
+    }
+}
+
+pub fn get_config(key: &str, config_args: &ConfigArgs) -> Result<()> {
+    let config = load_config(config_args, None)?;
+    let value = known_value(&config, key)?.ok_or_else(|| anyhow::anyhow!("'{key}' is not set"))?;
+
+    if output::is_json() {
+        println!(
+            "{}",
+            json!({ "version": JSON_SCHEMA_VERSION, "key": key, "value": value })
+        );
+    } else {
+        // Strings print bare so the output can be used directly in scripts
+        match &value {
+            toml::Value::String(text) => println!("{text}"),
+            other => println!("{other}"),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn set_config(
+    key: &str,
+    input: &str,
+    edit: ConfigEdit,
+    config_args: &ConfigArgs,
+) -> Result<()> {
+    let config = load_config(config_args, None)?;
+    let value = match known_value(&config, key)? {
+        Some(current) => {
+            let value = updated_value(key, &current, input, edit)?;
+            // Catch values of the right type that the config still rejects, e.g. unknown enum variants
+            check_value(&config, key, &value)?;
+            value
+        }
+        None => untyped_values(key, input, edit)?
+            .into_iter()
+            .find(|value| check_value(&config, key, value).is_ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid value for '{key}': {input}"))?,
+    };
+
+    // Edit the file itself so its layout and `extends` survive
+    let config_path = get_config_path(config_args)?;
+    let content = fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .with_context(|| "Failed to parse config file")?;
+    let (sections, name) = key.rsplit_once('.').unwrap_or(("", key));
+    // The value was computed from the profile-merged config, so it belongs in the profile
+    let profile = config_args
+        .profile
+        .iter()
+        .flat_map(|profile| ["profiles", profile.as_str()]);
+    let mut table = doc.as_table_mut();
+    for section in profile.chain(sections.split('.').filter(|s| !s.is_empty())) {
+        table = table
+            .entry(section)
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("'{section}' in the config file is not a table"))?;
+    }
+    let edit_value = value
+        .to_string()
+        .parse::<toml_edit::Value>()
+        .with_context(|| format!("Failed to convert value for '{key}'"))?;
+    table.insert(name, toml_edit::value(edit_value));
+    save_config(config_args, &doc.to_string())?;
+
+    match &config_args.profile {
+        Some(profile) => say!("✅ {key} = {value} (profile {profile})"),
+        None => say!("✅ {key} = {value}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_known_value() -> Result<()> {
+        let config = Config::default();
+        assert_eq!(
+            known_value(&config, "uvinit.add_hatch_vcs")?,
+            Some(toml::Value::Boolean(true))
+        );
+        assert_eq!(known_value(&config, "uvinit.max_depth")?, None);
+        assert!(known_value(&config, "uvinit").is_err());
+        assert!(known_value(&config, "uvinit.no_such_key").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_untyped_values() -> Result<()> {
+        let config = Config::default();
+        let accepted = |key: &str, input: &str| -> Result<Option<toml::Value>> {
+            Ok(untyped_values(key, input, ConfigEdit::Replace)?
+                .into_iter()
+                .find(|value| check_value(&config, key, value).is_ok()))
+        };
+
+        assert_eq!(
+            accepted("uvinit.max_depth", "3")?,
+            Some(toml::Value::Integer(3))
+        );
+        assert_eq!(
+            accepted("uvinit.python_version", "3")?,
+            Some(toml::Value::String("3".to_string()))
+        );
+        assert_eq!(accepted("uvinit.max_depth", "deep")?, None);
+        assert!(untyped_values("uvinit.license", "MIT", ConfigEdit::Append).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_updated_value() -> Result<()> {
+        let flag = toml::Value::Boolean(true);
+        assert_eq!(
+            updated_value("k", &flag, "false", ConfigEdit::Replace)?,
+            toml::Value::Boolean(false)
+        );
+        assert!(updated_value("k", &flag, "nope", ConfigEdit::Replace).is_err());
+        assert!(updated_value("k", &flag, "false", ConfigEdit::Append).is_err());
+
+        let list = toml::Value::Array(vec![toml::Value::String("a".to_string())]);
+        assert_eq!(
+            updated_value("k", &list, "b", ConfigEdit::Append)?,
+            toml::Value::Array(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(
+            updated_value("k", &list, "a", ConfigEdit::Remove)?,
+            toml::Value::Array(vec![])
+        );
+        assert_eq!(
+            updated_value("k", &list, "x, y", ConfigEdit::Replace)?,
+            toml::Value::Array(vec!["x".into(), "y".into()])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_config_with_profile() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("post-init.toml");
+        fs::write(
+            &config_path,
+            "[uvinit]\nbandit_skips = [\"B101\"]\n\n[profiles.ci.uvinit]\nbandit_skips = [\"B102\"]\n",
+        )?;
+        let mut config_args = ConfigArgs {
+            config_path: Some(config_path.clone()),
+            profile: Some("ci".to_string()),
+            ..Default::default()
+        };
+
+        // The profile's list is appended to, and the base list stays as it was
+        set_config(
+            "uvinit.bandit_skips",
+            "B103",
+            ConfigEdit::Append,
+            &config_args,
+        )?;
+        let config = load_config(&config_args, None)?;
+        assert_eq!(config.uvinit.bandit_skips, ["B102", "B103"]);
+        config_args.profile = None;
+        let config = load_config(&config_args, None)?;
+        assert_eq!(config.uvinit.bandit_skips, ["B101"]);
+
+        set_config(
+            "uvinit.bandit_skips",
+            "B104",
+            ConfigEdit::Append,
+            &config_args,
+        )?;
+        let config = load_config(&config_args, None)?;
+        assert_eq!(config.uvinit.bandit_skips, ["B101", "B104"]);
+        Ok(())
+    }
+}
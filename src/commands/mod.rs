@@ -1,4 +1,8 @@
 pub mod cargonew;
 pub mod config;
+pub mod restore;
+pub mod revert;
 pub mod tuarinew;
+pub mod undo;
 pub mod uvinit;
+pub mod uvnew;
@@ -0,0 +1,308 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backups::{BackupStore, content_hash};
+use crate::commands::uvinit::{discover_pyproject_files, walk_options};
+use crate::config::*;
+use crate::files::{OverwritePolicy, write_atomic};
+use crate::layout::SrcMove;
+use crate::output::{self, say};
+
+/// Which backups a restore run acts on
+#[derive(Debug, Default)]
+pub struct RestoreOptions {
+    /// Restore only this file instead of searching `path`
+    pub file: Option<PathBuf>,
+    /// Print every stored backup and exit
+    pub list: bool,
+    /// Skip confirmation
+    pub yes: bool,
+    pub policy: OverwritePolicy,
+}
+
+/// Whether `file` was edited after the write its backup was taken for
+///
+/// Backups from before written-content hashes were kept fall back to
+/// comparing modification times.
+fn is_edited_since_backup(store: &BackupStore, file: &Path, backup: &Path) -> Result<bool> {
+    if let Some(written) = store.written_hash(file) {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read file: {}", file.display()))?;
+        return Ok(content_hash(&content) != written);
+    }
+    let modified = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("Failed to read modification time: {}", path.display()))
+    };
+    Ok(modified(file)? > modified(backup)?)
+}
+
+/// Put the stored backup back in place of `file` and drop it from the store,
+/// along with a package move made with the write
+fn restore_file(
+    store: &BackupStore,
+    file: &Path,
+    backup: &Path,
+    policy: OverwritePolicy,
+) -> Result<()> {
+    let original = fs::read_to_string(backup)
+        .with_context(|| format!("Failed to read backup: {}", backup.display()))?;
+    let moved = store.moved(file);
+    write_atomic(file, &original)
+        .with_context(|| format!("Failed to restore {}", file.display()))?;
+    if let Some(moved) = moved {
+        restore_move(store, &moved, policy)?;
+    }
+    store.remove(file)
+}
+
+/// Put back the test files rewritten for `moved`, then move the package back
+fn restore_move(store: &BackupStore, moved: &SrcMove, policy: OverwritePolicy) -> Result<()> {
+    for test_file in &moved.rewritten {
+        let Some(backup) = store.find(test_file) else {
+            continue;
+        };
+        if policy != OverwritePolicy::Force && is_edited_since_backup(store, test_file, &backup)? {
+            say!(
+                "  ⏭️  {} was edited after its backup - skipping (use --force to restore anyway)",
+                test_file.display()
+            );
+            continue;
+        }
+        restore_file(store, test_file, &backup, policy)?;
+    }
+
+    if moved.is_in_place() {
+        moved.revert()?;
+    } else {
+        say!(
+            "  ⏭️  {} moved again after its backup - leaving it",
+            moved.to.display()
+        );
+    }
+    Ok(())
+}
+
+fn list_backups(store: &BackupStore) -> Result<()> {
+    let backups = store.list()?;
+    if backups.is_empty() {
+        say!("❌ No backups in {}", store.root().display());
+        return Ok(());
+    }
+
+    say!(
+        "📦 {} backup(s) in {}:",
+        backups.len(),
+        store.root().display()
+    );
+    for backup in &backups {
+        let saved = fs::metadata(&backup.backup)
+            .and_then(|m| m.modified())
+            .map(|time| {
+                chrono::DateTime::<chrono::Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| "unknown time".to_string());
+        say!("  {} (saved {saved})", backup.file.display());
+    }
+    Ok(())
+}
+
+pub fn run_restore(path: &Path, options: &RestoreOptions, config_args: &ConfigArgs) -> Result<()> {
+    restore_in(&BackupStore::open()?, path, options, config_args)
+}
+
+fn restore_in(
+    store: &BackupStore,
+    path: &Path,
+    options: &RestoreOptions,
+    config_args: &ConfigArgs,
+) -> Result<()> {
+    if options.list {
+        return list_backups(store);
+    }
+
+    let files = match &options.file {
+        Some(file) => vec![file.clone()],
+        None => {
+            let config = load_config(config_args, Some("uvinit"))?;
+            say!(
+                "🔍 Searching for pyproject.toml backups in: {}",
+                path.display()
+            );
+            discover_pyproject_files(path, &walk_options(&config.uvinit))?.files
+        }
+    };
+    let backups: Vec<(PathBuf, PathBuf)> = files
+        .into_iter()
+        .filter_map(|file| store.find(&file).map(|backup| (file, backup)))
+        .collect();
+
+    if backups.is_empty() {
+        say!("❌ No backups found.");
+        return Ok(());
+    }
+
+    say!("📦 Found {} backup(s):", backups.len());
+    for (file, _) in &backups {
+        say!("  {}", file.display());
+    }
+
+    if !options.yes
+        && !output::confirm(&format!(
+            "\n🔧 Will restore {} file(s). Continue?",
+            backups.len()
+        ))?
+    {
+        log::info!("Cancelled at confirmation prompt");
+        say!("❌ Cancelled.");
+        return Ok(());
+    }
+
+    say!("\n🔄 Restoring files...");
+    let mut failed = 0;
+    for (file, backup) in &backups {
+        let edited = match options.policy {
+            OverwritePolicy::Force => Ok(false),
+            _ => is_edited_since_backup(store, file, backup),
+        };
+        let result = match edited {
+            Ok(true) => {
+                say!(
+                    "  ⏭️  {} was edited after its backup - skipping (use --force to restore anyway)",
+                    file.display()
+                );
+                continue;
+            }
+            Ok(false) => restore_file(store, file, backup, options.policy),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => {
+                log::info!("Restored {} from {}", file.display(), backup.display());
+                say!("  ✅ {}", file.display());
+            }
+            Err(e) => {
+                log::error!("{e:#}");
+                eprintln!("  ❌ {e:#}");
+                failed += 1;
+            }
+        }
+    }
+
+    // Failures fail the command, so scripts don't take a partial restore for a clean one
+    if failed > 0 {
+        anyhow::bail!("{failed} file(s) could not be restored");
+    }
+    say!("\n🎉 Done!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_restore_after_backup() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = BackupStore::new(temp_dir.path().join("store"));
+        let file = temp_dir.path().join("pyproject.toml");
+        let backup = store.write(&file, "original", "modified")?;
+        fs::write(&file, "modified")?;
+
+        assert!(!is_edited_since_backup(&store, &file, &backup)?);
+        restore_file(&store, &file, &backup, OverwritePolicy::SkipExisting)?;
+
+        assert_eq!(fs::read_to_string(&file)?, "original");
+        assert!(!backup.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_moves_package_back() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path().join("project");
+        let store = BackupStore::new(temp_dir.path().join("store"));
+        let file = project.join("pyproject.toml");
+        let test_file = project.join("tests").join("test_data.py");
+        fs::create_dir_all(project.join("src").join("pkg"))?;
+        fs::create_dir_all(project.join("tests"))?;
+        fs::write(&file, "moved")?;
+        fs::write(&test_file, "DATA = \"src/pkg/data.json\"\n")?;
+
+        let backup = store.write(&file, "flat", "moved")?;
+        store.write(
+            &test_file,
+            "DATA = \"pkg/data.json\"\n",
+            "DATA = \"src/pkg/data.json\"\n",
+        )?;
+        store.record_move(
+            &file,
+            &SrcMove {
+                from: project.join("pkg"),
+                to: project.join("src").join("pkg"),
+                rewritten: vec![test_file.clone()],
+            },
+        )?;
+
+        restore_file(&store, &file, &backup, OverwritePolicy::SkipExisting)?;
+
+        assert_eq!(fs::read_to_string(&file)?, "flat");
+        assert_eq!(
+            fs::read_to_string(&test_file)?,
+            "DATA = \"pkg/data.json\"\n"
+        );
+        assert!(project.join("pkg").is_dir());
+        assert!(!project.join("src").exists());
+        assert!(store.list()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_after_backup_is_detected() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = BackupStore::new(temp_dir.path().join("store"));
+        let file = temp_dir.path().join("pyproject.toml");
+        let backup = store.write(&file, "original", "modified")?;
+        fs::write(&file, "modified")?;
+
+        // Touching the file is not an edit
+        fs::File::options()
+            .write(true)
+            .open(&file)?
+            .set_modified(SystemTime::now() + Duration::from_secs(60))?;
+        assert!(!is_edited_since_backup(&store, &file, &backup)?);
+
+        fs::write(&file, "edited")?;
+        assert!(is_edited_since_backup(&store, &file, &backup)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_restore() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = BackupStore::new(temp_dir.path().join("store"));
+        // Neither file exists, so it can't be compared or written back
+        let file = temp_dir.path().join("gone").join("pyproject.toml");
+        store.write(&file, "original", "modified")?;
+        let mut options = RestoreOptions {
+            file: Some(file.clone()),
+            yes: true,
+            ..Default::default()
+        };
+        let config_args = ConfigArgs::default();
+
+        assert!(restore_in(&store, temp_dir.path(), &options, &config_args).is_err());
+        options.policy = OverwritePolicy::Force;
+        assert!(restore_in(&store, temp_dir.path(), &options, &config_args).is_err());
+        // The backup stays for another try
+        assert!(store.find(&file).is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use toml_edit::{Item, Table};
+
+use crate::commands::uvinit::{
+    Pyproject, Rendered, backup_if_enabled, discover_pyproject_files, insert_at_key,
+    load_pyproject, walk_options,
+};
+use crate::config::*;
+use crate::files::{OverwritePolicy, with_line_endings, write_file};
+use crate::output::{self, say};
+use crate::report::Change;
+
+/// The table at dotted `path` below `table`, if every step is a regular table
+fn table_at<'a>(table: &'a mut Table, path: &[&str]) -> Option<&'a mut Table> {
+    path.iter().try_fold(table, |table, key| {
+        table.get_mut(key).and_then(Item::as_table_mut)
+    })
+}
+
+/// Remove the table at `path` if it is empty, then each parent that becomes empty
+fn remove_empty_tables(table: &mut Table, path: &[&str]) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    if let Some(child) = table.get_mut(first).and_then(Item::as_table_mut) {
+        remove_empty_tables(child, rest);
+        if child.is_empty() {
+            table.remove(first);
+        }
+    }
+}
+
+/// Remove `path.key` when it holds exactly the string uvinit writes there
+fn remove_string(
+    table: &mut Table,
+    path: &[&str],
+    key: &str,
+    expected: &str,
+    changes: &mut Vec<Change>,
+) {
+    let Some(parent) = table_at(table, path) else {
+        return;
+    };
+    if parent.get(key).and_then(|v| v.as_str()) != Some(expected) {
+        return;
+    }
+    parent.remove(key);
+    changes.push(Change::remove(&format!("{}.{key}", path.join("."))));
+    remove_empty_tables(table, path);
+}
+
+/// Drop the entries of the array at `path.key` that are in `values`, and the
+/// array itself once nothing is left
+fn remove_array_values(
+    table: &mut Table,
+    path: &[&str],
+    key: &str,
+    values: &[String],
+    changes: &mut Vec<Change>,
+) {
+    let Some(parent) = table_at(table, path) else {
+        return;
+    };
+    let Some(array) = parent.get_mut(key).and_then(Item::as_array_mut) else {
+        return;
+    };
+
+    let dotted = format!("{}.{key}", path.join("."));
+    let before = array.len();
+    array.retain(|v| {
+        let added = v
+            .as_str()
+            .is_some_and(|s| values.iter().any(|value| value == s));
+        if added && let Some(s) = v.as_str() {
+            changes.push(Change::remove(&format!("{dotted}[\"{s}\"]")));
+        }
+        !added
+    });
+    if array.len() == before {
+        return;
+    }
+
+    if array.is_empty() {
+        parent.remove(key);
+    } else if !array.iter().any(|v| {
+        v.decor()
+            .prefix()
+            .and_then(|p| p.as_str())
+            .is_some_and(|p| p.contains('\n'))
+    }) {
+        // A single-line array may have lost its first entry's leading space
+        array.fmt();
+    }
+    remove_empty_tables(table, path);
+}
+
+/// Undo the edits uvinit makes, touching only values that match what it writes
+pub(crate) fn revert_document(pyproject: Pyproject, config: &UvinitConfig) -> Rendered {
+    let Pyproject {
+        content, mut doc, ..
+    } = pyproject;
+    let mut changes = Vec::new();
+    let root = doc.as_table_mut();
+
+    // 1. Drop project.dynamic = ["version"], restoring a version moved to setuptools_scm
+    let fallback_version = table_at(root, &["tool", "setuptools_scm"])
+        .and_then(|scm| scm.get("fallback_version"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    if let Some(project) = table_at(root, &["project"])
+        && project
+            .get("dynamic")
+            .and_then(|d| d.as_array())
+            .is_some_and(|d| d.len() == 1 && d.iter().any(|v| v.as_str() == Some("version")))
+    {
+        match fallback_version.filter(|_| !project.contains_key("version")) {
+            Some(version) => {
+                insert_at_key(
+                    project,
+                    "dynamic",
+                    "version",
+                    toml_edit::value(&version),
+                    false,
+                );
+                changes.push(Change::remove("project.dynamic"));
+                changes.push(Change::set("project.version", &format!("\"{version}\"")));
+                remove_string(
+                    root,
+                    &["tool", "setuptools_scm"],
+                    "fallback_version",
+                    &version,
+                    &mut changes,
+                );
+            }
+            None => {
+                project.remove("dynamic");
+                changes.push(Change::remove("project.dynamic"));
+            }
+        }
+    }
+
+    // 2. Strip hatch-vcs from build-system.requires
+    remove_array_values(
+        root,
+        &["build-system"],
+        "requires",
+        &["hatch-vcs".to_string()],
+        &mut changes,
+    );
+
+    // 3. Drop tool.hatch.version.source = "vcs"
+    remove_string(
+        root,
+        &["tool", "hatch", "version"],
+        "source",
+        "vcs",
+        &mut changes,
+    );
+
+    // 4. Drop tool.pytest.ini_options.asyncio_mode = "auto"
+    remove_string(
+        root,
+        &["tool", "pytest", "ini_options"],
+        "asyncio_mode",
+        "auto",
+        &mut changes,
+    );
+
+    // 5. Drop the tool.bandit entries uvinit adds
+    remove_array_values(
+        root,
+        &["tool", "bandit"],
+        "skips",
+        &config.bandit_skips,
+        &mut changes,
+    );
+    remove_array_values(
+        root,
+        &["tool", "bandit"],
+        "exclude_dirs",
+        &config.bandit_exclude_dirs,
+        &mut changes,
+    );
+
+    let rendered = with_line_endings(Some(&content), &doc.to_string()).into_owned();
+    Rendered {
+        original: content,
+        content: rendered,
+        changes,
+        warnings: Vec::new(),
+        src_layout_package: None,
+    }
+}
+
+pub fn run_revert(path: &Path, yes: bool, config_args: &ConfigArgs) -> Result<()> {
+    let config = load_config(config_args, Some("uvinit"))?;
+
+    say!(
+        "🔍 Searching for pyproject.toml files in: {}",
+        path.display()
+    );
+    let pyproject_files = discover_pyproject_files(path, &walk_options(&config.uvinit))?.files;
+
+    let mut files_to_revert: Vec<(PathBuf, Rendered)> = Vec::new();
+    let mut failed = 0;
+    for file_path in pyproject_files {
+        match load_pyproject(&file_path) {
+            Ok(pyproject) => {
+                let rendered = revert_document(pyproject, &config.uvinit);
+                if !rendered.changes.is_empty() {
+                    files_to_revert.push((file_path, rendered));
+                }
+            }
+            Err(e) => {
+                log::error!("{e:#}");
+                eprintln!("  ❌ {e:#}");
+                failed += 1;
+            }
+        }
+    }
+
+    if files_to_revert.is_empty() {
+        if failed > 0 {
+            anyhow::bail!("{failed} file(s) could not be read");
+        }
+        say!("✅ No post-init changes found to revert.");
+        return Ok(());
+    }
+
+    say!("📦 Found {} file(s) to revert:", files_to_revert.len());
+    for (file_path, rendered) in &files_to_revert {
+        say!("  {}", file_path.display());
+        for change in &rendered.changes {
+            say!("      {change}");
+        }
+    }
+
+    if !yes
+        && !output::confirm(&format!(
+            "\n🔧 Will revert {} file(s). Continue?",
+            files_to_revert.len()
+        ))?
+    {
+        log::info!("Cancelled at confirmation prompt");
+        say!("❌ Cancelled.");
+        return Ok(());
+    }
+
+    say!("\n🔄 Reverting files...");
+    for (file_path, rendered) in files_to_revert {
+        match backup_if_enabled(&file_path, &config.uvinit, &rendered, None)
+            .and_then(|_| write_file(&file_path, &rendered.content, OverwritePolicy::Force))
+        {
+            Ok(_) => {
+                log::info!("Reverted {}", file_path.display());
+                say!("  ✅ {}", file_path.display());
+            }
+            Err(e) => {
+                log::error!("{e:#}");
+                eprintln!("  ❌ {e:#}");
+                failed += 1;
+            }
+        }
+    }
+
+    // Failures fail the command, so scripts don't take a partial revert for a clean one
+    if failed > 0 {
+        anyhow::bail!("{failed} file(s) could not be read or reverted");
+    }
+    say!("\n🎉 Done!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::uvinit::modify_pyproject_toml;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_revert_undoes_uvinit() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let original = r#"[project]
+name = "test-project"
+version = "0.1.0"
+description = "A test project"
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+"#;
+        fs::write(&test_file, original)?;
+        let config = UvinitConfig {
+            version_key_action: VersionKeyAction::MoveToScm,
+            ..Default::default()
+        };
+        modify_pyproject_toml(&test_file, &config)?;
+
+        let reverted = revert_document(load_pyproject(&test_file)?, &config);
+        assert_eq!(reverted.content, original);
+        Ok(())
+    }
+
+    #[test]
+    fn test_revert_keeps_user_content() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            r#"[project]
+name = "test-project"
+dynamic = ["version", "readme"]
+
+[build-system]
+requires = ["hatchling", "hatch-vcs>=0.4"]
+
+[tool.hatch.version]
+source = "regex"
+
+[tool.pytest.ini_options]
+asyncio_mode = "strict"
+
+[tool.bandit]
+skips = ["B101", "B404"]
+"#,
+        )?;
+
+        let reverted = revert_document(load_pyproject(&test_file)?, &UvinitConfig::default());
+        assert_eq!(
+            reverted.changes,
+            vec![Change::remove("tool.bandit.skips[\"B101\"]")]
+        );
+        assert!(reverted.content.contains("skips = [\"B404\"]"));
+        assert!(reverted.content.contains("hatch-vcs>=0.4"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_revert_fails_on_unreadable_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let config_path = root.join("post-init.toml");
+        // Nothing may reach the real backup store
+        fs::write(&config_path, "[uvinit]\nbackup_before_write = false\n")?;
+        fs::create_dir_all(root.join("good"))?;
+        fs::create_dir_all(root.join("bad"))?;
+        let good = root.join("good/pyproject.toml");
+        fs::write(
+            &good,
+            "[project]\nname = \"good\"\ndynamic = [\"version\"]\n",
+        )?;
+        fs::write(
+            root.join("bad/pyproject.toml"),
+            "[project\nname = \"bad\"\n",
+        )?;
+
+        let config_args = ConfigArgs {
+            config_path: Some(config_path),
+            ..Default::default()
+        };
+        assert!(run_revert(root, true, &config_args).is_err());
+        // The readable file is still reverted
+        assert_eq!(fs::read_to_string(&good)?, "[project]\nname = \"good\"\n");
+        Ok(())
+    }
+}
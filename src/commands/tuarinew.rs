@@ -1,5 +1,91 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
 
-pub fn run_tuarinew(_name: &str, _frontend: &str) -> Result<()> {
-    Err(anyhow::anyhow!("tuarinew command is not implemented yet"))
+use crate::config::*;
+use crate::infer::ProjectInfo;
+use crate::output::say;
+
+/// Frontends create-tauri-app can scaffold, each with a `-ts` variant
+const FRONTENDS: &[&str] = &["vanilla", "react", "vue", "svelte", "solid"];
+
+/// create-tauri-app `--template` value for `frontend`
+fn tauri_template(frontend: &str, use_typescript: bool) -> Result<String> {
+    if !FRONTENDS.contains(&frontend) {
+        return Err(anyhow::anyhow!(
+            "Unknown frontend '{frontend}'. Expected one of: {}",
+            FRONTENDS.join(", ")
+        ));
+    }
+
+    Ok(if use_typescript {
+        format!("{frontend}-ts")
+    } else {
+        frontend.to_string()
+    })
+}
+
+pub fn run_tuarinew(
+    project: &ProjectInfo,
+    frontend: Option<&str>,
+    config_args: &ConfigArgs,
+) -> Result<()> {
+    let config = load_config(config_args, Some("tuarinew"))?;
+    let tuarinew_config = &config.tuarinew;
+
+    let frontend = frontend.unwrap_or(&tuarinew_config.default_frontend);
+    let template = tauri_template(frontend, tuarinew_config.use_typescript)?;
+
+    let project_dir = Path::new(&project.name);
+    if project_dir.exists() {
+        return Err(anyhow::anyhow!(
+            "Destination {} already exists; choose another name or remove it first",
+            project_dir.display()
+        ));
+    }
+
+    say!("📦 Creating Tauri project: {} ({})", project.name, template);
+
+    let status = Command::new("npm")
+        .args(["create", "tauri-app@latest", "--"])
+        .args([&project.name, "--template", &template])
+        .args(["--manager", "npm", "--yes"])
+        .status()
+        .with_context(|| "Failed to run npm; is Node.js installed?")?;
+    log::info!(
+        "npm create tauri-app {} --template {} exited with {}",
+        project.name,
+        template,
+        status
+    );
+    if !status.success() {
+        return Err(anyhow::anyhow!("create-tauri-app failed with {status}"));
+    }
+
+    say!("\n🎉 Done!");
+    say!(
+        "💡 Next: cd {} && npm install && npm run tauri dev",
+        project.name
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tauri_template() -> Result<()> {
+        assert_eq!(tauri_template("vanilla", false)?, "vanilla");
+        assert_eq!(tauri_template("react", true)?, "react-ts");
+        assert_eq!(tauri_template("solid", true)?, "solid-ts");
+
+        let error = tauri_template("angular; rm -rf", true).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("vanilla, react, vue, svelte, solid")
+        );
+        Ok(())
+    }
 }
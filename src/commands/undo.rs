@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use std::fs;
+
+use crate::files::{OverwritePolicy, write_atomic};
+use crate::journal::{Entry, Journal, Run};
+use crate::output::{self, say};
+
+/// Which run an undo acts on
+#[derive(Debug, Default)]
+pub struct UndoOptions {
+    /// Undo this run instead of the latest one
+    pub run: Option<String>,
+    /// Print every recorded run and exit
+    pub list: bool,
+    /// Skip confirmation
+    pub yes: bool,
+    pub policy: OverwritePolicy,
+}
+
+/// Whether the file still holds exactly what the run wrote
+fn is_unchanged(entry: &Entry) -> bool {
+    fs::read_to_string(&entry.path).is_ok_and(|content| content == entry.after)
+}
+
+/// Put back the content the run replaced, deleting files it created
+fn undo_entry(entry: &Entry) -> Result<()> {
+    match &entry.before {
+        Some(before) => write_atomic(&entry.path, before)
+            .with_context(|| format!("Failed to restore {}", entry.path.display())),
+        None => fs::remove_file(&entry.path)
+            .with_context(|| format!("Failed to remove {}", entry.path.display())),
+    }
+}
+
+fn list_runs(journal: &Journal) -> Result<()> {
+    let ids = journal.ids()?;
+    if ids.is_empty() {
+        say!("❌ No runs in {}", journal.root().display());
+        return Ok(());
+    }
+
+    say!("📓 {} run(s) in {}:", ids.len(), journal.root().display());
+    for id in &ids {
+        let files = journal.load(id).map(|run| run.entries.len()).unwrap_or(0);
+        say!("  {id} ({files} file(s))");
+    }
+    Ok(())
+}
+
+pub fn run_undo(options: &UndoOptions) -> Result<()> {
+    undo_in(&Journal::open()?, options)
+}
+
+fn undo_in(journal: &Journal, options: &UndoOptions) -> Result<()> {
+    if options.list {
+        return list_runs(journal);
+    }
+
+    let run: Run = match &options.run {
+        Some(id) => journal.load(id)?,
+        None => match journal.latest()? {
+            Some(run) => run,
+            None => anyhow::bail!("No recorded runs to undo in {}", journal.root().display()),
+        },
+    };
+
+    say!("📓 Run {} wrote {} file(s):", run.id, run.entries.len());
+    for entry in &run.entries {
+        let action = if entry.before.is_some() {
+            "restore"
+        } else {
+            "remove"
+        };
+        say!("  {} ({action})", entry.path.display());
+    }
+    for moved in &run.moves {
+        say!(
+            "  {} (move back to {})",
+            moved.to.display(),
+            moved.from.display()
+        );
+    }
+
+    if !options.yes
+        && !output::confirm(&format!(
+            "\n🔧 Will undo {} file(s) and {} move(s). Continue?",
+            run.entries.len(),
+            run.moves.len()
+        ))?
+    {
+        log::info!("Cancelled at confirmation prompt");
+        say!("❌ Cancelled.");
+        return Ok(());
+    }
+
+    say!("\n🔄 Undoing run {}...", run.id);
+    let mut complete = true;
+    // Later writes may build on earlier ones, so they are undone first
+    for entry in run.entries.iter().rev() {
+        if options.policy != OverwritePolicy::Force && !is_unchanged(entry) {
+            say!(
+                "  ⏭️  {} changed after the run - skipping (use --force to undo anyway)",
+                entry.path.display()
+            );
+            complete = false;
+            continue;
+        }
+
+        match undo_entry(entry) {
+            Ok(()) => {
+                log::info!("Undid {} from run {}", entry.path.display(), run.id);
+                say!("  ✅ {}", entry.path.display());
+            }
+            Err(e) => {
+                log::error!("{e:#}");
+                eprintln!("  ❌ {e:#}");
+                complete = false;
+            }
+        }
+    }
+    // The test files rewritten for a move are entries, so they are back by now
+    for moved in run.moves.iter().rev() {
+        if !moved.is_in_place() {
+            say!(
+                "  ⏭️  {} moved again after the run - skipping",
+                moved.to.display()
+            );
+            complete = false;
+            continue;
+        }
+
+        match moved.revert() {
+            Ok(()) => say!("  ✅ {} -> {}", moved.to.display(), moved.from.display()),
+            Err(e) => {
+                log::error!("{e:#}");
+                eprintln!("  ❌ {e:#}");
+                complete = false;
+            }
+        }
+    }
+
+    // A partial undo fails the command, so scripts don't take it for a clean revert
+    if !complete {
+        anyhow::bail!(
+            "Run {} was only partly undone and stays in the journal",
+            run.id
+        );
+    }
+    journal.remove(&run.id)?;
+    say!("\n🎉 Done!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_entries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let modified = temp_dir.path().join("pyproject.toml");
+        let created = temp_dir.path().join("py.typed");
+        fs::write(&modified, "old")?;
+        let entries = [
+            Entry::before_write(&modified, "new"),
+            Entry::before_write(&created, ""),
+        ];
+        fs::write(&modified, "new")?;
+        fs::write(&created, "")?;
+
+        for entry in &entries {
+            assert!(is_unchanged(entry));
+            undo_entry(entry)?;
+        }
+        assert_eq!(fs::read_to_string(&modified)?, "old");
+        assert!(!created.exists());
+
+        // Edits made after the run are noticed
+        fs::write(&modified, "edited")?;
+        assert!(!is_unchanged(&entries[0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_fails_without_a_complete_revert() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let journal = Journal::new(temp_dir.path().join("journal"));
+        let options = UndoOptions {
+            yes: true,
+            ..Default::default()
+        };
+        assert!(undo_in(&journal, &options).is_err());
+
+        let file = temp_dir.path().join("pyproject.toml");
+        fs::write(&file, "old")?;
+        let run = journal.record(vec![Entry::before_write(&file, "new")], Vec::new())?;
+        fs::write(&file, "edited")?;
+
+        // The skipped file keeps the run in the journal and fails the undo
+        assert!(undo_in(&journal, &options).is_err());
+        assert_eq!(fs::read_to_string(&file)?, "edited");
+        assert_eq!(journal.latest()?, Some(run));
+        Ok(())
+    }
+}
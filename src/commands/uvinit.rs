@@ -1,55 +1,345 @@
 use anyhow::{Context, Result};
-use std::cmp::Ordering;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
-use toml_edit::{Array, DocumentMut, Key};
+use std::process::{Command, ExitCode};
+use std::sync::Mutex;
+use std::time::Duration;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Key, Table};
 
+use crate::annotations;
+use crate::backups::BackupStore;
+use crate::commands::uvnew::module_name;
 use crate::config::*;
+use crate::files::{OverwritePolicy, WriteOutcome, with_line_endings, write_file};
+use crate::git;
+use crate::journal::{self, Journal};
+use crate::layout::{self, SrcMove};
+use crate::legacy;
+use crate::output::{self, say};
+use crate::report::{
+    Change, FileReport, FileStatus, PlanRow, next_steps, render_error_report, render_json,
+    render_markdown, render_plan, render_summary, render_tree,
+};
+use crate::scan_cache::ScanCache;
+use crate::templates;
+use crate::watch;
 
-static REPLACE_KEY_VER: LazyLock<Key> = LazyLock::new(|| Key::new("version"));
-static REPLACE_KEY_DYN: LazyLock<Key> = LazyLock::new(|| Key::new("dynamic"));
+const PYPROJECT_FILE_NAME: &str = "pyproject.toml";
 
-// UV init specific functions
-fn find_pyproject_files<P: AsRef<Path>>(root_dir: P, skip_dirs: &[String]) -> Result<Vec<PathBuf>> {
-    let mut pyproject_files = Vec::new();
-    find_pyproject_files_recursive(root_dir.as_ref(), &mut pyproject_files, skip_dirs)?;
-    Ok(pyproject_files)
+/// Errors of a run, with their files, for `--report` and the exit status
+type RunErrors = Vec<(Option<PathBuf>, String)>;
+
+/// Record an error in `errors` and surface it as an annotation
+fn record_error(errors: &mut RunErrors, file: Option<&Path>, message: &str) {
+    errors.push((file.map(Path::to_path_buf), message.to_string()));
+    annotations::error(file, message);
 }
 
-fn find_pyproject_files_recursive(
-    dir: &Path,
-    files: &mut Vec<PathBuf>,
-    skip_dirs: &[String],
-) -> Result<()> {
-    if !dir.is_dir() {
-        return Ok(());
+/// `--check` exit status when at least one file needs changes
+pub const CHECK_NEEDS_CHANGES: u8 = 1;
+/// Exit status when at least one file could not be checked or processed,
+/// with or without `--check`
+pub const CHECK_FAILED: u8 = 2;
+
+/// Result of walking a directory tree for pyproject.toml files
+#[derive(Debug, Default)]
+pub(crate) struct Discovery {
+    pub(crate) files: Vec<PathBuf>,
+    collisions: Vec<Collision>,
+}
+
+/// A pyproject.toml candidate that was ignored in favor of another file
+#[derive(Debug)]
+struct Collision {
+    path: PathBuf,
+    /// The file processed instead, if any
+    canonical: Option<PathBuf>,
+}
+
+/// Settings controlling how the tree is walked for pyproject.toml files
+#[derive(Debug, Default, Clone)]
+pub(crate) struct WalkOptions {
+    /// Directory names or globs (e.g. `*.egg-info`) never descended into
+    pub(crate) skip_dirs: Vec<String>,
+    /// Walk subdirectories concurrently
+    pub(crate) parallel: bool,
+    /// Skip paths excluded by .gitignore, .ignore and the global git excludes
+    pub(crate) respect_gitignore: bool,
+    /// How many directory levels below the root to search; 0 is the root only
+    pub(crate) max_depth: Option<usize>,
+    /// Descend into symlinked directories
+    pub(crate) follow_symlinks: bool,
+    /// Globs over paths relative to the root; when set, only matching files are kept
+    pub(crate) include: Vec<String>,
+    /// Globs over paths relative to the root for files and directories to leave out
+    pub(crate) exclude: Vec<String>,
+}
+
+/// Walker over `root` that never enters `skip_dirs` or `exclude` matches, keeps
+/// only `include` matches and, if enabled, honors ignore files
+fn walk_builder(root: &Path, options: &WalkOptions) -> Result<WalkBuilder> {
+    let skip_dirs = glob_matcher(&options.skip_dirs, "skip_dirs")?;
+    let include = glob_matcher(&options.include, "include")?;
+    let exclude = glob_matcher(&options.exclude, "exclude")?;
+    let root_dir = root.to_path_buf();
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        // Symlink loops (e.g. node_modules -> repo root) are detected by the
+        // walker and surface as errors instead of recursing forever
+        .follow_links(options.follow_symlinks)
+        .git_ignore(options.respect_gitignore)
+        .git_global(options.respect_gitignore)
+        .git_exclude(options.respect_gitignore)
+        .ignore(options.respect_gitignore)
+        .parents(options.respect_gitignore)
+        .require_git(false)
+        // Files directly in the root are one level below it for the walker
+        .max_depth(options.max_depth.map(|depth| depth + 1))
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            if is_dir && skip_dirs.is_match(entry.file_name()) {
+                log::info!("Skipping {} (matches skip_dirs)", entry.path().display());
+                return false;
+            }
+            let relative = entry.path().strip_prefix(&root_dir).unwrap_or(entry.path());
+            if exclude.is_match(relative) {
+                if is_dir || is_pyproject_candidate(entry) {
+                    log::info!("Skipping {} (matches exclude)", entry.path().display());
+                }
+                return false;
+            }
+            is_dir || include.is_empty() || include.is_match(relative)
+        });
+    Ok(builder)
+}
+
+/// Matcher for the globs of the `setting` option; `*` stops at path separators
+/// while `**` crosses them, and plain names match exactly
+fn glob_matcher(patterns: &[String], setting: &str) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("Invalid {setting} pattern: {pattern}"))?;
+        builder.add(glob);
     }
+    builder
+        .build()
+        .with_context(|| format!("Failed to compile {setting} patterns"))
+}
 
-    let entries = fs::read_dir(dir)
-        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+/// Whether a walked entry is named pyproject.toml in any letter case
+fn is_pyproject_candidate(entry: &DirEntry) -> bool {
+    entry.file_type().is_some_and(|t| t.is_file())
+        && entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.eq_ignore_ascii_case(PYPROJECT_FILE_NAME))
+}
 
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
+fn report_walk_error(error: &ignore::Error) {
+    log::warn!("Skipping during search: {error}");
+    say!("⚠️  Skipping during search: {error}");
+}
 
-        if path.is_file() && path.file_name() == Some("pyproject.toml".as_ref()) {
-            files.push(path);
-        } else if path.is_dir() {
-            if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
-                if !skip_dirs.contains(&dir_name.to_string()) {
-                    find_pyproject_files_recursive(&path, files, skip_dirs)?;
+fn find_pyproject_candidates(root: &Path, options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    for entry in walk_builder(root, options)?.build() {
+        match entry {
+            Ok(entry) if is_pyproject_candidate(&entry) => found.push(entry.into_path()),
+            Ok(_) => {}
+            Err(e) => report_walk_error(&e),
+        }
+    }
+    Ok(found)
+}
+
+/// Like `find_pyproject_candidates`, but directories are walked on multiple threads
+fn find_pyproject_candidates_parallel(root: &Path, options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let found = Mutex::new(Vec::new());
+    walk_builder(root, options)?.build_parallel().run(|| {
+        let found = &found;
+        Box::new(move |entry| {
+            match entry {
+                Ok(entry) if is_pyproject_candidate(&entry) => {
+                    if let Ok(mut found) = found.lock() {
+                        found.push(entry.into_path());
+                    }
                 }
+                Ok(_) => {}
+                Err(e) => report_walk_error(&e),
+            }
+            WalkState::Continue
+        })
+    });
+    Ok(found.into_inner().unwrap_or_default())
+}
+
+// UV init specific functions
+pub(crate) fn discover_pyproject_files<P: AsRef<Path>>(
+    root_dir: P,
+    options: &WalkOptions,
+) -> Result<Discovery> {
+    let root_dir = root_dir.as_ref();
+    let mut discovery = Discovery::default();
+    if !root_dir.is_dir() {
+        return Ok(discovery);
+    }
+
+    let candidates = if options.parallel {
+        find_pyproject_candidates_parallel(root_dir, options)?
+    } else {
+        find_pyproject_candidates(root_dir, options)?
+    };
+
+    // Only the lowercase name is processed; case variants are reported
+    let (mut candidates, variants): (Vec<PathBuf>, Vec<PathBuf>) = candidates
+        .into_iter()
+        .partition(|path| path.file_name().is_some_and(|n| n == PYPROJECT_FILE_NAME));
+    let lowercase: HashSet<PathBuf> = candidates.iter().cloned().collect();
+    for path in variants {
+        let sibling = path.with_file_name(PYPROJECT_FILE_NAME);
+        discovery.collisions.push(Collision {
+            canonical: lowercase.contains(&sibling).then_some(sibling),
+            path,
+        });
+    }
+
+    // Sort so output is stable regardless of read_dir order or thread scheduling
+    candidates.sort();
+
+    // Symlinked directories can surface the same file under several paths
+    let mut seen: HashMap<PathBuf, PathBuf> = HashMap::new();
+    for path in candidates {
+        let real_path = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        match seen.get(&real_path) {
+            Some(first) => discovery.collisions.push(Collision {
+                path,
+                canonical: Some(first.clone()),
+            }),
+            None => {
+                seen.insert(real_path, path.clone());
+                discovery.files.push(path);
             }
         }
     }
+    discovery.collisions.sort_by(|a, b| a.path.cmp(&b.path));
 
-    Ok(())
+    Ok(discovery)
 }
 
-fn has_project_dynamic<P: AsRef<Path>>(file_path: P) -> Result<bool> {
-    let file_path = file_path.as_ref();
+/// The files a run should consider: `path` itself when it names a file (any
+/// `.toml` name is accepted), otherwise every pyproject.toml found below it
+fn discover_targets(path: &Path, options: &WalkOptions) -> Result<Discovery> {
+    if path.is_file() {
+        if path.extension().is_none_or(|ext| ext != "toml") {
+            return Err(anyhow::anyhow!(
+                "{} is not a .toml file; pass a pyproject.toml, another .toml file or a directory",
+                path.display()
+            ));
+        }
+        return Ok(Discovery {
+            files: vec![path.to_path_buf()],
+            ..Default::default()
+        });
+    }
+    if !path.is_dir() {
+        return Err(anyhow::anyhow!(
+            "{} is neither a directory nor a readable file",
+            path.display()
+        ));
+    }
+    discover_pyproject_files(path, options)
+}
+
+/// [`discover_targets`] for every target, keeping the first of any file that
+/// several targets reach
+fn discover_all(targets: &[PathBuf], options: &WalkOptions) -> Result<Discovery> {
+    let mut all = Discovery::default();
+    let mut seen = HashSet::new();
+    for target in targets {
+        let discovery = discover_targets(target, options)?;
+        for file in discovery.files {
+            let real_path = fs::canonicalize(&file).unwrap_or_else(|_| file.clone());
+            if seen.insert(real_path) {
+                all.files.push(file);
+            }
+        }
+        all.collisions.extend(discovery.collisions);
+    }
+    Ok(all)
+}
+
+/// Deepest directory holding every target, where a file target counts as its
+/// parent; reports and git lookups work from it
+fn common_dir(targets: &[PathBuf]) -> PathBuf {
+    let dirs = targets.iter().map(|target| match target.parent() {
+        Some(parent) if target.is_file() => parent.to_path_buf(),
+        _ => target.clone(),
+    });
+    let common = dirs
+        .reduce(|common, dir| {
+            common
+                .components()
+                .zip(dir.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        })
+        .unwrap_or_default();
+    if common.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        common
+    }
+}
+
+/// The pyproject.toml files named in a newline-separated list read from
+/// `source`, or from stdin when it is `-`
+///
+/// Other entries, like the rest of `git diff --name-only` output, and files
+/// that no longer exist are left out.
+pub fn read_file_list(source: &Path) -> Result<Vec<PathBuf>> {
+    let list = if source == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).with_context(|| "Failed to read stdin")?
+    } else {
+        fs::read_to_string(source)
+            .with_context(|| format!("Failed to read file list: {}", source.display()))?
+    };
+    Ok(list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .filter(|file| {
+            let keep = file.file_name().is_some_and(|n| n == PYPROJECT_FILE_NAME) && file.is_file();
+            if !keep {
+                log::info!("Ignoring {} from the file list", file.display());
+            }
+            keep
+        })
+        .collect())
+}
 
+/// A pyproject.toml read and parsed once, then shared by the check and the edit
+pub(crate) struct Pyproject {
+    /// Where the file was read from
+    pub(crate) path: PathBuf,
+    pub(crate) content: String,
+    pub(crate) doc: DocumentMut,
+}
+
+pub(crate) fn load_pyproject(file_path: &Path) -> Result<Pyproject> {
     let content = fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
@@ -57,482 +347,8031 @@ fn has_project_dynamic<P: AsRef<Path>>(file_path: P) -> Result<bool> {
         .parse::<DocumentMut>()
         .with_context(|| format!("Failed to parse TOML in: {}", file_path.display()))?;
 
-    if let Some(project) = doc.get("project") {
-        if let Some(project_table) = project.as_table() {
-            return Ok(project_table.contains_key("dynamic"));
-        }
+    Ok(Pyproject {
+        path: file_path.to_path_buf(),
+        content,
+        doc,
+    })
+}
+
+/// Requirement added to Poetry projects in place of hatch-vcs
+const POETRY_VERSIONING_REQUIRE: &str = "poetry-dynamic-versioning>=1.0.0,<2.0.0";
+const POETRY_BACKEND: &str = "poetry.core.masonry.api";
+const POETRY_VERSIONING_BACKEND: &str = "poetry_dynamic_versioning.backend";
+
+/// Whether Poetry manages the project, i.e. there is a `[tool.poetry]` table
+fn is_poetry_project(doc: &DocumentMut) -> bool {
+    doc.get("tool")
+        .and_then(|tool| tool.get("poetry"))
+        .is_some_and(|poetry| poetry.is_table_like())
+}
+
+/// Whether a Poetry project already derives its version with poetry-dynamic-versioning
+fn poetry_versioning_enabled(doc: &DocumentMut) -> bool {
+    doc.get("tool")
+        .and_then(|tool| tool.get("poetry-dynamic-versioning"))
+        .and_then(|pdv| pdv.get("enable"))
+        .and_then(|enable| enable.as_bool())
+        == Some(true)
+}
+
+/// Comment that opts a pyproject.toml out of uvinit when it is in the leading comments
+const IGNORE_MARKER: &str = "# post-init: ignore";
+
+/// Whether the project opted out of processing, via the marker comment at the
+/// top of the file or `[tool.post-init] ignore = true`
+fn is_opted_out(pyproject: &Pyproject) -> bool {
+    let marked = pyproject
+        .content
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .any(|line| line == IGNORE_MARKER);
+    marked
+        || pyproject
+            .doc
+            .get("tool")
+            .and_then(|tool| tool.get("post-init"))
+            .and_then(|post_init| post_init.get("ignore"))
+            .and_then(Item::as_bool)
+            == Some(true)
+}
+
+/// Whether the version already comes from git, via project.dynamic or Poetry's plugin
+fn has_dynamic_version(doc: &DocumentMut) -> bool {
+    document_has_dynamic(doc) || poetry_versioning_enabled(doc)
+}
+
+fn document_has_dynamic(doc: &DocumentMut) -> bool {
+    doc.get("project")
+        .and_then(|project| project.as_table())
+        .is_some_and(|project_table| project_table.contains_key("dynamic"))
+}
+
+/// Version tools other than the one uvinit sets up that the project already
+/// configures; switching it to git versions would leave two sources of truth
+fn competing_version_sources(
+    file_path: &Path,
+    doc: &DocumentMut,
+    backend: VersionBackend,
+) -> Vec<&'static str> {
+    let tool = doc.get("tool");
+    let has_tool = |name: &str| {
+        tool.and_then(|t| t.get(name))
+            .is_some_and(Item::is_table_like)
+    };
+    let poetry = is_poetry_project(doc);
+    let mut found = Vec::new();
+
+    // A table holding only the fallback_version uvinit records is no other source
+    let scm_settings = tool
+        .and_then(|t| t.get("setuptools_scm"))
+        .and_then(Item::as_table_like)
+        .is_some_and(|scm| scm.iter().any(|(key, _)| key != "fallback_version"));
+    if scm_settings && (poetry || backend != VersionBackend::SetuptoolsScm) {
+        found.push("tool.setuptools_scm");
+    }
+    if has_tool("poetry-dynamic-versioning") && !poetry {
+        found.push("tool.poetry-dynamic-versioning");
+    }
+
+    let dir = file_path.parent().unwrap_or(Path::new("."));
+    let versioneer = has_tool("versioneer")
+        || dir.join("versioneer.py").is_file()
+        || fs::read_to_string(dir.join("setup.cfg"))
+            .is_ok_and(|cfg| cfg.lines().any(|line| line.trim() == "[versioneer]"));
+    if versioneer {
+        found.push("versioneer");
     }
+    found
+}
+
+/// Options for a single uvinit run, collected from the command line
+#[derive(Debug, Default)]
+pub struct UvinitOptions {
+    /// Skip confirmation prompts
+    pub yes: bool,
+    /// Write results to `<file><suffix>` instead of modifying in place
+    pub out_suffix: Option<String>,
+    /// Policy for files written next to the originals
+    pub policy: OverwritePolicy,
+    /// Print results grouped by directory instead of a flat list
+    pub tree: bool,
+    /// Write a Markdown summary of the run to this path
+    pub report_markdown: Option<PathBuf>,
+    /// Write the run's errors as JSON to this path
+    pub report: Option<PathBuf>,
+    /// Only fill missing build pieces in files that already have project.dynamic
+    pub fix_requires_only: bool,
+    /// Stage written files with `git add`
+    pub git_add: bool,
+    /// Overrides the configured git_commit when set
+    pub git_commit: bool,
+    /// Overrides the configured create_version_tag when set
+    pub tag: bool,
+    /// Overrides the configured enable_publish_workflow when set
+    pub publish_workflow: bool,
+    /// Overrides the configured enable_dockerfile when set
+    pub dockerfile: bool,
+    /// Overrides the configured enable_devcontainer when set
+    pub devcontainer: bool,
+    /// Overrides the configured migrate_src_layout when set
+    pub migrate_src_layout: bool,
+    /// Overrides the configured enable_py_typed when set
+    pub py_typed: bool,
+    /// Overrides the configured import_requirements when set
+    pub import_requirements: bool,
+    /// Overrides the configured migrate_setup when set
+    pub migrate_setup: bool,
+    /// Overrides the configured enable_workspace_sources when set
+    pub workspace_sources: bool,
+    /// Overrides the configured type_checker
+    pub type_checker: Option<TypeChecker>,
+    /// Overrides the configured dependency_updates
+    pub dependency_updates: Option<DependencyUpdates>,
+    /// Overrides the configured enable_python_version_file when set
+    pub python_version_file: bool,
+    /// Overrides the configured python_version, enabling the file
+    pub python_version: Option<String>,
+    /// Overrides the configured validate
+    pub validate: Option<Validation>,
+    /// Replace an existing non-vcs version source
+    pub override_version_source: bool,
+    /// Process files that already have project.dynamic again, and files
+    /// whose version comes from another backend
+    pub reprocess: bool,
+    /// Overrides the configured version_key_action
+    pub version_key_action: Option<VersionKeyAction>,
+    /// Overrides the configured version_backend
+    pub version_backend: Option<VersionBackend>,
+    /// Overrides the configured parallel_walk
+    pub parallel_walk: Option<bool>,
+    /// Descend into directories excluded by ignore files
+    pub no_ignore: bool,
+    /// Skip the scan cache
+    pub no_cache: bool,
+    /// Overrides the configured max_depth
+    pub max_depth: Option<usize>,
+    /// Overrides the configured follow_symlinks
+    pub follow_symlinks: Option<bool>,
+    /// Overrides the configured include patterns when not empty
+    pub include: Vec<String>,
+    /// Overrides the configured exclude patterns when not empty
+    pub exclude: Vec<String>,
+    /// Only consider files with uncommitted changes
+    pub dirty: bool,
+    /// Print a follow-up checklist based on what changed
+    pub next_steps: bool,
+    /// Print a unified diff per file instead of writing anything
+    pub dry_run: bool,
+    /// Like `dry_run`, but only list files and report the outcome in the exit status
+    pub check: bool,
+    /// Feature toggles overriding the config file
+    pub features: UvinitFeatureArgs,
+}
 
-    Ok(false)
+/// Transformed pyproject.toml content and the changes that produced it
+pub(crate) struct Rendered {
+    /// File content before any change
+    pub(crate) original: String,
+    pub(crate) content: String,
+    pub(crate) changes: Vec<Change>,
+    /// Things deliberately left alone that the user should know about
+    pub(crate) warnings: Vec<String>,
+    /// Flat-layout package to move under src/ when the content is written
+    pub(crate) src_layout_package: Option<String>,
 }
 
-fn modify_pyproject_toml<P: AsRef<Path>>(file_path: P, config: &UvinitConfig) -> Result<()> {
+pub(crate) fn modify_pyproject_toml<P: AsRef<Path>>(
+    file_path: P,
+    config: &UvinitConfig,
+) -> Result<Rendered> {
     let file_path = file_path.as_ref();
+    let rendered = render_pyproject_toml(file_path, config)?;
 
-    let content = fs::read_to_string(file_path)
-        .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+    // The processing confirmation already covers in-place edits of pyproject.toml
+    write_file(file_path, &rendered.content, OverwritePolicy::Force)?;
 
-    let mut doc = content
-        .parse::<DocumentMut>()
-        .with_context(|| "Failed to parse TOML document")?;
+    Ok(rendered)
+}
 
-    // 1. Replace project.version with project.dynamic = ["version"]
-    if config.enable_dynamic_version {
-        if let Some(project) = doc.get_mut("project") {
-            if let Some(project_table) = project.as_table_mut() {
-                let mut dynamic_array = Array::new();
-                dynamic_array.push("version");
-                project_table.insert("dynamic", toml_edit::value(dynamic_array));
-
-                project_table.sort_values_by(|key1, _, key2, _| {
-                    if key1 == &*REPLACE_KEY_DYN && key2 != &*REPLACE_KEY_VER {
-                        Ordering::Less
-                    } else {
-                        Ordering::Equal
-                    }
-                });
+/// Normalized PEP 508 package name of a requirement, e.g. `Hatch_VCS>=0.3` -> `hatch-vcs`
+fn requirement_name(req: &str) -> String {
+    let name = req
+        .trim()
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .next()
+        .unwrap_or_default();
 
-                project_table.remove("version");
+    // PEP 503 normalization: runs of `-`, `_` and `.` are equivalent
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
             }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
         }
     }
+    normalized
+}
+
+/// Whether the array puts each entry on its own line
+fn is_multiline_array(array: &Array) -> bool {
+    array.iter().any(|v| {
+        v.decor()
+            .prefix()
+            .and_then(|p| p.as_str())
+            .is_some_and(|p| p.contains('\n'))
+    })
+}
+
+/// Append `value` to `array`, keeping its existing single-line or multiline layout
+fn push_preserving_style(array: &mut Array, value: &str) {
+    if !is_multiline_array(array) {
+        array.push(value);
+        return;
+    }
 
-    // 2. Add to build-system.requires
-    if config.add_hatch_vcs || !config.additional_requires.is_empty() {
-        let mut requires_to_add = Vec::new();
+    let indent = array
+        .iter()
+        .last()
+        .and_then(|v| v.decor().prefix())
+        .and_then(|p| p.as_str())
+        .unwrap_or("\n    ")
+        .to_string();
 
-        if config.add_hatch_vcs {
-            requires_to_add.push("hatch-vcs");
-        }
+    // Without a trailing comma the whitespace before `]` sits in the last
+    // entry's suffix; move it after the new entry
+    let closing = array
+        .iter_mut()
+        .last()
+        .and_then(|v| {
+            let suffix = v
+                .decor()
+                .suffix()
+                .and_then(|s| s.as_str())
+                .map(str::to_string);
+            v.decor_mut().set_suffix("");
+            suffix
+        })
+        .unwrap_or_default();
+
+    let mut new_value = toml_edit::Value::from(value);
+    new_value.decor_mut().set_prefix(indent);
+    array.push_formatted(new_value);
+
+    let trailing = format!("{closing}{}", array.trailing().as_str().unwrap_or_default());
+    array.set_trailing(trailing);
+}
 
-        for req in &config.additional_requires {
-            requires_to_add.push(req.as_str());
+/// How [`merge_requirement`] changed a requirements array
+#[derive(Debug, PartialEq, Eq)]
+enum RequirementMerge {
+    Added,
+    /// A bare requirement, kept here, gave way to a pinned one
+    Replaced(String),
+}
+
+impl RequirementMerge {
+    /// Report entry for merging `req` into the array at `key`
+    fn change(self, key: &str, req: &str) -> Change {
+        match self {
+            RequirementMerge::Added => Change::append(key, req),
+            RequirementMerge::Replaced(old) => Change::replace(key, &old, req),
         }
+    }
+}
 
-        if let Some(build_system) = doc.get_mut("build-system") {
-            if let Some(build_system_table) = build_system.as_table_mut() {
-                let requires = build_system_table
-                    .entry("requires")
-                    .or_insert(toml_edit::value(Array::new()));
+/// Add `req` unless a requirement for the same package exists; a pinned
+/// requirement replaces an existing bare one. Returns `None` when the array
+/// was left alone.
+fn merge_requirement(requires: &mut Array, req: &str) -> Option<RequirementMerge> {
+    let name = requirement_name(req);
+    let is_bare = |r: &str| {
+        r.trim()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
 
-                if let Some(requires_array) = requires.as_array_mut() {
-                    for req in requires_to_add {
-                        let has_req = requires_array.iter().any(|v| v.as_str() == Some(req));
+    let existing = requires
+        .iter()
+        .position(|v| v.as_str().map(requirement_name).as_deref() == Some(name.as_str()));
 
-                        if !has_req {
-                            requires_array.push(req);
-                        }
-                    }
-                }
+    match existing {
+        Some(index) => {
+            let existing_req = requires
+                .get(index)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            if is_bare(&existing_req) && !is_bare(req) {
+                requires.replace(index, req);
+                return Some(RequirementMerge::Replaced(existing_req));
             }
+            None
+        }
+        None => {
+            push_preserving_style(requires, req);
+            Some(RequirementMerge::Added)
         }
     }
+}
 
-    // 3. Add tool.hatch.version.source = "vcs"
-    if config.enable_dynamic_version {
-        if doc.get("tool").is_none() {
-            doc.insert("tool", toml_edit::table());
-        }
+fn proposed_path(file_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    file_path.with_file_name(name)
+}
 
-        if let Some(tool) = doc.get_mut("tool") {
-            if let Some(tool_table) = tool.as_table_mut() {
-                tool_table.set_implicit(true);
-                if tool_table.get("hatch").is_none() {
-                    tool_table.insert("hatch", toml_edit::table());
-                }
+/// Build requirements that derive the version from git for `doc` under `backend`
+fn version_requires(doc: &DocumentMut, backend: VersionBackend) -> &'static [&'static str] {
+    if is_poetry_project(doc) {
+        return &[POETRY_VERSIONING_REQUIRE];
+    }
+    match backend {
+        VersionBackend::HatchVcs => &["hatch-vcs"],
+        VersionBackend::SetuptoolsScm => &["setuptools>=64", "setuptools-scm>=8"],
+        VersionBackend::Pdm => &["pdm-backend"],
+    }
+}
 
-                if let Some(hatch) = tool_table.get_mut("hatch") {
-                    if let Some(hatch_table) = hatch.as_table_mut() {
-                        hatch_table.set_implicit(true);
-                        if hatch_table.get("version").is_none() {
-                            hatch_table.insert("version", toml_edit::table());
-                        }
-
-                        if let Some(version) = hatch_table.get_mut("version") {
-                            if let Some(version_table) = version.as_table_mut() {
-                                version_table.set_implicit(true);
-                                version_table.insert("source", toml_edit::value("vcs"));
-                            }
-                        }
-                    }
+/// build-backend the version plugin belongs to
+fn build_backend(backend: VersionBackend) -> &'static str {
+    match backend {
+        VersionBackend::HatchVcs => "hatchling.build",
+        VersionBackend::SetuptoolsScm => "setuptools.build_meta",
+        VersionBackend::Pdm => "pdm.backend",
+    }
+}
+
+/// Add the version plugin (when `add_hatch_vcs` is set) and the additional requires
+fn add_build_requires(doc: &mut DocumentMut, config: &UvinitConfig, changes: &mut Vec<Change>) {
+    if !config.add_hatch_vcs && config.additional_requires.is_empty() {
+        return;
+    }
+
+    let mut requires_to_add = Vec::new();
+
+    if config.add_hatch_vcs {
+        requires_to_add.extend(version_requires(doc, config.version_backend));
+    }
+
+    for req in &config.additional_requires {
+        requires_to_add.push(req.as_str());
+    }
+
+    if let Some(build_system) = doc.get_mut("build-system")
+        && let Some(build_system_table) = build_system.as_table_mut()
+    {
+        let requires = build_system_table
+            .entry("requires")
+            .or_insert(toml_edit::value(Array::new()));
+
+        if let Some(requires_array) = requires.as_array_mut() {
+            for req in requires_to_add {
+                if let Some(merge) = merge_requirement(requires_array, req) {
+                    changes.push(merge.change("build-system.requires", req));
                 }
             }
         }
     }
+}
 
-    // 4. Add tool.pytest.ini_options.asyncio_mode = "auto"
-    if config.enable_pytest_asyncio {
-        if doc.get("tool").is_none() {
-            doc.insert("tool", toml_edit::table());
+/// Set tool.hatch.version.source = "vcs"; with `overwrite` false an existing source is kept
+///
+/// Returns the existing source when it was kept despite not being "vcs".
+fn set_version_source(
+    doc: &mut DocumentMut,
+    overwrite: bool,
+    changes: &mut Vec<Change>,
+) -> Option<String> {
+    let version_table = table_path_mut(doc.as_table_mut(), &["tool", "hatch", "version"])?;
+    let current = version_table.get("source").and_then(|v| v.as_str());
+    if current == Some("vcs") {
+        return None;
+    }
+    if let Some(current) = current
+        && !overwrite
+    {
+        return Some(current.to_string());
+    }
+    changes.push(Change::set("tool.hatch.version.source", "\"vcs\""));
+    version_table.insert("source", toml_edit::value("vcs"));
+    None
+}
+
+/// Path of the version file hatch-vcs writes for `package`
+fn version_file(package: &str) -> String {
+    format!("{package}/_version.py")
+}
+
+/// Add `[tool.hatch.build.hooks.vcs]` with a version-file inside `package`,
+/// keeping a version-file that is already configured
+fn set_version_file_hook(doc: &mut DocumentMut, package: &str, changes: &mut Vec<Change>) {
+    if let Some(vcs_hook) = table_path_mut(
+        doc.as_table_mut(),
+        &["tool", "hatch", "build", "hooks", "vcs"],
+    ) {
+        insert_missing(
+            vcs_hook,
+            "tool.hatch.build.hooks.vcs.version-file",
+            version_file(package).as_str().into(),
+            changes,
+        );
+    }
+}
+
+/// Fill in the configured hatch-vcs fallback-version and raw-options
+fn set_hatch_vcs_options(doc: &mut DocumentMut, config: &UvinitConfig, changes: &mut Vec<Change>) {
+    let Some(version_table) = doc
+        .get_mut("tool")
+        .and_then(|t| t.get_mut("hatch"))
+        .and_then(|h| h.get_mut("version"))
+        .and_then(Item::as_table_mut)
+    else {
+        return;
+    };
+
+    if let Some(fallback) = &config.hatch_vcs_fallback_version {
+        insert_missing(
+            version_table,
+            "tool.hatch.version.fallback-version",
+            fallback.as_str().into(),
+            changes,
+        );
+    }
+    if !config.hatch_vcs_raw_options.is_empty()
+        && let Some(raw_options) = version_table
+            .entry("raw-options")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+    {
+        merge_missing(
+            raw_options,
+            "tool.hatch.version.raw-options",
+            &config.hatch_vcs_raw_options,
+            changes,
+        );
+    }
+}
+
+/// Set build-system.build-backend for `backend` when it is missing
+///
+/// Returns a different backend that was already set, which is kept.
+fn set_build_backend(
+    doc: &mut DocumentMut,
+    backend: VersionBackend,
+    changes: &mut Vec<Change>,
+) -> Option<String> {
+    let build_system_table = doc.get_mut("build-system")?.as_table_mut()?;
+    let wanted = build_backend(backend);
+    match build_system_table
+        .get("build-backend")
+        .and_then(|b| b.as_str())
+    {
+        Some(current) if current == wanted => None,
+        Some(current) => Some(current.to_string()),
+        None => {
+            build_system_table.insert("build-backend", toml_edit::value(wanted));
+            changes.push(Change::set(
+                "build-system.build-backend",
+                &format!("\"{wanted}\""),
+            ));
+            None
         }
+    }
+}
 
-        if let Some(tool) = doc.get_mut("tool") {
-            if let Some(tool_table) = tool.as_table_mut() {
-                tool_table.set_implicit(true);
-                if tool_table.get("pytest").is_none() {
-                    tool_table.insert("pytest", toml_edit::table());
-                }
+/// Point the backend's tool table at git: tool.setuptools_scm for
+/// setuptools-scm, tool.pdm.version.source = "scm" for pdm
+fn set_scm_tool_table(doc: &mut DocumentMut, backend: VersionBackend, changes: &mut Vec<Change>) {
+    let Some(tool_table) = table_path_mut(doc.as_table_mut(), &["tool"]) else {
+        return;
+    };
 
-                if let Some(pytest) = tool_table.get_mut("pytest") {
-                    if let Some(pytest_table) = pytest.as_table_mut() {
-                        pytest_table.set_implicit(true);
-                        if pytest_table.get("ini_options").is_none() {
-                            pytest_table.insert("ini_options", toml_edit::table());
-                        }
-
-                        if let Some(ini_options) = pytest_table.get_mut("ini_options") {
-                            if let Some(ini_options_table) = ini_options.as_table_mut() {
-                                ini_options_table.set_implicit(true);
-                                ini_options_table.insert("asyncio_mode", toml_edit::value("auto"));
-                            }
-                        }
-                    }
-                }
+    match backend {
+        VersionBackend::HatchVcs => {}
+        VersionBackend::SetuptoolsScm => {
+            // The table's presence is what turns setuptools-scm on
+            if !tool_table.contains_key("setuptools_scm") {
+                tool_table.insert("setuptools_scm", toml_edit::table());
+                changes.push(Change::set("tool.setuptools_scm", "{}"));
+            }
+        }
+        VersionBackend::Pdm => {
+            if let Some(version_table) = table_path_mut(tool_table, &["pdm", "version"]) {
+                insert_missing(
+                    version_table,
+                    "tool.pdm.version.source",
+                    "scm".into(),
+                    changes,
+                );
             }
         }
     }
+}
 
-    // 5. Add tool.bandit
-    if config.enable_bandit {
-        if doc.get("tool").is_none() {
-            doc.insert("tool", toml_edit::table());
-        }
+/// Enable poetry-dynamic-versioning: its build backend and
+/// `[tool.poetry-dynamic-versioning] enable = true`
+///
+/// PEP 621 projects (Poetry 2) also need a placeholder tool.poetry.version.
+fn set_poetry_versioning(doc: &mut DocumentMut, changes: &mut Vec<Change>) {
+    if let Some(build_system) = doc.get_mut("build-system")
+        && let Some(build_system_table) = build_system.as_table_mut()
+        && build_system_table
+            .get("build-backend")
+            .and_then(|b| b.as_str())
+            == Some(POETRY_BACKEND)
+    {
+        build_system_table.insert("build-backend", toml_edit::value(POETRY_VERSIONING_BACKEND));
+        changes.push(Change::set(
+            "build-system.build-backend",
+            &format!("\"{POETRY_VERSIONING_BACKEND}\""),
+        ));
+    }
 
-        if let Some(tool) = doc.get_mut("tool") {
-            if let Some(tool_table) = tool.as_table_mut() {
-                tool_table.set_implicit(true);
-                if tool_table.get("bandit").is_none() {
-                    tool_table.insert("bandit", toml_edit::table());
-                }
+    let has_dynamic = document_has_dynamic(doc);
+    if let Some(tool) = doc.get_mut("tool")
+        && let Some(tool_table) = tool.as_table_mut()
+    {
+        if has_dynamic
+            && let Some(poetry_table) = tool_table.get_mut("poetry").and_then(Item::as_table_mut)
+        {
+            insert_missing(poetry_table, "tool.poetry.version", "0.0.0".into(), changes);
+        }
 
-                if let Some(bandit) = tool_table.get_mut("bandit") {
-                    if let Some(bandit_table) = bandit.as_table_mut() {
-                        // Add skips = ["B101"]
-                        let skips_to_add = vec!["B101"];
-                        let skips = bandit_table
-                            .entry("skips")
-                            .or_insert(toml_edit::value(Array::new()));
-
-                        if let Some(skips_array) = skips.as_array_mut() {
-                            for skip in skips_to_add {
-                                let has_skip = skips_array.iter().any(|v| v.as_str() == Some(skip));
-
-                                if !has_skip {
-                                    skips_array.push(skip);
-                                }
-                            }
-                        }
-
-                        // Add exclude_dirs = ["tests", "venv"]
-                        let exclude_dirs_to_add = vec![".venv", "venv", "tests"];
-                        let exclude_dirs = bandit_table
-                            .entry("exclude_dirs")
-                            .or_insert(toml_edit::value(Array::new()));
-
-                        if let Some(exclude_dirs_array) = exclude_dirs.as_array_mut() {
-                            for exclude_dir in exclude_dirs_to_add {
-                                let has_exclude_dir = exclude_dirs_array
-                                    .iter()
-                                    .any(|v| v.as_str() == Some(exclude_dir));
-
-                                if !has_exclude_dir {
-                                    exclude_dirs_array.push(exclude_dir);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        if let Some(pdv_table) = tool_table
+            .entry("poetry-dynamic-versioning")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+        {
+            insert_missing(
+                pdv_table,
+                "tool.poetry-dynamic-versioning.enable",
+                true.into(),
+                changes,
+            );
         }
     }
+}
 
-    fs::write(file_path, doc.to_string())
-        .with_context(|| format!("Failed to write file: {}", file_path.display()))?;
+/// The table at dotted `path` below `table`, creating missing tables as
+/// implicit ones; `None` when a step holds something other than a table
+fn table_path_mut<'a>(table: &'a mut Table, path: &[&str]) -> Option<&'a mut Table> {
+    path.iter().try_fold(table, |table, key| {
+        let child = table
+            .entry(key)
+            .or_insert(toml_edit::table())
+            .as_table_mut()?;
+        child.set_implicit(true);
+        Some(child)
+    })
+}
 
-    Ok(())
+/// Put `key` in the slot `slot` occupies (just before it when `keep_slot` is set),
+/// leaving every other key where it was
+///
+/// An existing `key` is updated in place instead.
+pub(crate) fn insert_at_key(table: &mut Table, slot: &str, key: &str, item: Item, keep_slot: bool) {
+    let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+    let slot_index = keys.iter().position(|k| k == slot);
+
+    let Some(index) = slot_index.filter(|_| !table.contains_key(key)) else {
+        table.insert(key, item);
+        if !keep_slot {
+            table.remove(slot);
+        }
+        return;
+    };
+
+    // Tables only append, so lift out everything after the slot and put it back afterwards
+    let start = if keep_slot { index } else { index + 1 };
+    let moved: Vec<(Key, Item)> = keys[start..]
+        .iter()
+        .filter_map(|k| table.remove_entry(k))
+        .collect();
+
+    let mut new_key = Key::new(key);
+    if !keep_slot && let Some((slot_key, _)) = table.remove_entry(slot) {
+        // Comments above the replaced line now describe the new key
+        *new_key.leaf_decor_mut() = slot_key.leaf_decor().clone();
+    }
+    table.insert_formatted(&new_key, item);
+    for (key, item) in moved {
+        table.insert_formatted(&key, item);
+    }
 }
 
-pub fn run_uvinit(path: &Path, yes: bool) -> Result<()> {
-    let config = load_config()?;
-    let uvinit_config = &config.uvinit;
+/// Set the last part of dotted `key` in `table` unless the user already set it
+fn insert_missing(
+    table: &mut Table,
+    key: &str,
+    value: toml_edit::Value,
+    changes: &mut Vec<Change>,
+) {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    if table.contains_key(name) {
+        return;
+    }
+    changes.push(Change::set(key, &value.to_string()));
+    table.insert(name, toml_edit::value(value));
+}
 
-    println!(
-        "🔍 Searching for pyproject.toml files in: {}",
-        path.display()
-    );
+/// Merge the configured `tool.pytest.ini_options` entries: arrays gain the
+/// items they lack, other values are only set when the key is absent
+fn merge_pytest_ini_options(
+    doc: &mut DocumentMut,
+    options: &toml::Table,
+    changes: &mut Vec<Change>,
+) {
+    let Some(ini_options) = table_path_mut(doc.as_table_mut(), &["tool", "pytest", "ini_options"])
+    else {
+        return;
+    };
 
-    let pyproject_files = find_pyproject_files(path, &uvinit_config.skip_dirs)?;
+    for (key, value) in options {
+        let dotted = format!("tool.pytest.ini_options.{key}");
+        let Ok(value) = value.to_string().parse::<toml_edit::Value>() else {
+            continue;
+        };
+        match (ini_options.get_mut(key).and_then(Item::as_array_mut), value) {
+            (Some(existing), toml_edit::Value::Array(wanted)) => {
+                append_unique(existing, &dotted, &wanted, changes);
+            }
+            (_, value) => insert_missing(ini_options, &dotted, value, changes),
+        }
+    }
+}
 
-    if pyproject_files.is_empty() {
-        println!("❌ No pyproject.toml files found.");
-        return Ok(());
+/// Append the items of `wanted` that `array` lacks, recording each under `dotted`
+fn append_unique(array: &mut Array, dotted: &str, wanted: &Array, changes: &mut Vec<Change>) {
+    for item in wanted.iter() {
+        let item_repr = item.to_string();
+        if array
+            .iter()
+            .any(|v| v.to_string().trim() == item_repr.trim())
+        {
+            continue;
+        }
+        match item.as_str() {
+            Some(s) => push_preserving_style(array, s),
+            None => array.push(item.clone()),
+        }
+        changes.push(Change::append(
+            dotted,
+            item.as_str().unwrap_or(item_repr.trim()),
+        ));
     }
+}
 
-    println!("📦 Found {} pyproject.toml file(s):", pyproject_files.len());
+/// `item` as a plain TOML value, for comparing content regardless of formatting
+fn item_as_toml(item: &Item) -> Option<toml::Value> {
+    let mut doc = DocumentMut::new();
+    doc.insert("value", item.clone());
+    toml::from_str::<toml::Table>(&doc.to_string())
+        .ok()?
+        .remove("value")
+}
 
-    let mut files_to_process = Vec::new();
+/// Apply one `[[uvinit.patches]]` rule, creating the tables on its path
+fn apply_patch(
+    doc: &mut DocumentMut,
+    patch: &Patch,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let segments: Vec<&str> = patch.key.split('.').map(str::trim).collect();
+    let (Some((name, parents)), false) = (segments.split_last(), segments.contains(&"")) else {
+        warnings.push(format!("ignored patch with invalid key \"{}\"", patch.key));
+        return;
+    };
 
-    for file_path in &pyproject_files {
-        println!("  {}", file_path.display());
+    let mut table = doc.as_table_mut();
+    for (i, segment) in parents.iter().enumerate() {
+        let Some(child) = table
+            .entry(segment)
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+        else {
+            warnings.push(format!(
+                "ignored patch for {}: {} is not a table",
+                patch.key,
+                parents[..=i].join(".")
+            ));
+            return;
+        };
+        child.set_implicit(true);
+        table = child;
+    }
 
-        match has_project_dynamic(file_path) {
-            Ok(true) => {
-                println!("    ✅ Has project.dynamic - skipping");
+    let Ok(value) = patch.value.to_string().parse::<toml_edit::Value>() else {
+        return;
+    };
+    match patch.strategy {
+        PatchStrategy::SetIfMissing => match &patch.value {
+            toml::Value::Table(values) => {
+                if let Some(child) = table
+                    .entry(name)
+                    .or_insert(toml_edit::table())
+                    .as_table_mut()
+                {
+                    merge_missing(child, &patch.key, values, changes);
+                }
             }
-            Ok(false) => {
-                println!("    ⚠️  No project.dynamic - needs processing");
-                files_to_process.push(file_path);
+            _ => insert_missing(table, &patch.key, value, changes),
+        },
+        PatchStrategy::AppendUnique => {
+            let wanted = match value {
+                toml_edit::Value::Array(wanted) => wanted,
+                single => Array::from_iter([single]),
+            };
+            match table.get_mut(name) {
+                None => {
+                    changes.push(Change::set(&patch.key, &wanted.to_string()));
+                    table.insert(name, toml_edit::value(wanted));
+                }
+                Some(item) => match item.as_array_mut() {
+                    Some(array) => append_unique(array, &patch.key, &wanted, changes),
+                    None => warnings.push(format!(
+                        "ignored append-unique patch: {} is not an array",
+                        patch.key
+                    )),
+                },
             }
-            Err(e) => {
-                eprintln!("    ❌ Error checking {}: {}", file_path.display(), e);
+        }
+        PatchStrategy::Overwrite => {
+            if table.get(name).and_then(item_as_toml).as_ref() == Some(&patch.value) {
+                return;
+            }
+            match &patch.value {
+                toml::Value::Table(values) => {
+                    if table.contains_key(name) {
+                        changes.push(Change::remove(&patch.key));
+                    }
+                    let mut child = Table::new();
+                    merge_missing(&mut child, &patch.key, values, changes);
+                    table.insert(name, Item::Table(child));
+                }
+                _ => {
+                    changes.push(Change::set(&patch.key, &value.to_string()));
+                    table.insert(name, toml_edit::value(value));
+                }
             }
         }
     }
+}
 
-    if files_to_process.is_empty() {
-        println!("✅ All files already have project.dynamic configured!");
-        return Ok(());
+/// Add a `[[tool.mypy.overrides]]` entry per configured module pattern, skipping
+/// patterns that already have one
+fn add_mypy_overrides(
+    mypy_table: &mut Table,
+    overrides: &BTreeMap<String, toml::Table>,
+    changes: &mut Vec<Change>,
+) {
+    if overrides.is_empty() {
+        return;
     }
+    let Some(entries) = mypy_table
+        .entry("overrides")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+    else {
+        return;
+    };
 
-    if !yes {
-        println!(
-            "\n🔧 Will modify {} file(s). Continue? (y/N)",
-            files_to_process.len()
-        );
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        if !input.trim().to_lowercase().starts_with('y') {
-            println!("❌ Cancelled.");
-            return Ok(());
+    for (module, settings) in overrides {
+        let exists = entries
+            .iter()
+            .any(|entry| entry.get("module").and_then(|m| m.as_str()) == Some(module));
+        if exists {
+            continue;
         }
-    }
 
-    println!("\n🔄 Processing files...");
+        let mut entry = Table::new();
+        entry.insert("module", toml_edit::value(module));
+        for (key, value) in settings {
+            if let Ok(value) = value.to_string().parse::<toml_edit::Value>() {
+                entry.insert(key, toml_edit::value(value));
+            }
+        }
+        entries.push(entry);
+        changes.push(Change::append("tool.mypy.overrides", module));
+    }
+}
 
-    for file_path in files_to_process {
-        match modify_pyproject_toml(file_path, uvinit_config) {
-            Ok(()) => {
-                println!("  ✅ {}", file_path.display());
+/// Add the settings `checker` lacks: strictness, include and exclude
+/// directories, and the Python version when there is one
+fn add_type_checker(
+    doc: &mut DocumentMut,
+    checker: TypeChecker,
+    mode: TypeCheckingMode,
+    include: &[String],
+    exclude: &[String],
+    python_version: Option<&str>,
+    changes: &mut Vec<Change>,
+) {
+    let array = |dirs: &[String]| -> toml_edit::Value { dirs.iter().collect::<Array>().into() };
+    // (sub-table, key, value) in the order they are written
+    let mut settings: Vec<(&str, &str, toml_edit::Value)> = Vec::new();
+    let name = match checker {
+        TypeChecker::None => return,
+        TypeChecker::Pyright => {
+            settings.push(("", "typeCheckingMode", mode.as_str().into()));
+            if !include.is_empty() {
+                settings.push(("", "include", array(include)));
             }
-            Err(e) => {
-                eprintln!("  ❌ {}: {}", file_path.display(), e);
+            if !exclude.is_empty() {
+                settings.push(("", "exclude", array(exclude)));
             }
+            if let Some(version) = python_version {
+                settings.push(("", "pythonVersion", version.into()));
+            }
+            "pyright"
         }
-    }
+        TypeChecker::Ty => {
+            if let Some(version) = python_version {
+                settings.push(("environment", "python-version", version.into()));
+            }
+            if !include.is_empty() {
+                settings.push(("src", "include", array(include)));
+            }
+            if !exclude.is_empty() {
+                settings.push(("src", "exclude", array(exclude)));
+            }
+            if mode == TypeCheckingMode::Strict {
+                settings.push(("terminal", "error-on-warning", true.into()));
+            }
+            "ty"
+        }
+    };
 
-    println!("\n🎉 Done!");
-    Ok(())
+    let Some(checker_table) = table_path_mut(doc.as_table_mut(), &["tool", name]) else {
+        return;
+    };
+    // ty keeps everything in sub-tables, so its own header stays hidden
+    checker_table.set_implicit(checker == TypeChecker::Ty);
+    for (sub_table, key, value) in settings {
+        let (table, dotted) = match sub_table {
+            "" => (Some(&mut *checker_table), format!("tool.{name}.{key}")),
+            sub_table => (
+                table_path_mut(checker_table, &[sub_table]),
+                format!("tool.{name}.{sub_table}.{key}"),
+            ),
+        };
+        if let Some(table) = table {
+            insert_missing(table, &dotted, value, changes);
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-    use tempfile::TempDir;
+/// Release numbers of a version like `3.11`, or None if it isn't one
+fn release_numbers(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
 
-    #[test]
-    fn test_find_pyproject_files() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let root_path = temp_dir.path();
+/// The version of the `>=` clause in a specifier such as `>=3.9,<4`
+fn lower_bound(specifier: &str) -> Option<&str> {
+    specifier
+        .split(',')
+        .find_map(|clause| clause.trim().strip_prefix(">="))
+        .map(str::trim)
+}
 
-        // Create test directory structure
-        let sub_dir = root_path.join("subdir");
-        let skip_dir = root_path.join(".git");
-        let nested_dir = sub_dir.join("nested");
-        fs::create_dir_all(&nested_dir)?;
-        fs::create_dir_all(&skip_dir)?;
+/// Whether version `a` sorts before `b`, missing trailing parts counting as 0
+fn version_lt(a: &[u64], b: &[u64]) -> bool {
+    let len = a.len().max(b.len());
+    let pad = |v: &[u64]| -> Vec<u64> {
+        let mut v = v.to_vec();
+        v.resize(len, 0);
+        v
+    };
+    pad(a) < pad(b)
+}
 
-        // Create pyproject.toml files
-        let mut file1 = fs::File::create(root_path.join("pyproject.toml"))?;
-        file1.write_all(b"[project]\nname = \"test1\"")?;
+/// Set project.requires-python when missing; with `tighten`, raise a lower
+/// `>=` bound to the wanted one, keeping the other clauses
+fn set_requires_python(
+    project_table: &mut Table,
+    wanted: &str,
+    tighten: bool,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(current) = project_table
+        .get("requires-python")
+        .map(|v| v.as_str().map(str::to_string))
+    else {
+        project_table.insert("requires-python", toml_edit::value(wanted));
+        changes.push(Change::set(
+            "project.requires-python",
+            &format!("\"{wanted}\""),
+        ));
+        return;
+    };
+    let Some(current) = current.filter(|_| tighten) else {
+        return;
+    };
 
-        let mut file2 = fs::File::create(sub_dir.join("pyproject.toml"))?;
-        file2.write_all(b"[project]\nname = \"test2\"")?;
+    let wanted_bound = lower_bound(wanted).and_then(release_numbers);
+    let current_bound = lower_bound(&current).and_then(release_numbers);
+    let (Some(wanted_bound), Some(current_bound)) = (wanted_bound, current_bound) else {
+        warnings.push(format!(
+            "kept project.requires-python = \"{current}\"; only >= bounds can be compared with \"{wanted}\""
+        ));
+        return;
+    };
+    if !version_lt(&current_bound, &wanted_bound) {
+        return;
+    }
 
-        let mut file3 = fs::File::create(nested_dir.join("pyproject.toml"))?;
-        file3.write_all(b"[project]\nname = \"test3\"")?;
+    let wanted_clause = wanted
+        .split(',')
+        .find(|clause| clause.trim().starts_with(">="))
+        .unwrap_or(wanted)
+        .trim();
+    let tightened: Vec<&str> = current
+        .split(',')
+        .map(|clause| {
+            if clause.trim().starts_with(">=") {
+                wanted_clause
+            } else {
+                clause.trim()
+            }
+        })
+        .collect();
+    let tightened = tightened.join(",");
 
-        // Create file in skip directory (should be ignored)
-        let mut file4 = fs::File::create(skip_dir.join("pyproject.toml"))?;
-        file4.write_all(b"[project]\nname = \"skip\"")?;
+    if let Some(value) = project_table
+        .get_mut("requires-python")
+        .and_then(Item::as_value_mut)
+    {
+        // Keep the comment and spacing around the old value
+        let decor = value.decor().clone();
+        *value = tightened.as_str().into();
+        *value.decor_mut() = decor;
+    }
+    changes.push(Change::set(
+        "project.requires-python",
+        &format!("\"{tightened}\""),
+    ));
+}
 
-        let skip_dirs = vec![".git".to_string(), ".venv".to_string()];
-        let files = find_pyproject_files(root_path, &skip_dirs)?;
+/// Add project.urls pointing at `repository`, project.license and
+/// project.classifiers, each only when the project has none
+fn fill_metadata(
+    project_table: &mut Table,
+    config: &UvinitConfig,
+    repository: Option<&str>,
+    changes: &mut Vec<Change>,
+) {
+    if let Some(repository) = repository
+        && !project_table.contains_key("urls")
+    {
+        let mut urls = Table::new();
+        let links = [
+            ("Homepage", repository.to_string()),
+            ("Repository", repository.to_string()),
+            ("Issues", format!("{repository}/issues")),
+        ];
+        for (label, url) in links {
+            changes.push(Change::set(
+                &format!("project.urls.{label}"),
+                &format!("\"{url}\""),
+            ));
+            urls.insert(label, toml_edit::value(url));
+        }
+        project_table.insert("urls", Item::Table(urls));
+    }
 
-        assert_eq!(files.len(), 3);
-        assert!(
-            files
-                .iter()
-                .any(|f| f.file_name() == Some("pyproject.toml".as_ref()))
+    if let Some(license) = &config.license {
+        insert_missing(
+            project_table,
+            "project.license",
+            license.as_str().into(),
+            changes,
+        );
+    }
+
+    if !config.classifiers.is_empty() && !project_table.contains_key("classifiers") {
+        let mut classifiers = Array::new();
+        for classifier in &config.classifiers {
+            let mut value = toml_edit::Value::from(classifier.as_str());
+            value.decor_mut().set_prefix("\n    ");
+            classifiers.push_formatted(value);
+            changes.push(Change::append("project.classifiers", classifier));
+        }
+        classifiers.set_trailing_comma(true);
+        classifiers.set_trailing("\n");
+        project_table.insert("classifiers", toml_edit::value(classifiers));
+    }
+}
+
+/// Set `project.authors = [{ name, email }]` when the project lists no authors
+fn set_authors(
+    project_table: &mut Table,
+    name: Option<&str>,
+    email: Option<&str>,
+    changes: &mut Vec<Change>,
+) {
+    if project_table.contains_key("authors") || (name.is_none() && email.is_none()) {
+        return;
+    }
+
+    let mut author = toml_edit::InlineTable::new();
+    if let Some(name) = name {
+        author.insert("name", name.into());
+    }
+    if let Some(email) = email {
+        author.insert("email", email.into());
+    }
+    let mut authors = Array::new();
+    authors.push(author);
+    changes.push(Change::set("project.authors", &authors.to_string()));
+    project_table.insert("authors", toml_edit::value(authors));
+}
+
+/// Move the legacy dev requirement lists, project.optional-dependencies.dev and
+/// tool.uv.dev-dependencies, into the PEP 735 `[dependency-groups] dev` group
+fn move_dev_dependencies(
+    doc: &mut DocumentMut,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    // A `dev` group that isn't a list can't take the entries, so leave everything alone
+    let target_ok = match doc.get("dependency-groups") {
+        None => true,
+        Some(groups) => groups
+            .as_table_like()
+            .is_some_and(|groups| groups.get("dev").is_none_or(Item::is_array)),
+    };
+    if !target_ok {
+        return;
+    }
+
+    let mut moved: Vec<Array> = Vec::new();
+    let sources = [
+        ("project", "optional-dependencies", "dev"),
+        ("tool", "uv", "dev-dependencies"),
+    ];
+    for (top, table, key) in sources {
+        let Some(parent) = doc
+            .get_mut(top)
+            .and_then(|top| top.get_mut(table))
+            .and_then(Item::as_table_like_mut)
+        else {
+            continue;
+        };
+        let Some(array) = parent.get(key).and_then(Item::as_array).cloned() else {
+            continue;
+        };
+        parent.remove(key);
+        let now_empty = parent.is_empty();
+        if now_empty && let Some(top) = doc.get_mut(top).and_then(Item::as_table_like_mut) {
+            top.remove(table);
+        }
+        changes.push(Change::remove(&format!("{top}.{table}.{key}")));
+        moved.push(array);
+    }
+    if moved.is_empty() {
+        return;
+    }
+    if changes
+        .iter()
+        .any(|c| c.key() == "project.optional-dependencies.dev")
+    {
+        warnings.push(
+            "moved project.optional-dependencies.dev to dependency-groups; the `dev` extra is no longer installable"
+                .to_string(),
+        );
+    }
+
+    let Some(groups) = doc
+        .entry("dependency-groups")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+    else {
+        return;
+    };
+    let Some(dev) = groups
+        .entry("dev")
+        .or_insert(toml_edit::value(Array::new()))
+        .as_array_mut()
+    else {
+        return;
+    };
+    // An empty group takes over the first list as written, layout included
+    if dev.is_empty() {
+        let first = moved.remove(0);
+        for req in first.iter().filter_map(|v| v.as_str()) {
+            changes.push(Change::append("dependency-groups.dev", req));
+        }
+        *dev = first;
+    }
+    for array in moved {
+        for req in array.iter().filter_map(|v| v.as_str()) {
+            let name = requirement_name(req);
+            let present = dev
+                .iter()
+                .any(|v| v.as_str().map(requirement_name).as_deref() == Some(name.as_str()));
+            if !present {
+                push_preserving_style(dev, req);
+                changes.push(Change::append("dependency-groups.dev", req));
+            }
+        }
+    }
+}
+
+/// Requirements files `--import-requirements` reads, with the dotted key of the
+/// list each merges into
+const REQUIREMENTS_FILES: [(&str, &str); 2] = [
+    ("requirements.txt", "project.dependencies"),
+    ("requirements-dev.txt", "dependency-groups.dev"),
+];
+
+/// Whether `project_dir` has a requirements file to import
+fn has_requirements_files(project_dir: &Path) -> bool {
+    REQUIREMENTS_FILES
+        .iter()
+        .any(|(file_name, _)| project_dir.join(file_name).is_file())
+}
+
+/// The PEP 508 requirements of a requirements.txt, plus the entries that are
+/// not requirements, like editable installs and bare URLs
+///
+/// Comments, `--hash` options and option lines such as `-r` and
+/// `--index-url` are dropped; includes are not followed.
+fn parse_requirements(content: &str) -> (Vec<String>, Vec<String>) {
+    let mut requirements = Vec::new();
+    let mut unsupported = Vec::new();
+    let joined = content.replace("\\\r\n", " ").replace("\\\n", " ");
+    for line in joined.lines() {
+        let line = match line.find(" #") {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let line = match line.find(" -") {
+            Some(options) => &line[..options],
+            None => line,
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("-e") || line.starts_with("--editable") {
+            unsupported.push(line.to_string());
+        } else if line.starts_with('-') {
+            log::debug!("Ignoring requirements option: {line}");
+        } else if requirement_name(line).is_empty()
+            || (line.contains("://") && !line.contains(" @ "))
+        {
+            unsupported.push(line.to_string());
+        } else {
+            requirements.push(line.to_string());
+        }
+    }
+    (requirements, unsupported)
+}
+
+/// Merge the requirements files in `project_dir` into project.dependencies and
+/// `[dependency-groups] dev`, skipping packages the lists already name
+fn import_requirements(
+    doc: &mut DocumentMut,
+    project_dir: &Path,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    for (file_name, key) in REQUIREMENTS_FILES {
+        let Ok(content) = fs::read_to_string(project_dir.join(file_name)) else {
+            continue;
+        };
+        let (requirements, unsupported) = parse_requirements(&content);
+        for entry in unsupported {
+            warnings.push(format!(
+                "{file_name}: skipped `{entry}`, which is not a PEP 508 requirement"
+            ));
+        }
+        if requirements.is_empty() {
+            continue;
+        }
+
+        let (top, list) = key.split_once('.').unwrap_or((key, ""));
+        // Dependencies without a [project] table would make an invalid one
+        if top == "project" && !doc.get("project").is_some_and(Item::is_table_like) {
+            warnings.push(format!("{file_name}: no [project] table to import into"));
+            continue;
+        }
+        let Some(array) = doc
+            .entry(top)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .and_then(|table| {
+                table
+                    .entry(list)
+                    .or_insert(toml_edit::value(Array::new()))
+                    .as_array_mut()
+            })
+        else {
+            warnings.push(format!("{file_name}: {key} is not a list"));
+            continue;
+        };
+
+        for req in &requirements {
+            if let Some(merge) = merge_requirement_listed(array, req) {
+                changes.push(merge.change(key, req));
+            }
+        }
+    }
+}
+
+/// [`merge_requirement`], except that a list starting out empty gets one
+/// entry per line, a layout later pushes keep
+fn merge_requirement_listed(array: &mut Array, req: &str) -> Option<RequirementMerge> {
+    if !array.is_empty() {
+        return merge_requirement(array, req);
+    }
+    let mut value = toml_edit::Value::from(req);
+    value.decor_mut().set_prefix("\n    ");
+    array.push_formatted(value);
+    array.set_trailing("\n");
+    array.set_trailing_comma(true);
+    Some(RequirementMerge::Added)
+}
+
+/// Requirement a new `[build-system]` starts from for `backend`
+fn base_build_require(backend: VersionBackend) -> &'static str {
+    match backend {
+        VersionBackend::HatchVcs => "hatchling",
+        VersionBackend::SetuptoolsScm => "setuptools>=64",
+        VersionBackend::Pdm => "pdm-backend",
+    }
+}
+
+/// Fill `[project]` with the legacy packaging metadata it lacks, and give a
+/// project without `[build-system]` one for `backend`
+fn apply_legacy_metadata(
+    doc: &mut DocumentMut,
+    metadata: &legacy::Metadata,
+    backend: VersionBackend,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    if is_poetry_project(doc) {
+        return;
+    }
+    let changes_before = changes.len();
+    let Some(project) = doc
+        .entry("project")
+        .or_insert(toml_edit::table())
+        .as_table_mut()
+    else {
+        return;
+    };
+
+    // A version the project already derives must not come back as a static one
+    let version = metadata
+        .version
+        .as_ref()
+        .filter(|_| !project.contains_key("dynamic"));
+    let fields = [
+        ("name", metadata.name.as_ref()),
+        ("version", version),
+        ("description", metadata.description.as_ref()),
+        ("requires-python", metadata.requires_python.as_ref()),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            insert_missing(
+                project,
+                &format!("project.{key}"),
+                value.as_str().into(),
+                changes,
+            );
+        }
+    }
+
+    let mut merge_list = |table: &mut Table, key: &str, path: &str, requirements: &[String]| {
+        if requirements.is_empty() {
+            return;
+        }
+        let Some(array) = table
+            .entry(key)
+            .or_insert(toml_edit::value(Array::new()))
+            .as_array_mut()
+        else {
+            warnings.push(format!("{path} is not a list; left it alone"));
+            return;
+        };
+        for req in requirements {
+            if let Some(merge) = merge_requirement_listed(array, req) {
+                changes.push(merge.change(path, req));
+            }
+        }
+    };
+    merge_list(
+        project,
+        "dependencies",
+        "project.dependencies",
+        &metadata.dependencies,
+    );
+    if !metadata.optional_dependencies.is_empty()
+        && let Some(extras) = project
+            .entry("optional-dependencies")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+    {
+        for (extra, requirements) in &metadata.optional_dependencies {
+            merge_list(
+                extras,
+                extra,
+                &format!("project.optional-dependencies.{extra}"),
+                requirements,
+            );
+        }
+    }
+
+    for (group, entries) in &metadata.entry_points {
+        let (keys, path) = match group.as_str() {
+            "console_scripts" => (vec!["scripts"], "project.scripts".to_string()),
+            "gui_scripts" => (vec!["gui-scripts"], "project.gui-scripts".to_string()),
+            group => (
+                vec!["entry-points", group],
+                format!("project.entry-points.{group}"),
+            ),
+        };
+        let Some(table) = table_path_mut(project, &keys) else {
+            continue;
+        };
+        for (name, target) in entries {
+            insert_missing(
+                table,
+                &format!("{path}.{name}"),
+                target.as_str().into(),
+                changes,
+            );
+        }
+    }
+
+    if doc.get("build-system").is_none() {
+        let mut requires = Array::new();
+        requires.push(base_build_require(backend));
+        let mut build_system = Table::new();
+        build_system.insert("requires", toml_edit::value(requires));
+        build_system.insert("build-backend", toml_edit::value(build_backend(backend)));
+        doc.insert("build-system", Item::Table(build_system));
+        changes.push(Change::append(
+            "build-system.requires",
+            base_build_require(backend),
+        ));
+        changes.push(Change::set(
+            "build-system.build-backend",
+            &format!("\"{}\"", build_backend(backend)),
+        ));
+    }
+
+    if changes.len() > changes_before {
+        warnings.push(
+            "filled [project] from setup.cfg/setup.py; drop the metadata there once builds work"
+                .to_string(),
+        );
+    }
+}
+
+/// Where pyproject.toml would go for legacy projects under `targets`: directories
+/// with setup.cfg or setup.py naming a project, but no pyproject.toml yet
+fn find_legacy_projects(targets: &[PathBuf], options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let mut dirs = std::collections::BTreeSet::new();
+    for target in targets.iter().filter(|target| target.is_dir()) {
+        for entry in walk_builder(target, options)?.build() {
+            match entry {
+                Ok(entry)
+                    if entry.file_type().is_some_and(|t| t.is_file())
+                        && (entry.file_name() == "setup.cfg"
+                            || entry.file_name() == "setup.py") =>
+                {
+                    if let Some(dir) = entry.path().parent() {
+                        dirs.insert(dir.to_path_buf());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => report_walk_error(&e),
+            }
+        }
+    }
+    Ok(dirs
+        .into_iter()
+        .filter(|dir| !dir.join(PYPROJECT_FILE_NAME).exists() && legacy::read(dir).is_some())
+        .map(|dir| dir.join(PYPROJECT_FILE_NAME))
+        .collect())
+}
+
+/// Fill `table` (at dotted `path`) with the entries of `values` it lacks,
+/// descending into nested tables
+///
+/// New tables two levels down are written inline, as in
+/// `[tool.uv.sources] pkg = { workspace = true }`.
+fn merge_missing(table: &mut Table, path: &str, values: &toml::Table, changes: &mut Vec<Change>) {
+    merge_missing_at(table, path, values, 0, changes);
+}
+
+fn merge_missing_at(
+    table: &mut Table,
+    path: &str,
+    values: &toml::Table,
+    depth: usize,
+    changes: &mut Vec<Change>,
+) {
+    for (key, value) in values {
+        let dotted = format!("{path}.{key}");
+        match value {
+            toml::Value::Table(nested) if depth == 0 || table.contains_key(key) => {
+                if let Some(child) = table
+                    .entry(key)
+                    .or_insert(toml_edit::table())
+                    .as_table_mut()
+                {
+                    merge_missing_at(child, &dotted, nested, depth + 1, changes);
+                }
+            }
+            value => {
+                if let Ok(value) = value.to_string().parse::<toml_edit::Value>() {
+                    insert_missing(table, &dotted, value, changes);
+                }
+            }
+        }
+    }
+}
+
+/// Add the configured `[tool.hatch.envs.<name>]` tables
+fn add_hatch_envs(
+    doc: &mut DocumentMut,
+    envs: &BTreeMap<String, toml::Table>,
+    changes: &mut Vec<Change>,
+) {
+    let Some(envs_table) = table_path_mut(doc.as_table_mut(), &["tool", "hatch", "envs"]) else {
+        return;
+    };
+
+    for (name, settings) in envs {
+        if let Some(env_table) = envs_table
+            .entry(name)
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+        {
+            merge_missing(
+                env_table,
+                &format!("tool.hatch.envs.{name}"),
+                settings,
+                changes,
+            );
+        }
+    }
+}
+
+/// `[tool.uv]` settings a uv workspace shares from its root pyproject.toml
+const WORKSPACE_UV_SETTINGS: &[&str] = &["sources", "default-groups", "dev-dependencies"];
+
+/// Root directory of the uv workspace listing `file_path`'s project as a member
+///
+/// Like uv, only the nearest ancestor with a `[tool.uv.workspace]` table counts,
+/// and its `exclude` globs win over `members`.
+fn workspace_root(file_path: &Path) -> Option<PathBuf> {
+    let dir = file_path.parent().unwrap_or(Path::new("."));
+    let dir = fs::canonicalize(dir).ok()?;
+    for ancestor in dir.ancestors().skip(1) {
+        let Ok(pyproject) = load_pyproject(&ancestor.join(PYPROJECT_FILE_NAME)) else {
+            continue;
+        };
+        let Some(is_member) = workspace_matcher(&pyproject.doc) else {
+            continue;
+        };
+        let relative = dir.strip_prefix(ancestor).ok()?;
+        return is_member(relative).then(|| ancestor.to_path_buf());
+    }
+    None
+}
+
+/// Whether a directory, relative to the workspace root, is a member of the
+/// `[tool.uv.workspace]` in `doc`; `None` without a workspace table
+fn workspace_matcher(doc: &DocumentMut) -> Option<impl Fn(&Path) -> bool + use<>> {
+    let workspace = doc.get("tool")?.get("uv")?.get("workspace")?;
+    let globs = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let members = glob_matcher(&globs("members"), "tool.uv.workspace.members").ok()?;
+    let exclude = glob_matcher(&globs("exclude"), "tool.uv.workspace.exclude").ok()?;
+    Some(move |relative: &Path| members.is_match(relative) && !exclude.is_match(relative))
+}
+
+/// Normalized project names of uv workspace members, by workspace root
+pub(crate) type WorkspaceMembers = HashMap<PathBuf, BTreeSet<String>>;
+
+/// Normalized project names of the members of the uv workspace at `root`,
+/// found by walking it like the pyproject.toml search does
+fn workspace_member_names(root: &Path, walk_options: &WalkOptions) -> BTreeSet<String> {
+    let Some(is_member) = load_pyproject(&root.join(PYPROJECT_FILE_NAME))
+        .ok()
+        .and_then(|pyproject| workspace_matcher(&pyproject.doc))
+    else {
+        return BTreeSet::new();
+    };
+    let Ok(builder) = walk_builder(root, walk_options) else {
+        return BTreeSet::new();
+    };
+    let mut names = BTreeSet::new();
+    for entry in builder.build().flatten() {
+        let path = entry.path();
+        if path
+            .file_name()
+            .is_none_or(|name| name != PYPROJECT_FILE_NAME)
+        {
+            continue;
+        }
+        let Some(relative) = path.parent().and_then(|dir| dir.strip_prefix(root).ok()) else {
+            continue;
+        };
+        if relative.as_os_str().is_empty() || !is_member(relative) {
+            continue;
+        }
+        if let Ok(pyproject) = load_pyproject(path)
+            && let Some(name) = pyproject
+                .doc
+                .get("project")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+        {
+            names.insert(requirement_name(name));
+        }
+    }
+    names
+}
+
+/// Root of the uv workspace whose members the project at `path` may depend
+/// on; a workspace root counts as a member of its own workspace
+fn sources_workspace_root(path: &Path, doc: &DocumentMut) -> Option<PathBuf> {
+    match workspace_root(path) {
+        Some(root) => Some(root),
+        None if workspace_matcher(doc).is_some() => {
+            fs::canonicalize(path.parent().unwrap_or(Path::new("."))).ok()
+        }
+        None => None,
+    }
+}
+
+/// Members of each workspace the `projects` belong to, walking every
+/// workspace once
+fn workspace_members<'a>(
+    projects: impl IntoIterator<Item = (&'a Path, &'a DocumentMut)>,
+    walk_options: &WalkOptions,
+) -> WorkspaceMembers {
+    let mut members = WorkspaceMembers::new();
+    for (path, doc) in projects {
+        if let Some(root) = sources_workspace_root(path, doc)
+            && !members.contains_key(&root)
+        {
+            let names = workspace_member_names(&root, walk_options);
+            members.insert(root, names);
+        }
+    }
+    members
+}
+
+/// Names in `[tool.uv.sources]` of `doc`, normalized
+fn uv_source_names(doc: &DocumentMut) -> HashSet<String> {
+    doc.get("tool")
+        .and_then(|t| t.get("uv"))
+        .and_then(|u| u.get("sources"))
+        .and_then(|s| s.as_table_like())
+        .map(|sources| {
+            sources
+                .iter()
+                .map(|(name, _)| requirement_name(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// project.dependencies of the pyproject.toml at `path` that are other
+/// members of its uv workspace and have no source in the project or at the
+/// workspace root, looking the workspace up in `members`
+///
+/// A workspace root counts as a member of its own workspace.
+fn missing_workspace_sources(
+    path: &Path,
+    doc: &DocumentMut,
+    members: &WorkspaceMembers,
+) -> Vec<String> {
+    let Some(root) = sources_workspace_root(path, doc) else {
+        return Vec::new();
+    };
+    let Some(members) = members.get(&root) else {
+        return Vec::new();
+    };
+
+    let own_name = doc
+        .get("project")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(requirement_name);
+    let mut sourced = uv_source_names(doc);
+    if let Ok(root_pyproject) = load_pyproject(&root.join(PYPROJECT_FILE_NAME)) {
+        sourced.extend(uv_source_names(&root_pyproject.doc));
+    }
+    let dependencies: Vec<String> = doc
+        .get("project")
+        .and_then(|p| p.get("dependencies"))
+        .and_then(|d| d.as_array())
+        .map(|deps| {
+            deps.iter()
+                .filter_map(|d| d.as_str())
+                .map(requirement_name)
+                .collect()
+        })
+        .unwrap_or_default();
+    if dependencies.is_empty() {
+        return Vec::new();
+    }
+
+    let mut missing: Vec<String> = dependencies
+        .into_iter()
+        .filter(|name| {
+            members.contains(name) && Some(name) != own_name.as_ref() && !sourced.contains(name)
+        })
+        .collect();
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+/// Add `[tool.uv.sources] <name> = { workspace = true }` for each of `names`
+fn add_workspace_sources(doc: &mut DocumentMut, names: &[String], changes: &mut Vec<Change>) {
+    let mut sources = toml::Table::new();
+    for name in names {
+        let mut source = toml::Table::new();
+        source.insert("workspace".to_string(), toml::Value::Boolean(true));
+        sources.insert(name.clone(), toml::Value::Table(source));
+    }
+    let mut settings = toml::Table::new();
+    settings.insert("sources".to_string(), toml::Value::Table(sources));
+
+    if let Some(uv_table) = table_path_mut(doc.as_table_mut(), &["tool", "uv"]) {
+        merge_missing(uv_table, "tool.uv", &settings, changes);
+    }
+}
+
+/// Module name of the flat-layout package that --migrate-src-layout would move
+fn src_layout_package(path: &Path, doc: &DocumentMut) -> Option<String> {
+    let name = doc.get("project")?.get("name")?.as_str()?;
+    let module = module_name(name);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    layout::has_flat_package(dir, &module).then_some(module)
+}
+
+/// Package directories to list as hatch wheel packages for the pyproject.toml
+/// at `path`: empty unless it builds with hatchling (`uses_hatchling`), has no
+/// wheel file selection yet and its packages can't be found from the project name
+fn namespace_wheel_packages(path: &Path, doc: &DocumentMut, uses_hatchling: bool) -> Vec<String> {
+    let wheel = doc
+        .get("tool")
+        .and_then(|t| t.get("hatch"))
+        .and_then(|h| h.get("build"))
+        .and_then(|b| b.get("targets"))
+        .and_then(|t| t.get("wheel"));
+    let selects_files = ["packages", "only-include", "include"]
+        .iter()
+        .any(|key| wheel.and_then(|w| w.get(key)).is_some());
+    let Some(name) = doc
+        .get("project")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+    else {
+        return Vec::new();
+    };
+    if !uses_hatchling || selects_files {
+        return Vec::new();
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    layout::namespace_packages(dir, &module_name(name))
+}
+
+/// Set `[tool.hatch.build.targets.wheel] packages` to `packages`
+fn set_wheel_packages(doc: &mut DocumentMut, packages: &[String], changes: &mut Vec<Change>) {
+    let Some(wheel_table) = table_path_mut(
+        doc.as_table_mut(),
+        &["tool", "hatch", "build", "targets", "wheel"],
+    ) else {
+        return;
+    };
+    wheel_table.set_implicit(false);
+    let packages: Array = packages.iter().map(String::as_str).collect();
+    changes.push(Change::set(
+        "tool.hatch.build.targets.wheel.packages",
+        &packages.to_string(),
+    ));
+    wheel_table.insert("packages", toml_edit::value(packages));
+}
+
+/// Point the hatch wheel packages and, when there are tests, the pytest
+/// pythonpath at `src/<module>`
+fn point_at_src_layout(
+    doc: &mut DocumentMut,
+    module: &str,
+    uses_hatchling: bool,
+    has_tests: bool,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(tool_table) = table_path_mut(doc.as_table_mut(), &["tool"]) else {
+        return;
+    };
+
+    if uses_hatchling {
+        if let Some(wheel_table) =
+            table_path_mut(tool_table, &["hatch", "build", "targets", "wheel"])
+        {
+            let target = format!("src/{module}");
+            let mut packages = wheel_table
+                .get("packages")
+                .and_then(Item::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let before = packages.to_string();
+            let flat = [module.to_string(), format!("./{module}")];
+            packages.retain(|v| v.as_str().is_none_or(|s| !flat.iter().any(|f| f == s)));
+            if !packages.iter().any(|v| v.as_str() == Some(&target)) {
+                packages.push(target.as_str());
+            }
+            if packages.to_string() != before {
+                changes.push(Change::set(
+                    "tool.hatch.build.targets.wheel.packages",
+                    &packages.to_string(),
+                ));
+                wheel_table.insert("packages", toml_edit::value(packages));
+            }
+        }
+    } else {
+        warnings.push(format!(
+            "moved {module} to src/{module}; check that the build backend finds it there"
+        ));
+    }
+
+    if !has_tests {
+        return;
+    }
+    if let Some(ini_options) = table_path_mut(tool_table, &["pytest", "ini_options"]) {
+        match ini_options
+            .get_mut("pythonpath")
+            .and_then(Item::as_array_mut)
+        {
+            Some(pythonpath) if pythonpath.iter().any(|v| v.as_str() == Some("src")) => {}
+            Some(pythonpath) => {
+                push_preserving_style(pythonpath, "src");
+                changes.push(Change::append("tool.pytest.ini_options.pythonpath", "src"));
+            }
+            None => {
+                let mut pythonpath = Array::new();
+                pythonpath.push("src");
+                ini_options.insert("pythonpath", toml_edit::value(pythonpath));
+                changes.push(Change::set(
+                    "tool.pytest.ini_options.pythonpath",
+                    "[\"src\"]",
+                ));
+            }
+        }
+    }
+}
+
+/// Classifier of packages that ship a py.typed marker
+const TYPED_CLASSIFIER: &str = "Typing :: Typed";
+
+/// Append `classifier` to project.classifiers unless it is already listed,
+/// creating a multi-line list when there is none
+fn add_classifier(project_table: &mut Table, classifier: &str, changes: &mut Vec<Change>) {
+    match project_table
+        .get_mut("classifiers")
+        .and_then(Item::as_array_mut)
+    {
+        Some(classifiers) if classifiers.iter().any(|v| v.as_str() == Some(classifier)) => {}
+        Some(classifiers) => {
+            push_preserving_style(classifiers, classifier);
+            changes.push(Change::append("project.classifiers", classifier));
+        }
+        None => {
+            let mut classifiers = Array::new();
+            let mut value = toml_edit::Value::from(classifier);
+            value.decor_mut().set_prefix("\n    ");
+            classifiers.push_formatted(value);
+            classifiers.set_trailing_comma(true);
+            classifiers.set_trailing("\n");
+            project_table.insert("classifiers", toml_edit::value(classifiers));
+            changes.push(Change::append("project.classifiers", classifier));
+        }
+    }
+}
+
+/// Trove license classifiers by SPDX identifier
+const LICENSE_CLASSIFIERS: &[(&str, &str)] = &[
+    ("MIT", "License :: OSI Approved :: MIT License"),
+    (
+        "Apache-2.0",
+        "License :: OSI Approved :: Apache Software License",
+    ),
+    ("BSD-2-Clause", "License :: OSI Approved :: BSD License"),
+    ("BSD-3-Clause", "License :: OSI Approved :: BSD License"),
+    ("ISC", "License :: OSI Approved :: ISC License (ISCL)"),
+    (
+        "MPL-2.0",
+        "License :: OSI Approved :: Mozilla Public License 2.0 (MPL 2.0)",
+    ),
+    (
+        "GPL-2.0-only",
+        "License :: OSI Approved :: GNU General Public License v2 (GPLv2)",
+    ),
+    (
+        "GPL-2.0-or-later",
+        "License :: OSI Approved :: GNU General Public License v2 or later (GPLv2+)",
+    ),
+    (
+        "GPL-3.0-only",
+        "License :: OSI Approved :: GNU General Public License v3 (GPLv3)",
+    ),
+    (
+        "GPL-3.0-or-later",
+        "License :: OSI Approved :: GNU General Public License v3 or later (GPLv3+)",
+    ),
+    (
+        "LGPL-3.0-only",
+        "License :: OSI Approved :: GNU Lesser General Public License v3 (LGPLv3)",
+    ),
+    (
+        "LGPL-3.0-or-later",
+        "License :: OSI Approved :: GNU Lesser General Public License v3 or later (LGPLv3+)",
+    ),
+    (
+        "AGPL-3.0-only",
+        "License :: OSI Approved :: GNU Affero General Public License v3",
+    ),
+    (
+        "AGPL-3.0-or-later",
+        "License :: OSI Approved :: GNU Affero General Public License v3 or later (AGPLv3+)",
+    ),
+    (
+        "Unlicense",
+        "License :: OSI Approved :: The Unlicense (Unlicense)",
+    ),
+];
+
+/// Classifiers that follow from the project's metadata: the Python 3
+/// versions requires-python allows, its license and, when `package` (relative
+/// to `dir`) ships a py.typed marker, `Typing :: Typed`
+fn derive_classifiers(
+    doc: &mut DocumentMut,
+    dir: &Path,
+    package: Option<&str>,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let uses_setuptools = doc
+        .get("build-system")
+        .and_then(|b| b.get("build-backend"))
+        .and_then(|b| b.as_str())
+        == Some(build_backend(VersionBackend::SetuptoolsScm));
+    let Some(project_table) = doc.get_mut("project").and_then(Item::as_table_mut) else {
+        return;
+    };
+    let is_dynamic = project_table
+        .get("dynamic")
+        .and_then(Item::as_array)
+        .is_some_and(|dynamic| dynamic.iter().any(|v| v.as_str() == Some("classifiers")));
+    if is_dynamic {
+        return;
+    }
+
+    let mut derived: Vec<String> = Vec::new();
+    if let Some(requires_python) = project_table.get("requires-python").and_then(Item::as_str) {
+        derived.push("Programming Language :: Python :: 3".to_string());
+        derived.extend(
+            python_versions(Some(requires_python))
+                .iter()
+                .map(|version| format!("Programming Language :: Python :: {version}")),
+        );
+    }
+
+    // PEP 639 expressions or the SPDX id in a legacy `{ text = ... }` table
+    let license = project_table.get("license");
+    let expression = license.and_then(Item::as_str);
+    let spdx = expression.or_else(|| license?.get("text")?.as_str());
+    if let Some((spdx, classifier)) = spdx.and_then(|spdx| {
+        LICENSE_CLASSIFIERS
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(spdx.trim()))
+    }) {
+        if expression.is_some() && uses_setuptools {
+            warnings.push(format!(
+                "Not adding '{classifier}': setuptools rejects license classifiers next to the license expression '{spdx}'"
+            ));
+        } else {
+            derived.push(classifier.to_string());
+        }
+    }
+
+    if package.is_some_and(|package| dir.join(package).join("py.typed").is_file()) {
+        derived.push(TYPED_CLASSIFIER.to_string());
+    }
+
+    for classifier in &derived {
+        add_classifier(project_table, classifier, changes);
+    }
+}
+
+/// Classify the project as typed and make sure wheels of `package` (the
+/// package directory relative to the project) include its py.typed marker
+fn mark_typed(doc: &mut DocumentMut, module: &str, package: &str, changes: &mut Vec<Change>) {
+    let backend = doc
+        .get("build-system")
+        .and_then(|b| b.get("build-backend"))
+        .and_then(|b| b.as_str())
+        .map(str::to_string);
+
+    if let Some(project_table) = doc.get_mut("project").and_then(Item::as_table_mut) {
+        add_classifier(project_table, TYPED_CLASSIFIER, changes);
+    }
+
+    // hatchling wheels carry every file in the package unless include lists narrow them;
+    // setuptools only ships non-Python files listed as package data
+    let marker = format!("{package}/py.typed");
+    let uses_setuptools = backend.as_deref() == Some(build_backend(VersionBackend::SetuptoolsScm));
+    if !uses_setuptools && !doc.contains_key("tool") {
+        return;
+    }
+    let Some(tool_table) = table_path_mut(doc.as_table_mut(), &["tool"]) else {
+        return;
+    };
+    match backend.as_deref() {
+        Some(b) if b == build_backend(VersionBackend::HatchVcs) => {
+            let Some(wheel_table) = ["hatch", "build", "targets", "wheel"]
+                .iter()
+                .try_fold(&mut *tool_table, |table, key| {
+                    table.get_mut(key).and_then(Item::as_table_mut)
+                })
+            else {
+                return;
+            };
+            for key in ["only-include", "include"] {
+                if let Some(include) = wheel_table.get_mut(key).and_then(Item::as_array_mut)
+                    && !include
+                        .iter()
+                        .any(|v| v.as_str().is_some_and(|s| s == package || s == marker))
+                {
+                    push_preserving_style(include, &marker);
+                    changes.push(Change::append(
+                        &format!("tool.hatch.build.targets.wheel.{key}"),
+                        &marker,
+                    ));
+                }
+            }
+        }
+        _ if uses_setuptools => {
+            let Some(package_data) = table_path_mut(tool_table, &["setuptools", "package-data"])
+            else {
+                return;
+            };
+            let files = package_data
+                .entry(module)
+                .or_insert(toml_edit::value(Array::new()));
+            if let Some(files) = files.as_array_mut()
+                && !files.iter().any(|v| v.as_str() == Some("py.typed"))
+            {
+                push_preserving_style(files, "py.typed");
+                changes.push(Change::append(
+                    &format!("tool.setuptools.package-data.{module}"),
+                    "py.typed",
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Record a former static version as tool.setuptools_scm.fallback_version
+fn move_version_to_scm(doc: &mut DocumentMut, version: &str, changes: &mut Vec<Change>) {
+    if let Some(scm_table) = table_path_mut(doc.as_table_mut(), &["tool", "setuptools_scm"])
+        && !scm_table.contains_key("fallback_version")
+    {
+        scm_table.insert("fallback_version", toml_edit::value(version));
+        changes.push(Change::set(
+            "tool.setuptools_scm.fallback_version",
+            &format!("\"{version}\""),
+        ));
+    }
+}
+
+/// Fill in the build-system and tool pieces an already-dynamic project needs
+/// to build, without touching `[project]`
+fn repair_document(pyproject: Pyproject, config: &UvinitConfig) -> Rendered {
+    let Pyproject {
+        content, mut doc, ..
+    } = pyproject;
+    let mut changes = Vec::new();
+
+    add_build_requires(&mut doc, config, &mut changes);
+    if is_poetry_project(&doc) {
+        set_poetry_versioning(&mut doc, &mut changes);
+    } else if config.version_backend == VersionBackend::HatchVcs {
+        set_version_source(&mut doc, false, &mut changes);
+    } else {
+        set_scm_tool_table(&mut doc, config.version_backend, &mut changes);
+    }
+
+    let rendered = with_line_endings(Some(&content), &doc.to_string()).into_owned();
+    Rendered {
+        original: content,
+        content: rendered,
+        changes,
+        warnings: Vec::new(),
+        src_layout_package: None,
+    }
+}
+
+/// Give every table that wasn't in `original` a consistent leading blank line
+/// (or none, if `blank_line` is false), since inserted tables get default decor
+fn space_new_tables(table: &mut Table, original: Option<&Table>, blank_line: bool) {
+    for (key, item) in table.iter_mut() {
+        let Some(child) = item.as_table_mut() else {
+            continue;
+        };
+        let original_child = original
+            .and_then(|t| t.get(key.get()))
+            .and_then(|i| i.as_table());
+
+        // Implicit tables without values emit no header, so the decor is harmless there
+        if original_child.is_none() {
+            child
+                .decor_mut()
+                .set_prefix(if blank_line { "\n" } else { "" });
+        }
+        space_new_tables(child, original_child, blank_line);
+    }
+}
+
+fn render_pyproject_toml(file_path: &Path, config: &UvinitConfig) -> Result<Rendered> {
+    let pyproject = load_pyproject(file_path)?;
+    let members = match config.enable_workspace_sources {
+        true => workspace_members([(file_path, &pyproject.doc)], &walk_options(config)),
+        false => WorkspaceMembers::new(),
+    };
+    let repo = RepoFacts::lookup(file_path.parent().unwrap_or(Path::new(".")), config);
+    Ok(render_document(pyproject, config, &members, &repo))
+}
+
+/// Replace `project.version` with `project.dynamic = ["version"]`, keeping or
+/// moving the old version as configured
+fn set_dynamic_version(
+    doc: &mut DocumentMut,
+    config: &UvinitConfig,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(project_table) = doc.get_mut("project").and_then(Item::as_table_mut) else {
+        return;
+    };
+    let version = project_table
+        .get("version")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    if project_table.contains_key("version") {
+        match config.version_key_action {
+            VersionKeyAction::Remove | VersionKeyAction::MoveToScm => {
+                changes.push(Change::remove("project.version"));
+            }
+            VersionKeyAction::Keep => warnings.push(
+                "kept project.version alongside dynamic = [\"version\"]; most build backends reject both"
+                    .to_string(),
+            ),
+        }
+    }
+    if !project_table
+        .get("dynamic")
+        .and_then(|d| d.as_array())
+        .is_some_and(|d| d.len() == 1 && d.iter().any(|v| v.as_str() == Some("version")))
+    {
+        changes.push(Change::set("project.dynamic", r#"["version"]"#));
+    }
+
+    let mut dynamic_array = Array::new();
+    dynamic_array.push("version");
+    insert_at_key(
+        project_table,
+        "version",
+        "dynamic",
+        toml_edit::value(dynamic_array),
+        config.version_key_action == VersionKeyAction::Keep,
+    );
+    if config.version_key_action == VersionKeyAction::MoveToScm
+        && let Some(version) = version
+    {
+        move_version_to_scm(doc, &version, changes);
+    }
+}
+
+/// Point the version backend at git: `tool.hatch.version.source = "vcs"` or
+/// the equivalent for the configured backend or Poetry
+fn set_git_version_source(
+    doc: &mut DocumentMut,
+    config: &UvinitConfig,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    if is_poetry_project(doc) {
+        set_poetry_versioning(doc, changes);
+        return;
+    }
+    if let Some(current) = set_build_backend(doc, config.version_backend, changes) {
+        warnings.push(format!(
+            "kept build-system.build-backend = \"{current}\"; the {} plugin expects \"{}\"",
+            version_requires(doc, config.version_backend).join(" + "),
+            build_backend(config.version_backend)
+        ));
+    }
+    if config.version_backend != VersionBackend::HatchVcs {
+        set_scm_tool_table(doc, config.version_backend, changes);
+    } else if let Some(source) = set_version_source(doc, config.override_version_source, changes) {
+        warnings.push(format!(
+            "kept tool.hatch.version.source = \"{source}\"; pass --override-version-source to switch it to \"vcs\""
+        ));
+    } else {
+        set_hatch_vcs_options(doc, config, changes);
+    }
+}
+
+/// Whether the document builds with hatchling
+fn uses_hatchling(doc: &DocumentMut) -> bool {
+    doc.get("build-system")
+        .and_then(|b| b.get("build-backend"))
+        .and_then(|b| b.as_str())
+        == Some(build_backend(VersionBackend::HatchVcs))
+}
+
+/// Set `tool.pytest.ini_options.asyncio_mode = "auto"`
+fn set_pytest_asyncio_mode(doc: &mut DocumentMut, changes: &mut Vec<Change>) {
+    let Some(ini_options) = table_path_mut(doc.as_table_mut(), &["tool", "pytest", "ini_options"])
+    else {
+        return;
+    };
+    if ini_options.get("asyncio_mode").and_then(|v| v.as_str()) != Some("auto") {
+        changes.push(Change::set(
+            "tool.pytest.ini_options.asyncio_mode",
+            "\"auto\"",
+        ));
+    }
+    ini_options.insert("asyncio_mode", toml_edit::value("auto"));
+}
+
+/// Add the configured skips and exclude_dirs to `tool.bandit`
+fn add_bandit(doc: &mut DocumentMut, config: &UvinitConfig, changes: &mut Vec<Change>) {
+    let Some(bandit_table) = table_path_mut(doc.as_table_mut(), &["tool", "bandit"]) else {
+        return;
+    };
+    let settings = [
+        ("skips", &config.bandit_skips),
+        ("exclude_dirs", &config.bandit_exclude_dirs),
+    ];
+    for (key, values) in settings {
+        if values.is_empty() {
+            continue;
+        }
+        let array = bandit_table
+            .entry(key)
+            .or_insert(toml_edit::value(Array::new()));
+
+        // Entries already present, the user's included, are kept as-is
+        if let Some(array) = array.as_array_mut() {
+            for value in values {
+                if !array.iter().any(|v| v.as_str() == Some(value)) {
+                    push_preserving_style(array, value);
+                    changes.push(Change::append(&format!("tool.bandit.{key}"), value));
+                }
+            }
+        }
+    }
+}
+
+/// Add `tool.ruff` line-length and `tool.ruff.lint.select`
+fn add_ruff(doc: &mut DocumentMut, config: &UvinitConfig, changes: &mut Vec<Change>) {
+    let Some(ruff_table) = table_path_mut(doc.as_table_mut(), &["tool", "ruff"]) else {
+        return;
+    };
+    // Existing ruff settings are the user's; only fill in what's missing
+    if !ruff_table.contains_key("line-length") {
+        ruff_table.insert("line-length", toml_edit::value(config.ruff_line_length));
+        changes.push(Change::set(
+            "tool.ruff.line-length",
+            &config.ruff_line_length.to_string(),
+        ));
+    }
+
+    // Older ruff versions take select directly under [tool.ruff]
+    if !ruff_table.contains_key("select")
+        && let Some(lint_table) = ruff_table
+            .entry("lint")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+        && !lint_table.contains_key("select")
+    {
+        let select: Array = config.ruff_select.iter().collect();
+        changes.push(Change::set("tool.ruff.lint.select", &select.to_string()));
+        lint_table.insert("select", toml_edit::value(select));
+    }
+}
+
+/// Add `tool.mypy` strictness settings and per-module overrides
+fn add_mypy(doc: &mut DocumentMut, config: &UvinitConfig, changes: &mut Vec<Change>) {
+    let Some(mypy_table) = table_path_mut(doc.as_table_mut(), &["tool", "mypy"]) else {
+        return;
+    };
+    insert_missing(
+        mypy_table,
+        "tool.mypy.strict",
+        config.mypy_strict.into(),
+        changes,
+    );
+    insert_missing(
+        mypy_table,
+        "tool.mypy.python_version",
+        config.mypy_python_version.as_str().into(),
+        changes,
+    );
+    insert_missing(
+        mypy_table,
+        "tool.mypy.ignore_missing_imports",
+        config.mypy_ignore_missing_imports.into(),
+        changes,
+    );
+    add_mypy_overrides(mypy_table, &config.mypy_overrides, changes);
+}
+
+/// Add `tool.pyright` or `tool.ty` for the pyproject.toml at `path`, covering
+/// the src and tests directories it has and targeting the requires-python
+/// lower bound
+fn add_configured_type_checker(
+    doc: &mut DocumentMut,
+    path: &Path,
+    config: &UvinitConfig,
+    changes: &mut Vec<Change>,
+) {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let include = match config.type_checker_include.is_empty() {
+        false => config.type_checker_include.clone(),
+        true => {
+            let has_src = dir.join("src").is_dir()
+                || (config.migrate_src_layout && src_layout_package(path, doc).is_some());
+            [("src", has_src), ("tests", dir.join("tests").is_dir())]
+                .into_iter()
+                .filter(|(_, exists)| *exists)
+                .map(|(dir, _)| dir.to_string())
+                .collect()
+        }
+    };
+    // Type checkers take a minor version, like `3.11`
+    let python_version = python_version_for(doc, None)
+        .map(|version| version.split('.').take(2).collect::<Vec<_>>().join("."));
+    add_type_checker(
+        doc,
+        config.type_checker,
+        config.type_checking_mode,
+        &include,
+        &config.type_checker_exclude,
+        python_version.as_deref(),
+        changes,
+    );
+}
+
+/// Add `tool.coverage.run` source and omit, and `tool.coverage.report.fail_under`;
+/// the source defaults to the project's module
+fn add_coverage(doc: &mut DocumentMut, config: &UvinitConfig, changes: &mut Vec<Change>) {
+    let source = if config.coverage_source.is_empty() {
+        doc.get("project")
+            .and_then(|project| project.get("name"))
+            .and_then(|name| name.as_str())
+            .map(|name| vec![module_name(name)])
+            .unwrap_or_default()
+    } else {
+        config.coverage_source.clone()
+    };
+
+    let Some(coverage_table) = table_path_mut(doc.as_table_mut(), &["tool", "coverage"]) else {
+        return;
+    };
+    if let Some(run_table) = coverage_table
+        .entry("run")
+        .or_insert(toml_edit::table())
+        .as_table_mut()
+    {
+        let settings = [
+            ("tool.coverage.run.source", &source),
+            ("tool.coverage.run.omit", &config.coverage_omit),
+        ];
+        for (key, values) in settings {
+            if !values.is_empty() {
+                let array: Array = values.iter().collect();
+                insert_missing(run_table, key, array.into(), changes);
+            }
+        }
+    }
+
+    if let Some(report_table) = coverage_table
+        .entry("report")
+        .or_insert(toml_edit::table())
+        .as_table_mut()
+    {
+        insert_missing(
+            report_table,
+            "tool.coverage.report.fail_under",
+            config.coverage_fail_under.into(),
+            changes,
+        );
+    }
+}
+
+/// Add the configured `[tool.uv]` settings; workspace members leave the
+/// workspace-wide ones to the root
+fn add_uv_settings(
+    doc: &mut DocumentMut,
+    config: &UvinitConfig,
+    in_workspace: bool,
+    changes: &mut Vec<Change>,
+) {
+    let uv_settings: toml::Table = config
+        .uv_settings
+        .iter()
+        .filter(|(key, _)| !in_workspace || !WORKSPACE_UV_SETTINGS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if uv_settings.is_empty() {
+        return;
+    }
+    if let Some(uv_table) = table_path_mut(doc.as_table_mut(), &["tool", "uv"]) {
+        merge_missing(uv_table, "tool.uv", &uv_settings, changes);
+    }
+}
+
+/// The package directory of `module` relative to `dir`, where it is after any
+/// move to src/
+fn package_path(dir: &Path, module: &str, moving_to_src: bool) -> Option<String> {
+    if moving_to_src {
+        return Some(format!("src/{module}"));
+    }
+    layout::package_dir(dir, module).and_then(|package| {
+        package
+            .strip_prefix(dir)
+            .ok()
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+    })
+}
+
+/// Whether hatch takes the version from git
+fn uses_vcs_source(doc: &DocumentMut) -> bool {
+    doc.get("tool")
+        .and_then(|t| t.get("hatch"))
+        .and_then(|h| h.get("version"))
+        .and_then(|v| v.get("source"))
+        .and_then(|s| s.as_str())
+        == Some("vcs")
+}
+
+/// What rendering takes from the git repository of a project
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RepoFacts {
+    /// Browser URL of the `origin` remote
+    repository: Option<String>,
+    /// git `user.name` and `user.email`
+    user_name: Option<String>,
+    user_email: Option<String>,
+}
+
+impl RepoFacts {
+    /// Look up, from `dir`, the facts `config` needs
+    fn lookup(dir: &Path, config: &UvinitConfig) -> Self {
+        RepoFacts {
+            repository: config
+                .enable_metadata
+                .then(|| git::remote_url(dir).and_then(|remote| git::web_url(&remote)))
+                .flatten(),
+            user_name: config
+                .fill_authors
+                .then(|| git::config_value(dir, "user.name"))
+                .flatten(),
+            user_email: config
+                .fill_authors
+                .then(|| git::config_value(dir, "user.email"))
+                .flatten(),
+        }
+    }
+}
+
+/// Repository facts by repository root, `None` standing for projects outside git
+pub(crate) type RepoFactsByRoot = HashMap<Option<PathBuf>, RepoFacts>;
+
+/// The facts of the repositories holding `files`, looked up once per repository
+fn repo_facts<'a>(
+    files: impl IntoIterator<Item = &'a PathBuf>,
+    config: &UvinitConfig,
+) -> RepoFactsByRoot {
+    let mut facts = RepoFactsByRoot::new();
+    for file_path in files {
+        let dir = file_path.parent().unwrap_or(Path::new("."));
+        facts
+            .entry(git::work_tree_root(dir))
+            .or_insert_with(|| RepoFacts::lookup(dir, config));
+    }
+    facts
+}
+
+/// What the rendering steps for one pyproject.toml share
+struct RenderContext<'a> {
+    path: &'a Path,
+    dir: &'a Path,
+    config: &'a UvinitConfig,
+    repo: &'a RepoFacts,
+    /// Root of the uv workspace the file is a member of
+    workspace: Option<PathBuf>,
+}
+
+/// Fill [project] from legacy metadata, switch it to a git-derived version and
+/// set up the build system that derives it
+fn render_build_steps(
+    doc: &mut DocumentMut,
+    context: &RenderContext,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let config = context.config;
+
+    // 1. Fill [project] from legacy setup.cfg and setup.py metadata
+    if config.migrate_setup
+        && let Some(metadata) = legacy::read(context.dir)
+    {
+        apply_legacy_metadata(doc, &metadata, config.version_backend, changes, warnings);
+    }
+
+    // 2. Replace project.version with project.dynamic = ["version"]
+    if config.enable_dynamic_version {
+        set_dynamic_version(doc, config, changes, warnings);
+    }
+
+    // 3. Add to build-system.requires
+    add_build_requires(doc, config, changes);
+
+    // 4. Point the version backend at git
+    if config.enable_dynamic_version {
+        set_git_version_source(doc, config, changes, warnings);
+    }
+}
+
+/// Add the configured test, lint, type checking and coverage tool tables
+fn render_tool_steps(doc: &mut DocumentMut, context: &RenderContext, changes: &mut Vec<Change>) {
+    let config = context.config;
+
+    // 5. Add tool.pytest.ini_options.asyncio_mode = "auto" and the configured entries
+    if config.enable_pytest_asyncio {
+        set_pytest_asyncio_mode(doc, changes);
+    }
+    if !config.pytest_ini_options.is_empty() {
+        merge_pytest_ini_options(doc, &config.pytest_ini_options, changes);
+    }
+
+    // 6. Add tool.bandit
+    let has_bandit_settings =
+        !config.bandit_skips.is_empty() || !config.bandit_exclude_dirs.is_empty();
+    if config.enable_bandit && has_bandit_settings {
+        add_bandit(doc, config, changes);
+    }
+
+    // 7. Add tool.ruff
+    if config.enable_ruff {
+        add_ruff(doc, config, changes);
+    }
+
+    // 8. Add tool.mypy
+    if config.enable_mypy {
+        add_mypy(doc, config, changes);
+    }
+
+    // 9. Add tool.pyright or tool.ty
+    if config.type_checker != TypeChecker::None {
+        add_configured_type_checker(doc, context.path, config, changes);
+    }
+
+    // 10. Add tool.coverage
+    if config.enable_coverage {
+        add_coverage(doc, config, changes);
+    }
+}
+
+/// Fill in [project] metadata: requires-python, urls, license, classifiers and authors
+fn render_metadata_steps(
+    doc: &mut DocumentMut,
+    context: &RenderContext,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let config = context.config;
+    let Some(project_table) = doc.get_mut("project").and_then(Item::as_table_mut) else {
+        return;
+    };
+
+    // 11. Set or tighten project.requires-python
+    if let Some(wanted) = &config.requires_python {
+        set_requires_python(
+            project_table,
+            wanted,
+            config.tighten_requires_python,
+            changes,
+            warnings,
+        );
+    }
+
+    // 12. Fill in project.urls, license and classifiers
+    if config.enable_metadata {
+        fill_metadata(
+            project_table,
+            config,
+            context.repo.repository.as_deref(),
+            changes,
+        );
+    }
+
+    // 13. Fill in project.authors from git config
+    if config.fill_authors {
+        set_authors(
+            project_table,
+            context.repo.user_name.as_deref(),
+            context.repo.user_email.as_deref(),
+            changes,
+        );
+    }
+}
+
+/// Set up dependency handling: hatch environments, dependency groups,
+/// requirements files, `[tool.uv]` and workspace sources
+fn render_dependency_steps(
+    doc: &mut DocumentMut,
+    context: &RenderContext,
+    members: &WorkspaceMembers,
+    uses_hatchling: bool,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) {
+    let config = context.config;
+
+    // 14. Add hatch environments to hatchling projects
+    if !config.hatch_envs.is_empty() && uses_hatchling {
+        add_hatch_envs(doc, &config.hatch_envs, changes);
+    }
+
+    // 15. Move legacy dev requirements into [dependency-groups]
+    if config.enable_dependency_groups && context.workspace.is_none() {
+        move_dev_dependencies(doc, changes, warnings);
+    }
+
+    // 16. Merge requirements files into the dependency lists
+    if config.import_requirements {
+        import_requirements(doc, context.dir, changes, warnings);
+    }
+
+    // 17. Add [tool.uv] settings
+    add_uv_settings(doc, config, context.workspace.is_some(), changes);
+
+    // 18. Point dependencies on other workspace members at the workspace
+    if config.enable_workspace_sources {
+        let missing = missing_workspace_sources(context.path, doc, members);
+        if !missing.is_empty() {
+            add_workspace_sources(doc, &missing, changes);
+        }
+    }
+}
+
+/// Configure the package itself: its layout, what the wheel ships, typing,
+/// classifiers, the version file and console scripts
+///
+/// Returns the flat-layout package to move under src/.
+fn render_package_steps(
+    doc: &mut DocumentMut,
+    context: &RenderContext,
+    uses_hatchling: bool,
+    changes: &mut Vec<Change>,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    let RenderContext {
+        path, dir, config, ..
+    } = *context;
+
+    // 19. Point build and test settings at a flat-layout package moving to src/
+    let src_layout_package = config
+        .migrate_src_layout
+        .then(|| src_layout_package(path, doc))
+        .flatten();
+    if let Some(module) = &src_layout_package {
+        let has_tests = dir.join("tests").is_dir();
+        point_at_src_layout(doc, module, uses_hatchling, has_tests, changes, warnings);
+    }
+
+    // 20. List namespace packages hatchling would not find by itself
+    if config.detect_namespace_packages && src_layout_package.is_none() {
+        let packages = namespace_wheel_packages(path, doc, uses_hatchling);
+        if !packages.is_empty() {
+            set_wheel_packages(doc, &packages, changes);
+        }
+    }
+
+    let project_name = doc
+        .get("project")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(str::to_string);
+    let module = project_name.as_deref().map(module_name);
+    let package = module
+        .as_deref()
+        .and_then(|module| package_path(dir, module, src_layout_package.is_some()));
+
+    // 21. Mark the package as typed
+    if config.enable_py_typed
+        && let (Some(module), Some(package)) = (&module, &package)
+    {
+        mark_typed(doc, module, package, changes);
+    }
+
+    // 22. Derive classifiers from requires-python, the license and py.typed
+    if config.derive_classifiers {
+        derive_classifiers(doc, dir, package.as_deref(), changes, warnings);
+    }
+
+    // 23. Have hatch-vcs write <package>/_version.py at build time
+    if config.enable_version_file
+        && uses_hatchling
+        && uses_vcs_source(doc)
+        && let Some(package) = &package
+    {
+        set_version_file_hook(doc, package, changes);
+    }
+
+    // 24. Add the configured console scripts
+    if !config.scripts.is_empty()
+        && let (Some(name), Some(module)) = (&project_name, &module)
+    {
+        add_scripts(doc, &config.scripts, name, module, changes);
+    }
+
+    src_layout_package
+}
+
+fn render_document(
+    pyproject: Pyproject,
+    config: &UvinitConfig,
+    members: &WorkspaceMembers,
+    repo: &RepoFacts,
+) -> Rendered {
+    let Pyproject {
+        path,
+        content,
+        doc: original,
+    } = pyproject;
+    let mut doc = original.clone();
+    let mut changes = Vec::new();
+    let mut warnings = Vec::new();
+    // Workspace members get per-package edits; shared settings stay at the root
+    let context = RenderContext {
+        path: &path,
+        dir: path.parent().unwrap_or(Path::new(".")),
+        config,
+        repo,
+        workspace: workspace_root(&path),
+    };
+    if let Some(root) = &context.workspace {
+        log::info!(
+            "{} is a member of the uv workspace at {}",
+            path.display(),
+            root.display()
+        );
+    }
+
+    render_build_steps(&mut doc, &context, &mut changes, &mut warnings);
+    // The build backend is settled from here on
+    let uses_hatchling = uses_hatchling(&doc);
+    render_tool_steps(&mut doc, &context, &mut changes);
+    render_metadata_steps(&mut doc, &context, &mut changes, &mut warnings);
+    render_dependency_steps(
+        &mut doc,
+        &context,
+        members,
+        uses_hatchling,
+        &mut changes,
+        &mut warnings,
+    );
+    let src_layout_package = render_package_steps(
+        &mut doc,
+        &context,
+        uses_hatchling,
+        &mut changes,
+        &mut warnings,
+    );
+
+    // 25. Let commitizen bump versions from git tags
+    if config.enable_commitizen && !is_poetry_project(&doc) {
+        set_commitizen(&mut doc, &config.version_tag_format, &mut changes);
+    }
+
+    // 26. Apply the configured patches
+    for patch in &config.patches {
+        apply_patch(&mut doc, patch, &mut changes, &mut warnings);
+    }
+
+    space_new_tables(
+        doc.as_table_mut(),
+        Some(original.as_table()),
+        config.blank_line_between_tables,
+    );
+
+    // New lines follow the file's own line endings
+    let mut rendered = with_line_endings(Some(&content), &doc.to_string()).into_owned();
+    // A created file starts with its first table, not a blank line
+    if content.is_empty() {
+        rendered = rendered.trim_start().to_string();
+    }
+    Rendered {
+        original: content,
+        content: rendered,
+        changes,
+        warnings,
+        src_layout_package,
+    }
+}
+
+/// The config for a run: the loaded file with `options` applied on top
+fn load_uvinit_config(options: &UvinitOptions, config_args: &ConfigArgs) -> Result<Config> {
+    let mut config = load_config(config_args, Some("uvinit"))?;
+    if options.override_version_source {
+        config.uvinit.override_version_source = true;
+    }
+    if let Some(action) = options.version_key_action {
+        config.uvinit.version_key_action = action;
+    }
+    if let Some(backend) = options.version_backend {
+        config.uvinit.version_backend = backend;
+    }
+    if let Some(parallel) = options.parallel_walk {
+        config.uvinit.parallel_walk = parallel;
+    }
+    if options.no_ignore {
+        config.uvinit.respect_gitignore = false;
+    }
+    if options.no_cache {
+        config.uvinit.scan_cache = false;
+    }
+    if options.max_depth.is_some() {
+        config.uvinit.max_depth = options.max_depth;
+    }
+    if options.git_commit {
+        config.uvinit.git_commit = true;
+    }
+    if options.tag {
+        config.uvinit.create_version_tag = true;
+    }
+    if options.publish_workflow {
+        config.uvinit.enable_publish_workflow = true;
+    }
+    if options.dockerfile {
+        config.uvinit.enable_dockerfile = true;
+    }
+    if options.devcontainer {
+        config.uvinit.enable_devcontainer = true;
+    }
+    if options.migrate_src_layout {
+        config.uvinit.migrate_src_layout = true;
+    }
+    if options.py_typed {
+        config.uvinit.enable_py_typed = true;
+    }
+    if options.import_requirements {
+        config.uvinit.import_requirements = true;
+    }
+    if options.migrate_setup {
+        config.uvinit.migrate_setup = true;
+    }
+    if options.workspace_sources {
+        config.uvinit.enable_workspace_sources = true;
+    }
+    if let Some(checker) = options.type_checker {
+        config.uvinit.type_checker = checker;
+    }
+    if let Some(bot) = options.dependency_updates {
+        config.uvinit.dependency_updates = bot;
+    }
+    if options.python_version_file || options.python_version.is_some() {
+        config.uvinit.enable_python_version_file = true;
+    }
+    if options.python_version.is_some() {
+        config.uvinit.python_version = options.python_version.clone();
+    }
+    if let Some(validate) = options.validate {
+        config.uvinit.validate = validate;
+    }
+    if let Some(follow) = options.follow_symlinks {
+        config.uvinit.follow_symlinks = follow;
+    }
+    if !options.include.is_empty() {
+        config.uvinit.include = options.include.clone();
+    }
+    if !options.exclude.is_empty() {
+        config.uvinit.exclude = options.exclude.clone();
+    }
+    options.features.apply(&mut config.uvinit);
+    if config.uvinit.enable_commitizen
+        && !config
+            .uvinit
+            .precommit_hooks
+            .iter()
+            .any(|h| h == "commitizen")
+    {
+        config.uvinit.precommit_hooks.push("commitizen".to_string());
+    }
+    Ok(config)
+}
+
+/// How a run walks the tree, from the config
+pub(crate) fn walk_options(config: &UvinitConfig) -> WalkOptions {
+    WalkOptions {
+        skip_dirs: config.skip_dirs.clone(),
+        parallel: config.parallel_walk,
+        respect_gitignore: config.respect_gitignore,
+        max_depth: config.max_depth,
+        follow_symlinks: config.follow_symlinks,
+        include: config.include.clone(),
+        exclude: config.exclude.clone(),
+    }
+}
+
+/// Templates of the files written next to the projects, read up front so a
+/// broken one fails the run before any file is touched
+#[derive(Default)]
+struct RunTemplates {
+    precommit: Option<String>,
+    publish_workflow: Option<String>,
+    dockerfile: Option<String>,
+    devcontainer: Option<String>,
+}
+
+/// The content of `template`, or `default` when none is configured
+fn read_template(template: Option<&Path>, default: &str, what: &str) -> Result<String> {
+    match template {
+        Some(template) => fs::read_to_string(template)
+            .with_context(|| format!("Failed to read {what} template: {}", template.display())),
+        None => Ok(default.to_string()),
+    }
+}
+
+/// The templates of the files `config` enables
+fn load_run_templates(config: &UvinitConfig) -> Result<RunTemplates> {
+    // Unknown hook ids fail here as well
+    let precommit = config
+        .enable_precommit
+        .then(|| templates::precommit_config(&config.precommit_hooks))
+        .transpose()?;
+    let publish_workflow = config
+        .enable_publish_workflow
+        .then(|| {
+            read_template(
+                config.publish_workflow_template.as_deref(),
+                templates::PUBLISH_WORKFLOW,
+                "workflow",
+            )
+        })
+        .transpose()?;
+    let dockerfile = config
+        .enable_dockerfile
+        .then(|| {
+            read_template(
+                config.dockerfile_template.as_deref(),
+                templates::DOCKERFILE,
+                "Dockerfile",
+            )
+        })
+        .transpose()?;
+    let devcontainer = config
+        .enable_devcontainer
+        .then(|| {
+            read_template(
+                config.devcontainer_template.as_deref(),
+                templates::DEVCONTAINER,
+                "devcontainer",
+            )
+        })
+        .transpose()?;
+    Ok(RunTemplates {
+        precommit,
+        publish_workflow,
+        dockerfile,
+        devcontainer,
+    })
+}
+
+/// The pyproject.toml files a run covers: those found at or below `targets`,
+/// only the uncommitted ones with --dirty, and the ones legacy projects will get
+fn discover_run_files(
+    targets: &[PathBuf],
+    options: &UvinitOptions,
+    config: &UvinitConfig,
+    walk_options: &WalkOptions,
+) -> Result<Vec<PathBuf>> {
+    for path in targets {
+        if path.is_file() {
+            say!("🔍 Using {}", path.display());
+        } else if path.is_dir() {
+            say!(
+                "🔍 Searching for pyproject.toml files in: {}",
+                path.display()
+            );
+        }
+    }
+
+    let scanning = output::progress(None, "🔍 Scanning for pyproject.toml files");
+    let discovery = discover_all(targets, walk_options);
+    scanning.finish_and_clear();
+    let mut discovery = discovery?;
+
+    if options.dirty {
+        let dirty = dirty_files_by_repository(&discovery.files)?;
+        discovery.files.retain(|file| {
+            fs::canonicalize(file).is_ok_and(|real_path| dirty.contains(&real_path))
+        });
+        say!("🧹 Limiting to pyproject.toml files with uncommitted changes");
+    }
+
+    // Legacy projects join as pyproject.toml files that processing creates
+    if config.migrate_setup {
+        for file_path in find_legacy_projects(targets, walk_options)? {
+            say!(
+                "🆕 {} has setup.cfg/setup.py but no pyproject.toml - will create one",
+                file_path.parent().unwrap_or(Path::new(".")).display()
+            );
+            discovery.files.push(file_path);
+        }
+    }
+
+    for file_path in &discovery.files {
+        log::info!("Discovered {}", file_path.display());
+    }
+
+    for collision in &discovery.collisions {
+        log::warn!(
+            "Ignoring {} (canonical: {:?})",
+            collision.path.display(),
+            collision.canonical
+        );
+        let reason = match &collision.canonical {
+            Some(canonical) => format!("same file as {}", canonical.display()),
+            None => "only lowercase pyproject.toml is processed".to_string(),
+        };
+        say!("⚠️  Ignoring {} - {}", collision.path.display(), reason);
+        annotations::warning(Some(&collision.path), &format!("Ignored: {reason}"));
+    }
+
+    Ok(discovery.files)
+}
+
+/// Canonical paths of the files with uncommitted changes in each repository
+/// holding one of `files`
+///
+/// A parent repository lists a nested one or a submodule only by its path,
+/// so each file's own repository is asked.
+fn dirty_files_by_repository(files: &[PathBuf]) -> Result<HashSet<PathBuf>> {
+    let mut toplevels = BTreeSet::new();
+    let mut seen_dirs = HashSet::new();
+    for file in files {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        if seen_dirs.insert(dir) {
+            let toplevel = git::toplevel(dir).with_context(|| {
+                format!(
+                    "--dirty needs {} to be inside a git repository",
+                    file.display()
+                )
+            })?;
+            toplevels.insert(toplevel);
+        }
+    }
+
+    let mut dirty = HashSet::new();
+    for toplevel in toplevels {
+        dirty.extend(
+            git::dirty_files(&toplevel)?
+                .iter()
+                .filter_map(|p| fs::canonicalize(p).ok()),
+        );
+    }
+    Ok(dirty)
+}
+
+/// What a run does with a parsed pyproject.toml
+enum FilePlan {
+    /// Process it, for the logged reason
+    Process(&'static str),
+    /// Leave it alone, for the reason its report gives
+    Skip(&'static str),
+    /// Leave it alone, as it has project.dynamic and nothing left to add
+    Configured,
+}
+
+/// Decide whether `pyproject` needs processing
+fn plan_file(
+    file_path: &Path,
+    pyproject: &Pyproject,
+    members: &WorkspaceMembers,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) -> FilePlan {
+    let doc = &pyproject.doc;
+    match has_dynamic_version(doc) {
+        true if options.fix_requires_only => {
+            FilePlan::Process("has project.dynamic, checking for gaps")
+        }
+        false if options.fix_requires_only => FilePlan::Skip("no project.dynamic"),
+        true if config.import_requirements
+            && has_requirements_files(file_path.parent().unwrap_or(Path::new("."))) =>
+        {
+            FilePlan::Process("has project.dynamic, importing requirements")
+        }
+        true if config.enable_workspace_sources
+            && !missing_workspace_sources(file_path, doc, members).is_empty() =>
+        {
+            FilePlan::Process("has project.dynamic, adding workspace sources")
+        }
+        true if config.detect_namespace_packages
+            && !namespace_wheel_packages(file_path, doc, uses_hatchling(doc)).is_empty() =>
+        {
+            FilePlan::Process("has project.dynamic, listing namespace packages")
+        }
+        true if config.migrate_src_layout && src_layout_package(file_path, doc).is_some() => {
+            FilePlan::Process("has project.dynamic, moving to src layout")
+        }
+        true if options.reprocess => FilePlan::Process("has project.dynamic, reprocessing"),
+        true => FilePlan::Configured,
+        false => FilePlan::Process("needs processing"),
+    }
+}
+
+/// The files a run goes on to process, and what it knows about all it found
+struct Selection<'a> {
+    /// Files to process, already parsed
+    to_process: Vec<(&'a PathBuf, Pyproject)>,
+    /// Reports of the files left alone
+    reports: Vec<FileReport>,
+    /// Project names by file, for the plan
+    names: HashMap<&'a Path, String>,
+    members: WorkspaceMembers,
+}
+
+/// Read and parse each of `pyproject_files` once and sort them into the ones
+/// to process and the ones to leave alone
+fn select_files<'a>(
+    pyproject_files: &'a [PathBuf],
+    mut scan_cache: Option<ScanCache>,
+    walk_options: &WalkOptions,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+    errors: &mut RunErrors,
+) -> Selection<'a> {
+    let flat = !options.tree;
+    let mut reports = Vec::new();
+    let mut to_process = Vec::new();
+
+    // Files the cache knows as configured and unchanged are not read at all
+    let loaded: Vec<Option<Result<Pyproject>>> = pyproject_files
+        .par_iter()
+        .map(|file_path| match file_path.exists() {
+            true if scan_cache
+                .as_ref()
+                .is_some_and(|cache| cache.is_configured(file_path)) =>
+            {
+                None
+            }
+            true => Some(load_pyproject(file_path)),
+            false => Some(Ok(Pyproject {
+                path: file_path.to_path_buf(),
+                content: String::new(),
+                doc: DocumentMut::new(),
+            })),
+        })
+        .collect();
+    let names: HashMap<&Path, String> = pyproject_files
+        .iter()
+        .zip(&loaded)
+        .filter_map(|(file_path, loaded)| {
+            let name = loaded
+                .as_ref()?
+                .as_ref()
+                .ok()?
+                .doc
+                .get("project")?
+                .get("name")?
+                .as_str()?;
+            Some((file_path.as_path(), name.to_string()))
+        })
+        .collect();
+
+    let members = match config.enable_workspace_sources {
+        true => workspace_members(
+            loaded
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|pyproject| (pyproject.path.as_path(), &pyproject.doc)),
+            walk_options,
+        ),
+        false => WorkspaceMembers::new(),
+    };
+
+    for (file_path, loaded) in pyproject_files.iter().zip(loaded) {
+        let Some(loaded) = loaded else {
+            log::info!(
+                "{}: unchanged since found configured, skipping",
+                file_path.display()
+            );
+            reports.push(FileReport::new(file_path, FileStatus::AlreadyConfigured));
+            continue;
+        };
+        let pyproject = match loaded {
+            Ok(pyproject) => pyproject,
+            Err(e) => {
+                log::error!("{}: failed to check: {}", file_path.display(), e);
+                record_error(errors, Some(file_path), &format!("Failed to check: {e}"));
+                if flat {
+                    eprintln!("    ❌ Error checking {}: {}", file_path.display(), e);
+                }
+                reports.push(FileReport::new(
+                    file_path,
+                    FileStatus::Failed(e.to_string()),
+                ));
+                continue;
+            }
+        };
+
+        // An opt-out is permanent, so --force does not override it
+        if is_opted_out(&pyproject) {
+            log::info!("{}: opted out, skipping", file_path.display());
+            reports.push(FileReport::new(
+                file_path,
+                FileStatus::Skipped("opted out".to_string()),
+            ));
+            continue;
+        }
+
+        match plan_file(file_path, &pyproject, &members, config, options) {
+            FilePlan::Process(reason) => {
+                log::info!("{}: {reason}", file_path.display());
+                to_process.push((file_path, pyproject));
+            }
+            FilePlan::Skip(reason) => {
+                log::info!("{}: {reason}, skipping", file_path.display());
+                reports.push(FileReport::new(
+                    file_path,
+                    FileStatus::Skipped(reason.to_string()),
+                ));
+            }
+            FilePlan::Configured => {
+                log::info!("{}: has project.dynamic, skipping", file_path.display());
+                if let Some(cache) = &mut scan_cache {
+                    cache.mark_configured(file_path);
+                }
+                reports.push(FileReport::new(file_path, FileStatus::AlreadyConfigured));
+            }
+        }
+    }
+
+    if config.enable_dynamic_version {
+        skip_competing_sources(&mut to_process, &mut reports, config, options);
+    }
+
+    if let Some(cache) = &mut scan_cache {
+        for (file_path, _) in &to_process {
+            cache.forget(file_path);
+        }
+        if let Err(e) = cache.save() {
+            log::warn!("{e:#}");
+        }
+    }
+
+    Selection {
+        to_process,
+        reports,
+        names,
+        members,
+    }
+}
+
+/// Move the files that take their version from something other than the
+/// configured backend from `to_process` to `reports`, unless reprocessing
+fn skip_competing_sources(
+    to_process: &mut Vec<(&PathBuf, Pyproject)>,
+    reports: &mut Vec<FileReport>,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) {
+    to_process.retain(|(file_path, pyproject)| {
+        let competing =
+            competing_version_sources(file_path, &pyproject.doc, config.version_backend);
+        if competing.is_empty() {
+            return true;
+        }
+        let competing = competing.join(", ");
+        annotations::warning(
+            Some(file_path),
+            &format!("Competing version source: {competing}"),
+        );
+        if options.reprocess {
+            log::warn!(
+                "{}: also uses {competing}, processing anyway",
+                file_path.display()
+            );
+            say!(
+                "  ⚠️  {} also uses {competing} - processing anyway (--reprocess)",
+                file_path.display()
+            );
+            return true;
+        }
+        log::warn!("{}: uses {competing}, skipping", file_path.display());
+        say!(
+            "  ⏭️  {} uses {competing} - skipping (use --reprocess to process anyway)",
+            file_path.display()
+        );
+        reports.push(FileReport::new(
+            file_path,
+            FileStatus::Skipped(format!("competing version source: {competing}")),
+        ));
+        false
+    });
+}
+
+/// Ask which of `to_process` to go ahead with, moving the others to `reports`;
+/// returns false when the user cancelled
+fn confirm_files(
+    to_process: &mut Vec<(&PathBuf, Pyproject)>,
+    reports: &mut Vec<FileReport>,
+    options: &UvinitOptions,
+) -> Result<bool> {
+    let action = if options.out_suffix.is_some() {
+        "write proposals for"
+    } else if options.fix_requires_only {
+        "check and repair"
+    } else {
+        "modify"
+    };
+
+    // Several files on a terminal get a checklist; otherwise it's all or nothing
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let selected: Vec<bool> = if to_process.len() > 1 && interactive {
+        let items: Vec<String> = to_process
+            .iter()
+            .map(|(file_path, _)| file_path.display().to_string())
+            .collect();
+        let chosen = dialoguer::MultiSelect::new()
+            .with_prompt(format!(
+                "\n🔧 Select the files to {action} (space toggles, enter confirms, esc cancels)"
+            ))
+            .items(&items)
+            .defaults(&vec![true; items.len()])
+            .interact_opt()
+            .with_context(|| "Failed to read the file selection")?
+            .unwrap_or_default();
+        (0..items.len()).map(|i| chosen.contains(&i)).collect()
+    } else {
+        let confirmed = output::confirm(&format!(
+            "\n🔧 Will {} {} file(s). Continue?",
+            action,
+            to_process.len()
+        ))?;
+        vec![confirmed; to_process.len()]
+    };
+
+    if !selected.contains(&true) {
+        log::info!("Cancelled at confirmation prompt");
+        say!("❌ Cancelled.");
+        for (file_path, _) in to_process.drain(..) {
+            reports.push(FileReport::new(
+                file_path,
+                FileStatus::Skipped("cancelled".to_string()),
+            ));
+        }
+        return Ok(false);
+    }
+
+    let mut selected = selected.into_iter();
+    to_process.retain(|(file_path, _)| {
+        let keep = selected.next().unwrap_or(false);
+        if !keep {
+            log::info!("{}: deselected", file_path.display());
+            reports.push(FileReport::new(
+                file_path,
+                FileStatus::Skipped("deselected".to_string()),
+            ));
+        }
+        keep
+    });
+    Ok(true)
+}
+
+/// Process `to_process`, in parallel unless overwrites may prompt
+fn process_files(
+    to_process: Vec<(&PathBuf, Pyproject)>,
+    members: &WorkspaceMembers,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) -> Vec<FileReport> {
+    // git is asked once per repository here rather than once per file below
+    let repos = repo_facts(to_process.iter().map(|(file_path, _)| *file_path), config);
+    // Interactive overwrite prompts can't be interleaved, so those runs stay sequential
+    if options.policy == OverwritePolicy::Prompt {
+        return to_process
+            .into_iter()
+            .map(|(file_path, pyproject)| {
+                process_file(file_path, pyproject, members, &repos, config, options)
+            })
+            .collect();
+    }
+    let progress = output::progress(Some(to_process.len() as u64), "🔄 Processing");
+    let processed = to_process
+        .into_par_iter()
+        .map(|(file_path, pyproject)| {
+            let report = process_file(file_path, pyproject, members, &repos, config, options);
+            progress.inc(1);
+            report
+        })
+        .collect();
+    progress.finish_and_clear();
+    processed
+}
+
+/// Log, annotate and, in flat output, print the outcome of a processed file
+fn print_processed(report: &FileReport, options: &UvinitOptions, errors: &mut RunErrors) {
+    let file_path = &report.path;
+    for warning in &report.warnings {
+        log::warn!("{}: {}", file_path.display(), warning);
+        annotations::warning(Some(file_path), warning);
+    }
+    match &report.status {
+        FileStatus::Failed(e) => {
+            log::error!("{}: {}", file_path.display(), e);
+            record_error(errors, Some(file_path), &format!("Failed to process: {e}"));
+        }
+        status => log::info!("{}: {}", file_path.display(), status.describe()),
+    }
+    if options.tree {
+        return;
+    }
+    match &report.status {
+        FileStatus::Modified => say!("  ✅ {}", file_path.display()),
+        FileStatus::WouldChange => {
+            say!("  📝 {}", file_path.display());
+            if let Some(diff) = report.diff.as_ref().filter(|_| !options.check) {
+                say!("{}", diff.trim_end());
+            }
+        }
+        FileStatus::AlreadyConfigured => {
+            say!("  ✅ {} - nothing to repair", file_path.display())
+        }
+        FileStatus::Proposed(out_path) => {
+            say!("  ✅ {} -> {}", file_path.display(), out_path.display())
+        }
+        FileStatus::ProposalSkipped(out_path) => {
+            say!("  ⏭️  {} already exists - skipping", out_path.display())
+        }
+        FileStatus::Failed(e) => eprintln!("  ❌ {}: {}", file_path.display(), e),
+        FileStatus::Skipped(_) => {}
+    }
+    if options.fix_requires_only {
+        for change in &report.changes {
+            say!("      filled: {change}");
+        }
+    }
+    for warning in &report.warnings {
+        say!("    ⚠️  {warning}");
+    }
+}
+
+/// What the files of a run held before processing
+struct Before {
+    /// Static versions, which name the first tag after processing removed them
+    static_versions: HashMap<PathBuf, String>,
+    /// Content of the files that existed, for the journal
+    originals: HashMap<PathBuf, String>,
+}
+
+impl Before {
+    fn of(to_process: &[(&PathBuf, Pyproject)]) -> Self {
+        let static_versions = to_process
+            .iter()
+            .filter_map(|(file_path, pyproject)| {
+                static_version(&pyproject.doc).map(|version| (file_path.to_path_buf(), version))
+            })
+            .collect();
+        let originals = to_process
+            .iter()
+            .filter(|(file_path, _)| file_path.exists())
+            .map(|(file_path, pyproject)| (file_path.to_path_buf(), pyproject.content.clone()))
+            .collect();
+        Before {
+            static_versions,
+            originals,
+        }
+    }
+}
+
+/// Write the files that go next to the modified projects
+fn write_companion_files(
+    reports: &[FileReport],
+    templates: &RunTemplates,
+    config: &UvinitConfig,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    if config.enable_version_file {
+        write_version_imports(reports, journal, errors);
+    }
+    if config.enable_py_typed {
+        write_py_typed_markers(reports, journal, errors);
+    }
+    if !config.scripts.is_empty() {
+        write_main_modules(reports, journal, errors);
+    }
+    write_license_files(reports, policy, journal, errors);
+    if config.enable_python_version_file {
+        write_python_version_files(reports, config.python_version.as_deref(), journal, errors);
+    }
+    if let Some(precommit) = &templates.precommit {
+        write_precommit_configs(
+            reports,
+            precommit,
+            config.precommit_install,
+            policy,
+            journal,
+            errors,
+        );
+    }
+    if let Some(template) = &templates.publish_workflow {
+        write_publish_workflows(reports, template, config, policy, journal, errors);
+    }
+    if let Some(template) = &templates.dockerfile {
+        write_dockerfiles(
+            reports,
+            template,
+            config.python_version.as_deref(),
+            policy,
+            journal,
+            errors,
+        );
+    }
+    if let Some(template) = &templates.devcontainer {
+        write_devcontainers(
+            reports,
+            template,
+            &config.devcontainer_extensions,
+            config.python_version.as_deref(),
+            policy,
+            journal,
+            errors,
+        );
+    }
+    if config.dependency_updates != DependencyUpdates::None {
+        write_dependency_update_configs(
+            reports,
+            config.dependency_updates,
+            policy,
+            journal,
+            errors,
+        );
+    }
+}
+
+/// The steps after processing: companion files, git, validation and the journal
+fn run_post_steps(
+    reports: &mut [FileReport],
+    templates: &RunTemplates,
+    before: &Before,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+    errors: &mut RunErrors,
+) {
+    let in_place = !options.dry_run && options.out_suffix.is_none();
+    let mut journal_entries: Vec<journal::Entry> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .flat_map(|r| {
+            let entry = fs::read_to_string(&r.path)
+                .ok()
+                .map(|after| journal::Entry {
+                    path: std::path::absolute(&r.path).unwrap_or_else(|_| r.path.clone()),
+                    before: before.originals.get(&r.path).cloned(),
+                    after,
+                });
+            r.rewritten.iter().cloned().chain(entry)
+        })
+        .collect();
+    let moves: Vec<SrcMove> = reports.iter().filter_map(|r| r.moved.clone()).collect();
+
+    if in_place {
+        write_companion_files(
+            reports,
+            templates,
+            config,
+            options.policy,
+            &mut journal_entries,
+            errors,
+        );
+    }
+
+    if config.git_commit && in_place {
+        commit_written_files(
+            reports,
+            &journal_entries,
+            &config.git_commit_message,
+            errors,
+        );
+    } else if options.git_add {
+        stage_written_files(reports, &journal_entries, errors);
+    }
+
+    if config.create_version_tag && in_place {
+        tag_initial_versions(reports, &before.static_versions, config, errors);
+    }
+
+    if in_place {
+        validate_written_files(reports, config.validate, errors);
+    }
+
+    if config.journal_runs && !journal_entries.is_empty() {
+        match Journal::open().and_then(|journal| journal.record(journal_entries, moves)) {
+            Ok(run) => {
+                log::info!("Recorded run {}", run.id);
+                say!(
+                    "\n📓 Recorded run {} - revert it with `post-init undo`",
+                    run.id
+                );
+            }
+            Err(e) => {
+                log::warn!("Failed to record the run: {e:#}");
+                eprintln!("  ⚠️  Failed to record the run for undo: {e:#}");
+            }
+        }
+    }
+}
+
+/// The closing line of a run with nothing to process, unless failures or
+/// skipped files leave it to the summary and exit status
+fn nothing_to_process_message(
+    reports: &[FileReport],
+    options: &UvinitOptions,
+) -> Option<&'static str> {
+    if options.fix_requires_only {
+        let failed = reports
+            .iter()
+            .any(|r| matches!(r.status, FileStatus::Failed(_)));
+        (!failed).then_some("✅ No files with project.dynamic to repair!")
+    } else {
+        reports
+            .iter()
+            .all(|r| r.status == FileStatus::AlreadyConfigured)
+            .then_some("✅ All files already have project.dynamic configured!")
+    }
+}
+
+pub fn run_uvinit(
+    targets: &[PathBuf],
+    options: &UvinitOptions,
+    config_args: &ConfigArgs,
+) -> Result<ExitCode> {
+    let config = load_uvinit_config(options, config_args)?;
+    let uvinit_config = &config.uvinit;
+    let mut errors = RunErrors::new();
+    let templates = load_run_templates(uvinit_config)?;
+
+    let walk_options = walk_options(uvinit_config);
+    let path = &common_dir(targets);
+    let pyproject_files = discover_run_files(targets, options, uvinit_config, &walk_options)?;
+
+    if pyproject_files.is_empty() {
+        say!("❌ No pyproject.toml files found.");
+        return finish_run(path, options, &mut [], &errors);
+    }
+
+    say!("📦 Found {} pyproject.toml file(s):", pyproject_files.len());
+
+    let Selection {
+        mut to_process,
+        mut reports,
+        names,
+        members,
+    } = select_files(
+        &pyproject_files,
+        open_scan_cache(path, uvinit_config, options),
+        &walk_options,
+        uvinit_config,
+        options,
+        &mut errors,
+    );
+
+    if !options.tree {
+        let rows = plan_rows(
+            &pyproject_files,
+            &reports,
+            &to_process,
+            &names,
+            &members,
+            uvinit_config,
+            options,
+        );
+        say!("{}", render_plan(&rows).trim_end());
+    }
+
+    if to_process.is_empty() {
+        let code = finish_run(path, options, &mut reports, &errors)?;
+        if let Some(message) = nothing_to_process_message(&reports, options) {
+            say!("{message}");
+        }
+        return Ok(code);
+    }
+
+    // Nothing is written in a dry run, so there is nothing to confirm
+    if !options.yes && !options.dry_run && !confirm_files(&mut to_process, &mut reports, options)? {
+        return finish_run(path, options, &mut reports, &errors);
+    }
+
+    if options.check {
+        say!("\n🔍 Check - no files will be written");
+    } else if options.dry_run {
+        say!("\n🔍 Dry run - no files will be written");
+    } else {
+        say!("\n🔄 Processing files...");
+    }
+
+    let before = Before::of(&to_process);
+    // Results are printed after the parallel section so output stays in discovery order
+    for report in process_files(to_process, &members, uvinit_config, options) {
+        print_processed(&report, options, &mut errors);
+        reports.push(report);
+    }
+
+    run_post_steps(
+        &mut reports,
+        &templates,
+        &before,
+        uvinit_config,
+        options,
+        &mut errors,
+    );
+
+    let code = finish_run(path, options, &mut reports, &errors)?;
+
+    if options.dry_run {
+        let would_change = reports
+            .iter()
+            .filter(|r| r.status == FileStatus::WouldChange)
+            .count();
+        if options.check {
+            say!("\n🔍 Check: {would_change} file(s) need changes");
+        } else {
+            say!("\n🔍 Dry run: {would_change} file(s) would be changed");
+        }
+        return Ok(code);
+    }
+
+    say!("\n🎉 Done!");
+    Ok(code)
+}
+
+fn process_file(
+    file_path: &Path,
+    pyproject: Pyproject,
+    members: &WorkspaceMembers,
+    repos: &RepoFactsByRoot,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) -> FileReport {
+    let report = |status, rendered: Option<Rendered>| {
+        let (changes, warnings) = rendered
+            .map(|r| (r.changes, r.warnings))
+            .unwrap_or_default();
+        FileReport {
+            path: file_path.to_path_buf(),
+            status,
+            changes,
+            warnings,
+            diff: None,
+            moved: None,
+            rewritten: Vec::new(),
+        }
+    };
+
+    let mut rendered = if options.fix_requires_only {
+        repair_document(pyproject, config)
+    } else {
+        let dir = file_path.parent().unwrap_or(Path::new("."));
+        let repo = repos
+            .get(&git::work_tree_root(dir))
+            .cloned()
+            .unwrap_or_default();
+        render_document(pyproject, config, members, &repo)
+    };
+    // Nothing to write: no backup, journal entry, staging or post-writers follow
+    if rendered.changes.is_empty() || rendered.content == rendered.original {
+        return report(FileStatus::AlreadyConfigured, Some(rendered));
+    }
+
+    if options.dry_run {
+        let diff = unified_diff(file_path, &rendered.original, &rendered.content);
+        let mut dry_run = report(FileStatus::WouldChange, Some(rendered));
+        dry_run.diff = Some(diff);
+        return dry_run;
+    }
+
+    let Some(suffix) = &options.out_suffix else {
+        // Planned up front, so the test files it rewrites are backed up before anything moves
+        let project_dir = file_path.parent().unwrap_or(Path::new("."));
+        let migration = match rendered
+            .src_layout_package
+            .as_deref()
+            .map(|module| layout::SrcMigration::plan(project_dir, module))
+            .transpose()
+        {
+            Ok(migration) => migration,
+            Err(e) => return report(FileStatus::Failed(e.to_string()), None),
+        };
+        let rewritten: Vec<journal::Entry> = migration
+            .iter()
+            .flat_map(|migration| &migration.rewrites)
+            .map(|rewrite| journal::Entry::before_write(&rewrite.path, &rewrite.content))
+            .collect();
+
+        // The backups come first and the package moves next, so a failure in
+        // either leaves pyproject.toml untouched
+        return match backup_if_enabled(file_path, config, &rendered, migration.as_ref())
+            .and_then(|_| {
+                migration
+                    .as_ref()
+                    .map_or(Ok(()), layout::SrcMigration::apply)
+            })
+            .and_then(|_| write_file(file_path, &rendered.content, OverwritePolicy::Force))
+        {
+            Ok(_) => {
+                rendered.warnings.extend(
+                    migration
+                        .iter()
+                        .flat_map(|migration| migration.warnings.iter().cloned()),
+                );
+                let mut modified = report(FileStatus::Modified, Some(rendered));
+                modified.moved = migration.map(|migration| migration.src_move);
+                modified.rewritten = rewritten;
+                modified
+            }
+            Err(e) => report(FileStatus::Failed(e.to_string()), None),
+        };
+    };
+
+    let out_path = proposed_path(file_path, suffix);
+    match write_file(&out_path, &rendered.content, options.policy) {
+        Ok(WriteOutcome::Skipped) => report(FileStatus::ProposalSkipped(out_path), None),
+        Ok(_) => report(FileStatus::Proposed(out_path), Some(rendered)),
+        Err(e) => report(FileStatus::Failed(e.to_string()), None),
+    }
+}
+
+/// Keep the pre-edit content of an in-place write, and of the test files
+/// `migration` rewrites, in the backup store for `restore`; call it before
+/// the write, which should not happen if it fails
+pub(crate) fn backup_if_enabled(
+    file_path: &Path,
+    config: &UvinitConfig,
+    rendered: &Rendered,
+    migration: Option<&layout::SrcMigration>,
+) -> Result<()> {
+    if !config.backup_before_write {
+        return Ok(());
+    }
+    let store = BackupStore::open()?;
+    for rewrite in migration.iter().flat_map(|migration| &migration.rewrites) {
+        let written = with_line_endings(Some(&rewrite.original), &rewrite.content);
+        let backup = store.write(&rewrite.path, &rewrite.original, &written)?;
+        log::info!(
+            "Backed up {} to {}",
+            rewrite.path.display(),
+            backup.display()
+        );
+    }
+
+    // A file the run created has nothing to back up
+    if !rendered.original.is_empty() {
+        // What write_file will actually leave on disk
+        let written = with_line_endings(Some(&rendered.original), &rendered.content);
+        let backup = store.write(file_path, &rendered.original, &written)?;
+        if let Some(migration) = migration {
+            store.record_move(file_path, &migration.src_move)?;
+        }
+        log::info!("Backed up {} to {}", file_path.display(), backup.display());
+    }
+    Ok(())
+}
+
+/// Unified diff of a file's content before and after rendering
+fn unified_diff(file_path: &Path, original: &str, content: &str) -> String {
+    let name = file_path.display().to_string();
+    similar::TextDiff::from_lines(original, content)
+        .unified_diff()
+        .header(&name, &name)
+        .to_string()
+}
+
+/// Paths the run wrote in place, as recorded in `journal`: pyproject.toml,
+/// the test files rewritten for a package move and every companion file,
+/// plus both sides of each package move
+///
+/// The old package path is left out when git never tracked it, as there is
+/// no removal to stage then.
+fn run_written_paths<'a>(
+    reports: &'a [FileReport],
+    journal: &'a [journal::Entry],
+) -> Vec<&'a Path> {
+    let mut paths: Vec<&Path> = journal.iter().map(|entry| entry.path.as_path()).collect();
+    for moved in reports.iter().filter_map(|r| r.moved.as_ref()) {
+        paths.push(&moved.to);
+        if git::is_tracked(&moved.from) {
+            paths.push(&moved.from);
+        }
+    }
+    let mut seen = HashSet::new();
+    paths.retain(|path| seen.insert(*path));
+    paths
+}
+
+/// `git add` every file written by the run, skipping files outside a repository
+fn stage_written_files(reports: &[FileReport], journal: &[journal::Entry], errors: &mut RunErrors) {
+    say!("\n📌 Staging modified files...");
+
+    let proposed = reports.iter().filter_map(|r| match &r.status {
+        FileStatus::Proposed(out_path) => Some(out_path.as_path()),
+        _ => None,
+    });
+    for file in run_written_paths(reports, journal)
+        .into_iter()
+        .chain(proposed)
+    {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        if !git::is_inside_work_tree(dir) {
+            say!(
+                "  ⚠️  {} is not in a git repository - skipping",
+                file.display()
+            );
+            annotations::warning(Some(file), "Not in a git repository; not staged");
+            continue;
+        }
+
+        match git::add(file) {
+            Ok(()) => say!("  ➕ {}", file.display()),
+            Err(e) => {
+                eprintln!("  ❌ {e}");
+                record_error(errors, Some(file), &e.to_string());
+            }
+        }
+    }
+}
+
+/// uv arguments that perform `validation`
+fn validation_args(validation: Validation) -> Option<&'static [&'static str]> {
+    match validation {
+        Validation::None => None,
+        Validation::Lock => Some(&["lock", "--check"]),
+        Validation::Build => Some(&["build", "--no-build-isolation", "-q"]),
+    }
+}
+
+/// Run the validation in the directory of each pyproject.toml modified in
+/// place, recording failures as warnings on its report
+fn validate_written_files(
+    reports: &mut [FileReport],
+    validation: Validation,
+    errors: &mut RunErrors,
+) {
+    let Some(args) = validation_args(validation) else {
+        return;
+    };
+    if !reports.iter().any(|r| r.status == FileStatus::Modified) {
+        return;
+    }
+
+    let command = format!("uv {}", args.join(" "));
+    say!("\n🔎 Validating with {command}...");
+    for report in reports
+        .iter_mut()
+        .filter(|r| r.status == FileStatus::Modified)
+    {
+        let dir = report
+            .path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let output = match Command::new("uv").args(args).current_dir(dir).output() {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("  ❌ Failed to run uv; is it installed? ({e})");
+                return;
+            }
+        };
+        log::info!(
+            "{command} in {} exited with {}",
+            dir.display(),
+            output.status
+        );
+        if output.status.success() {
+            say!("  ✅ {}", report.path.display());
+            continue;
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("no output");
+        let warning = format!("{command} failed: {detail}");
+        eprintln!("  ❌ {}: {warning}", report.path.display());
+        record_error(errors, Some(&report.path), &warning);
+        report.warnings.push(warning);
+    }
+}
+
+/// Import `__version__` from the hatch-vcs version file in the `__init__.py`
+/// of each pyproject.toml modified in place that configures one
+fn write_version_imports(
+    reports: &[FileReport],
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let inits: Vec<(PathBuf, String)> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .filter_map(|r| {
+            let doc = load_pyproject(&r.path).ok()?.doc;
+            let version_file = doc
+                .get("tool")?
+                .get("hatch")?
+                .get("build")?
+                .get("hooks")?
+                .get("vcs")?
+                .get("version-file")?
+                .as_str()?;
+            let version_file = r.path.parent().unwrap_or(Path::new(".")).join(version_file);
+            let version_module = version_file.file_stem()?.to_str()?.to_string();
+            Some((version_file.parent()?.join("__init__.py"), version_module))
+        })
+        .filter(|(init, _)| init.is_file())
+        .collect();
+
+    let mut announced = false;
+    for (init, version_module) in inits {
+        let content = match fs::read_to_string(&init) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("  ❌ Failed to read {}: {e}", init.display());
+                continue;
+            }
+        };
+        let Some(patched) = layout::add_version_import(&content, &version_module) else {
+            continue;
+        };
+        if !announced {
+            say!("\n🔢 Importing __version__ from the version file...");
+            announced = true;
+        }
+        write_generated_file(
+            &init,
+            &patched,
+            OverwritePolicy::Force,
+            journal,
+            errors,
+            None,
+        );
+    }
+}
+
+/// Write a generated file at `path`, recording it in `journal` and reporting
+/// the outcome with `label` after the path; returns whether it was written
+fn write_generated_file(
+    path: &Path,
+    content: &str,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+    label: Option<&str>,
+) -> bool {
+    let entry = journal::Entry::before_write(path, content);
+    match write_file(path, content, policy) {
+        Ok(WriteOutcome::Skipped) => {
+            say!("  ⏭️  {} already exists - skipping", path.display());
+            false
+        }
+        Ok(_) => {
+            journal.push(entry);
+            let label = label.map(|label| format!(" ({label})")).unwrap_or_default();
+            log::info!("Wrote {}{label}", path.display());
+            say!("  ✅ {}{label}", path.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("  ❌ {e:#}");
+            record_error(errors, Some(path), &format!("{e:#}"));
+            false
+        }
+    }
+}
+
+/// Write `render`'s output for each pyproject.toml modified in place at
+/// `relative` to its directory, announcing the step with `heading`
+fn write_project_files(
+    reports: &[FileReport],
+    heading: &str,
+    relative: &Path,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+    render: impl Fn(&DocumentMut) -> String,
+) {
+    let modified: Vec<&Path> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .map(|r| r.path.as_path())
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    say!("\n{heading}");
+    for file_path in modified {
+        let Ok(pyproject) = load_pyproject(file_path) else {
+            continue;
+        };
+        let target = file_path.parent().unwrap_or(Path::new(".")).join(relative);
+        write_generated_file(
+            &target,
+            &render(&pyproject.doc),
+            policy,
+            journal,
+            errors,
+            None,
+        );
+    }
+}
+
+/// Create an empty py.typed in the package of each pyproject.toml modified in place
+fn write_py_typed_markers(
+    reports: &[FileReport],
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let packages: Vec<PathBuf> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .filter_map(|r| {
+            let doc = load_pyproject(&r.path).ok()?.doc;
+            let module = module_name(doc.get("project")?.get("name")?.as_str()?);
+            layout::package_dir(r.path.parent().unwrap_or(Path::new(".")), &module)
+        })
+        .collect();
+    if packages.is_empty() {
+        return;
+    }
+
+    say!("\n🏷️  Writing py.typed markers...");
+    for package in packages {
+        let marker = package.join("py.typed");
+        write_generated_file(
+            &marker,
+            "",
+            OverwritePolicy::SkipExisting,
+            journal,
+            errors,
+            None,
+        );
+    }
+}
+
+/// Add the `scripts` entries `[project.scripts]` lacks, filling in the `{{name}}`
+/// and `{{module}}` placeholders
+fn add_scripts(
+    doc: &mut DocumentMut,
+    scripts: &BTreeMap<String, String>,
+    name: &str,
+    module: &str,
+    changes: &mut Vec<Change>,
+) {
+    let Some(table) = doc
+        .get_mut("project")
+        .and_then(Item::as_table_mut)
+        .and_then(|project| {
+            project
+                .entry("scripts")
+                .or_insert(toml_edit::table())
+                .as_table_mut()
+        })
+    else {
+        return;
+    };
+    let values = [("name", name), ("module", module)];
+    for (script, target) in scripts {
+        let script = templates::render(script, &values);
+        let target = templates::render(target, &values);
+        insert_missing(
+            table,
+            &format!("project.scripts.{script}"),
+            target.as_str().into(),
+            changes,
+        );
+    }
+}
+
+/// Fill `[tool.commitizen]` so `cz bump` reads and tags versions in git,
+/// with tags named like the ones `--tag` creates
+fn set_commitizen(doc: &mut DocumentMut, tag_format: &str, changes: &mut Vec<Change>) {
+    let Some(commitizen) = table_path_mut(doc.as_table_mut(), &["tool", "commitizen"]) else {
+        return;
+    };
+
+    let mut values = toml::Table::new();
+    values.insert("name".to_string(), "cz_conventional_commits".into());
+    values.insert("version_provider".to_string(), "scm".into());
+    values.insert(
+        "tag_format".to_string(),
+        tag_format.replace("{version}", "$version").into(),
+    );
+    merge_missing(commitizen, "tool.commitizen", &values, changes);
+}
+
+/// `(package, function)` of a console script run from `<package>.__main__`
+fn main_module_target(target: &str) -> Option<(&str, &str)> {
+    let (module, function) = target.split_once(':')?;
+    let package = module.trim().strip_suffix(".__main__")?;
+    let function = function.trim();
+    (!package.contains('.') && !function.is_empty()).then_some((package, function))
+}
+
+/// Create `__main__.py` stubs for the console scripts of each pyproject.toml
+/// modified in place that point at one that doesn't exist
+fn write_main_modules(
+    reports: &[FileReport],
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let mut stubs: Vec<(PathBuf, String, String)> = Vec::new();
+    for report in reports.iter().filter(|r| r.status == FileStatus::Modified) {
+        let Ok(pyproject) = load_pyproject(&report.path) else {
+            continue;
+        };
+        let project = pyproject.doc.get("project");
+        let name = project
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("package");
+        let Some(scripts) = project
+            .and_then(|p| p.get("scripts"))
+            .and_then(Item::as_table_like)
+        else {
+            continue;
+        };
+        let dir = report.path.parent().unwrap_or(Path::new("."));
+        for (_, target) in scripts.iter() {
+            let Some((package, function)) = target.as_str().and_then(main_module_target) else {
+                continue;
+            };
+            if let Some(package_dir) = layout::package_dir(dir, package) {
+                let stub = package_dir.join("__main__.py");
+                if !stub.exists() && !stubs.iter().any(|(path, _, _)| *path == stub) {
+                    stubs.push((stub, name.to_string(), function.to_string()));
+                }
+            }
+        }
+    }
+    if stubs.is_empty() {
+        return;
+    }
+
+    say!("\n▶️  Writing __main__.py stubs...");
+    for (stub, name, function) in stubs {
+        let content = templates::render(
+            templates::MAIN_MODULE,
+            &[("name", &name), ("function", &function)],
+        );
+        write_generated_file(
+            &stub,
+            &content,
+            OverwritePolicy::SkipExisting,
+            journal,
+            errors,
+            None,
+        );
+    }
+}
+
+/// Python used in container images of projects without a requires-python lower bound
+const IMAGE_DEFAULT_PYTHON: &str = "3.12";
+
+/// Python for a project's container image; image tags name a minor version,
+/// like `3.12`
+fn image_python_version(doc: &DocumentMut, configured_python: Option<&str>) -> String {
+    python_version_for(doc, configured_python)
+        .map(|version| version.split('.').take(2).collect::<Vec<_>>().join("."))
+        .unwrap_or_else(|| IMAGE_DEFAULT_PYTHON.to_string())
+}
+
+/// Dockerfile for the project in `doc`, running its first console script or
+/// else its package as a module
+fn render_dockerfile(template: &str, doc: &DocumentMut, configured_python: Option<&str>) -> String {
+    let project = doc.get("project");
+    let name = project
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or("app");
+    let module = module_name(name);
+    let python_version = image_python_version(doc, configured_python);
+    let script = project
+        .and_then(|p| p.get("scripts"))
+        .and_then(Item::as_table_like)
+        .and_then(|scripts| scripts.iter().next().map(|(script, _)| script.to_string()));
+    let command = match script {
+        Some(script) => format!("[\"{script}\"]"),
+        None => format!("[\"python\", \"-m\", \"{module}\"]"),
+    };
+    templates::render(
+        template,
+        &[
+            ("name", name),
+            ("module", &module),
+            ("python_version", &python_version),
+            ("command", &command),
+        ],
+    )
+}
+
+/// Write a Dockerfile from `template` next to each pyproject.toml modified in place
+fn write_dockerfiles(
+    reports: &[FileReport],
+    template: &str,
+    configured_python: Option<&str>,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    write_project_files(
+        reports,
+        "🐳 Writing Dockerfiles...",
+        Path::new("Dockerfile"),
+        policy,
+        journal,
+        errors,
+        |doc| render_dockerfile(template, doc, configured_python),
+    );
+}
+
+/// devcontainer.json for the project in `doc`, recommending `extensions`
+fn render_devcontainer(
+    template: &str,
+    doc: &DocumentMut,
+    extensions: &[String],
+    configured_python: Option<&str>,
+) -> String {
+    let name = doc
+        .get("project")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or("app");
+    let python_version = image_python_version(doc, configured_python);
+    let extensions = serde_json::to_string(extensions).unwrap_or_else(|_| "[]".to_string());
+    templates::render(
+        template,
+        &[
+            ("name", name),
+            ("python_version", &python_version),
+            ("extensions", &extensions),
+        ],
+    )
+}
+
+/// Write `.devcontainer/devcontainer.json` from `template` next to each
+/// pyproject.toml modified in place
+fn write_devcontainers(
+    reports: &[FileReport],
+    template: &str,
+    extensions: &[String],
+    configured_python: Option<&str>,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    write_project_files(
+        reports,
+        "📦 Writing dev containers...",
+        &Path::new(".devcontainer").join("devcontainer.json"),
+        policy,
+        journal,
+        errors,
+        |doc| render_devcontainer(template, doc, extensions, configured_python),
+    );
+}
+
+/// Names a project's license text may already go by
+const LICENSE_FILE_NAMES: &[&str] = &[
+    "LICENSE",
+    "LICENSE.txt",
+    "LICENSE.md",
+    "LICENCE",
+    "LICENCE.txt",
+    "COPYING",
+];
+
+/// Copyright holder for a project's LICENSE: its first listed author, else
+/// the git user, else its contributors
+fn license_author(doc: &DocumentMut, dir: &Path) -> String {
+    let project = doc.get("project");
+    project
+        .and_then(|p| p.get("authors"))
+        .and_then(Item::as_array)
+        .and_then(|authors| {
+            authors
+                .iter()
+                .find_map(|a| a.as_inline_table()?.get("name")?.as_str())
+        })
+        .map(str::to_string)
+        .or_else(|| git::config_value(dir, "user.name"))
+        .unwrap_or_else(|| {
+            let name = project
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .unwrap_or("project");
+            format!("The {name} contributors")
+        })
+}
+
+/// Write a LICENSE next to each modified pyproject.toml whose project.license
+/// this run set, unless the project already ships a license file
+fn write_license_files(
+    reports: &[FileReport],
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let licensed: Vec<&Path> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .filter(|r| r.changes.iter().any(|c| c.key() == "project.license"))
+        .map(|r| r.path.as_path())
+        .collect();
+    if licensed.is_empty() {
+        return;
+    }
+
+    say!("\n⚖️  Writing LICENSE files...");
+    let year = chrono::Local::now().format("%Y").to_string();
+    for file_path in licensed {
+        let dir = file_path.parent().unwrap_or(Path::new("."));
+        if let Some(existing) = LICENSE_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+        {
+            say!("  ⏭️  {} already exists - skipping", existing.display());
+            continue;
+        }
+        let Ok(pyproject) = load_pyproject(file_path) else {
+            continue;
+        };
+        let Some(spdx) = pyproject
+            .doc
+            .get("project")
+            .and_then(|p| p.get("license"))
+            .and_then(|l| l.as_str())
+        else {
+            continue;
+        };
+        let target = dir.join("LICENSE");
+        let Some(text) = templates::license(spdx) else {
+            say!(
+                "  ⚠️  No license text for '{spdx}' - add {} by hand",
+                target.display()
+            );
+            continue;
+        };
+        let author = license_author(&pyproject.doc, dir);
+        let content = templates::render(text, &[("year", &year), ("author", &author)]);
+        write_generated_file(&target, &content, policy, journal, errors, Some(spdx));
+    }
+}
+
+/// Interpreter version for a project's `.python-version`: `configured`, or
+/// else the lower bound of its requires-python
+fn python_version_for(doc: &DocumentMut, configured: Option<&str>) -> Option<String> {
+    configured
+        .or_else(|| {
+            doc.get("project")
+                .and_then(|p| p.get("requires-python"))
+                .and_then(|r| r.as_str())
+                .and_then(lower_bound)
+                .filter(|bound| release_numbers(bound).is_some())
+        })
+        .map(str::to_string)
+}
+
+/// Write or update `.python-version` next to each pyproject.toml modified in place
+fn write_python_version_files(
+    reports: &[FileReport],
+    configured: Option<&str>,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let modified: Vec<&Path> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .map(|r| r.path.as_path())
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    say!("\n🐍 Writing .python-version files...");
+    for file_path in modified {
+        let target = file_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(".python-version");
+        let version = load_pyproject(file_path)
+            .ok()
+            .and_then(|pyproject| python_version_for(&pyproject.doc, configured));
+        let Some(version) = version else {
+            say!(
+                "  ⏭️  {} has no requires-python lower bound - skipping",
+                file_path.display()
+            );
+            continue;
+        };
+        let content = format!("{version}\n");
+        if fs::read_to_string(&target).is_ok_and(|current| current.trim() == version) {
+            say!("  ✅ {} already pins {version}", target.display());
+            continue;
+        }
+
+        write_generated_file(
+            &target,
+            &content,
+            OverwritePolicy::Force,
+            journal,
+            errors,
+            Some(&version),
+        );
+    }
+}
+
+/// Write `content` as .pre-commit-config.yaml next to each pyproject.toml
+/// modified in place, then run `pre-commit install` there if asked to
+fn write_precommit_configs(
+    reports: &[FileReport],
+    content: &str,
+    install: bool,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let dirs: Vec<&Path> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .map(|r| r.path.parent().unwrap_or(Path::new(".")))
+        .collect();
+    if dirs.is_empty() {
+        return;
+    }
+
+    say!("\n🪝 Writing pre-commit configuration...");
+    for dir in dirs {
+        let config_path = dir.join(".pre-commit-config.yaml");
+        if !write_generated_file(&config_path, content, policy, journal, errors, None) {
+            continue;
+        }
+
+        if install {
+            install_precommit(dir);
+        }
+    }
+}
+
+/// Newest Python minor version the generated workflows test against
+const LATEST_PYTHON_MINOR: u64 = 14;
+
+/// Python 3 versions allowed by a requires-python specifier, from its `>=`
+/// bound (3.10 without one) up to its `<` or `<=` bound or the newest release
+fn python_versions(requires_python: Option<&str>) -> Vec<String> {
+    let minor = |version: Option<&str>| {
+        version
+            .and_then(release_numbers)
+            .filter(|numbers| numbers.first() == Some(&3))
+            .and_then(|numbers| numbers.get(1).copied())
+    };
+    let first = minor(requires_python.and_then(lower_bound)).unwrap_or(10);
+    let last = requires_python
+        .and_then(|spec| {
+            spec.split(',')
+                .map(str::trim)
+                .find_map(|clause| match clause.strip_prefix("<=") {
+                    Some(bound) => minor(Some(bound.trim())),
+                    None => minor(clause.strip_prefix('<').map(str::trim))
+                        .map(|end| end.saturating_sub(1)),
+                })
+        })
+        .unwrap_or(LATEST_PYTHON_MINOR);
+
+    (first..=last.max(first))
+        .map(|minor| format!("3.{minor}"))
+        .collect()
+}
+
+/// What a run with `config` will do to the pyproject.toml at `file_path`,
+/// in the order the transforms apply
+fn planned_actions(
+    file_path: &Path,
+    pyproject: &Pyproject,
+    members: &WorkspaceMembers,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) -> Vec<&'static str> {
+    if options.fix_requires_only {
+        return vec!["repair build requirements"];
+    }
+    let dir = file_path.parent().unwrap_or(Path::new("."));
+    let mut actions = Vec::new();
+    if config.migrate_setup && !file_path.exists() {
+        actions.push("create from setup.cfg/setup.py");
+    }
+    if config.enable_dynamic_version && !has_dynamic_version(&pyproject.doc) {
+        actions.push("dynamic version");
+    }
+    if config.import_requirements && has_requirements_files(dir) {
+        actions.push("import requirements");
+    }
+    if config.enable_workspace_sources
+        && !missing_workspace_sources(file_path, &pyproject.doc, members).is_empty()
+    {
+        actions.push("workspace sources");
+    }
+    if config.migrate_src_layout && src_layout_package(file_path, &pyproject.doc).is_some() {
+        actions.push("move to src/");
+    } else if config.detect_namespace_packages
+        && !namespace_wheel_packages(file_path, &pyproject.doc, uses_hatchling(&pyproject.doc))
+            .is_empty()
+    {
+        actions.push("namespace packages");
+    }
+    for (enabled, action) in [
+        (config.enable_ruff, "ruff"),
+        (config.enable_mypy, "mypy"),
+        (config.type_checker == TypeChecker::Pyright, "pyright"),
+        (config.type_checker == TypeChecker::Ty, "ty"),
+        (config.enable_bandit, "bandit"),
+        (config.enable_coverage, "coverage"),
+        (config.enable_pytest_asyncio, "pytest-asyncio"),
+        (config.enable_metadata, "metadata"),
+        (config.derive_classifiers, "classifiers"),
+        (config.enable_dependency_groups, "dependency groups"),
+        (!config.scripts.is_empty(), "scripts"),
+        (config.enable_commitizen, "commitizen"),
+    ] {
+        if enabled {
+            actions.push(action);
+        }
+    }
+    if actions.is_empty() {
+        actions.push("reprocess");
+    }
+    actions
+}
+
+/// One plan row per discovered file, in discovery order, grouped later by
+/// the git repository it belongs to
+fn plan_rows(
+    pyproject_files: &[PathBuf],
+    reports: &[FileReport],
+    files_to_process: &[(&PathBuf, Pyproject)],
+    names: &HashMap<&Path, String>,
+    members: &WorkspaceMembers,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) -> Vec<PlanRow> {
+    let mut roots: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+    pyproject_files
+        .iter()
+        .map(|file_path| {
+            let dir = file_path.parent().unwrap_or(Path::new("."));
+            let repo = roots
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| git::toplevel(dir).ok())
+                .clone();
+            let real_dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+            let relative = match &repo {
+                Some(root) => real_dir.strip_prefix(root).unwrap_or(&real_dir),
+                None => dir,
+            };
+            let relative = match relative.as_os_str().is_empty() {
+                true => ".".to_string(),
+                false => relative.to_string_lossy().replace('\\', "/"),
+            };
+            let package = match names.get(file_path.as_path()) {
+                Some(name) => format!("{name} ({relative})"),
+                None => relative,
+            };
+
+            let (state, actions) =
+                match files_to_process.iter().find(|(path, _)| *path == file_path) {
+                    Some((_, pyproject)) => {
+                        let state = if !file_path.exists() {
+                            "not created yet"
+                        } else if has_dynamic_version(&pyproject.doc) {
+                            "has project.dynamic"
+                        } else {
+                            "no project.dynamic"
+                        };
+                        let actions =
+                            planned_actions(file_path, pyproject, members, config, options);
+                        (state.to_string(), actions)
+                    }
+                    None => {
+                        let state = reports
+                            .iter()
+                            .find(|report| &report.path == file_path)
+                            .map(|report| report.status.describe())
+                            .unwrap_or_default();
+                        (state, Vec::new())
+                    }
+                };
+
+            PlanRow {
+                repo,
+                package,
+                state,
+                actions: actions.into_iter().map(str::to_string).collect(),
+            }
+        })
+        .collect()
+}
+
+/// How long the tree must stay quiet before new files are processed, so
+/// that files still being written are complete
+const WATCH_SETTLE: Duration = Duration::from_secs(1);
+
+/// Whether `file`, which appeared below `root`, is one a walk from `root`
+/// would find; ignore files are not consulted
+fn is_watched(file: &Path, root: &Path, options: &WalkOptions) -> bool {
+    let Ok(relative) = file.strip_prefix(root) else {
+        return false;
+    };
+    let (Ok(skip_dirs), Ok(include), Ok(exclude)) = (
+        glob_matcher(&options.skip_dirs, "skip_dirs"),
+        glob_matcher(&options.include, "include"),
+        glob_matcher(&options.exclude, "exclude"),
+    ) else {
+        return false;
+    };
+    // A file directly in the root is one level down, as for the walker
+    if options
+        .max_depth
+        .is_some_and(|depth| relative.components().count() > depth + 1)
+    {
+        return false;
+    }
+    let mut dir = PathBuf::new();
+    for component in relative.parent().into_iter().flat_map(Path::components) {
+        dir.push(component);
+        if skip_dirs.is_match(component.as_os_str()) || exclude.is_match(&dir) {
+            return false;
+        }
+    }
+    !exclude.is_match(relative) && (include.is_empty() || include.is_match(relative))
+}
+
+/// Run uvinit over `targets`, then keep watching their directories and run
+/// it again, without confirmation, for every pyproject.toml that appears
+pub fn watch_uvinit(
+    targets: &[PathBuf],
+    mut options: UvinitOptions,
+    config_args: &ConfigArgs,
+) -> Result<ExitCode> {
+    let roots: Vec<PathBuf> = targets.iter().filter(|t| t.is_dir()).cloned().collect();
+    if roots.is_empty() {
+        anyhow::bail!("--watch needs a directory to watch");
+    }
+    // A failure in any run, the first or a later batch, fails the watch
+    let mut code = run_uvinit(targets, &options, config_args)?;
+    let config = load_uvinit_config(&options, config_args)?;
+    let walk_options = walk_options(&config.uvinit);
+    options.yes = true;
+
+    // Editors save by renaming a temp file over the original, and processing
+    // replaces files in place too, so both look like arrivals; only paths
+    // missing from the startup discovery count as new
+    let mut known: HashSet<PathBuf> = HashSet::new();
+    for root in &roots {
+        known.extend(discover_pyproject_files(root, &walk_options)?.files);
+    }
+    say!(
+        "\n👀 Watching {} for new pyproject.toml files (Ctrl-C to stop)...",
+        roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    // The watcher reports absolute paths; runs get them below the roots as given
+    let absolute_roots: Vec<PathBuf> = roots
+        .iter()
+        .map(|root| std::path::absolute(root).unwrap_or_else(|_| root.clone()))
+        .collect();
+    watch::watch(
+        &absolute_roots,
+        PYPROJECT_FILE_NAME,
+        WATCH_SETTLE,
+        |arrived| {
+            // A file deleted since is new again when it comes back
+            known.retain(|file| file.exists());
+            let files: Vec<PathBuf> = arrived
+                .into_iter()
+                .filter_map(|file| {
+                    roots
+                        .iter()
+                        .zip(&absolute_roots)
+                        .find_map(|(root, absolute)| {
+                            let relative = file.strip_prefix(absolute).ok()?;
+                            is_watched(&root.join(relative), root, &walk_options)
+                                .then(|| root.join(relative))
+                        })
+                })
+                .filter(|file| !known.contains(file))
+                .collect();
+            if files.is_empty() {
+                return;
+            }
+
+            log::info!("Watch picked up {} file(s)", files.len());
+            say!("\n✨ {} new pyproject.toml file(s)", files.len());
+            code = watch_status(code, run_uvinit(&files, &options, config_args));
+            known.extend(files);
+        },
+    )?;
+    Ok(code)
+}
+
+/// Rank of a run's exit status: failures outrank files that need changes,
+/// which outrank success
+fn status_rank(code: ExitCode) -> u8 {
+    if code == ExitCode::SUCCESS {
+        0
+    } else if code == ExitCode::from(CHECK_NEEDS_CHANGES) {
+        1
+    } else {
+        2
+    }
+}
+
+/// The watch's exit status after a batch: the worst of `code` and the batch's
+/// own, where a batch that errored counts as failed rather than as needing changes
+fn watch_status(code: ExitCode, batch: Result<ExitCode>) -> ExitCode {
+    let batch = batch.unwrap_or_else(|e| {
+        log::error!("{e:#}");
+        eprintln!("❌ {e:#}");
+        ExitCode::from(CHECK_FAILED)
+    });
+    if status_rank(batch) > status_rank(code) {
+        batch
+    } else {
+        code
+    }
+}
+
+/// Whether "already configured" depends on nothing but the file's own
+/// content and the settings in `scan_cache_settings`, so the scan cache can
+/// stand in for parsing it
+///
+/// Requirements files, workspace members and package directories all live
+/// outside pyproject.toml, so the checks reading them turn the cache off.
+fn scan_cache_applies(config: &UvinitConfig, options: &UvinitOptions) -> bool {
+    config.scan_cache
+        && !options.reprocess
+        && !options.fix_requires_only
+        && !config.import_requirements
+        && !config.migrate_src_layout
+        && !config.enable_workspace_sources
+        && !config.detect_namespace_packages
+}
+
+/// Fingerprint of the settings deciding whether a file is already
+/// configured; a cache written under others is dropped
+fn scan_cache_settings(config: &UvinitConfig) -> String {
+    serde_json::json!({
+        "enable_dynamic_version": config.enable_dynamic_version,
+        "version_backend": config.version_backend,
+        "detect_namespace_packages": config.detect_namespace_packages,
+    })
+    .to_string()
+}
+
+/// The scan cache of the repository holding `root`, when the run can use one
+fn open_scan_cache(
+    root: &Path,
+    config: &UvinitConfig,
+    options: &UvinitOptions,
+) -> Option<ScanCache> {
+    if !scan_cache_applies(config, options) {
+        return None;
+    }
+    let repo_root = git::toplevel(root).unwrap_or_else(|_| root.to_path_buf());
+    ScanCache::open(&repo_root, &scan_cache_settings(config))
+        .inspect_err(|e| log::warn!("Not using the scan cache: {e:#}"))
+        .ok()
+}
+
+/// Paths of the projects modified in place, relative to their repository
+/// root (the project directory outside git), grouped by that root
+fn projects_by_repository(reports: &[FileReport]) -> BTreeMap<PathBuf, Vec<String>> {
+    let mut repositories: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for report in reports.iter().filter(|r| r.status == FileStatus::Modified) {
+        let dir = report.path.parent().unwrap_or(Path::new("."));
+        let real_dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let root = git::toplevel(dir).unwrap_or_else(|_| real_dir.clone());
+        let relative = real_dir
+            .strip_prefix(&root)
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let projects = repositories.entry(root).or_default();
+        if !projects.contains(&relative) {
+            projects.push(relative);
+        }
+    }
+    for projects in repositories.values_mut() {
+        projects.sort();
+    }
+    repositories
+}
+
+/// Config for `bot` covering `projects`, as (path relative to the repository root, content)
+fn dependency_update_config(
+    bot: DependencyUpdates,
+    projects: &[String],
+) -> Option<(&'static str, String)> {
+    match bot {
+        DependencyUpdates::None => None,
+        DependencyUpdates::Dependabot => {
+            let directories: Vec<String> = projects
+                .iter()
+                .map(|project| format!("      - \"/{project}\""))
+                .collect();
+            Some((
+                ".github/dependabot.yml",
+                templates::render(
+                    templates::DEPENDABOT,
+                    &[("directories", &directories.join("\n"))],
+                ),
+            ))
+        }
+        DependencyUpdates::Renovate => {
+            let patterns: Vec<String> = projects
+                .iter()
+                .map(|project| match project.as_str() {
+                    "" => "\"/^pyproject\\\\.toml$/\"".to_string(),
+                    project => format!("\"/^{project}/pyproject\\\\.toml$/\""),
+                })
+                .collect();
+            Some((
+                "renovate.json",
+                templates::render(
+                    templates::RENOVATE,
+                    &[("file_patterns", &patterns.join(", "))],
+                ),
+            ))
+        }
+    }
+}
+
+/// Write the `bot` config at the root of each repository with a project
+/// modified in place, covering all of that repository's modified projects
+fn write_dependency_update_configs(
+    reports: &[FileReport],
+    bot: DependencyUpdates,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    if bot == DependencyUpdates::None {
+        return;
+    }
+    let repositories = projects_by_repository(reports);
+    if repositories.is_empty() {
+        return;
+    }
+
+    say!("\n🤖 Writing dependency update configs...");
+    for (root, projects) in repositories {
+        let Some((relative, content)) = dependency_update_config(bot, &projects) else {
+            continue;
+        };
+        let config_path = root.join(relative);
+        write_generated_file(&config_path, &content, policy, journal, errors, None);
+    }
+}
+
+/// Write a publish workflow from `template` for each project modified in place,
+/// into the `.github/workflows` of its repository (or of the project outside git)
+///
+/// The first project of a repository gets `publish.yml`, others `publish-<name>.yml`.
+fn write_publish_workflows(
+    reports: &[FileReport],
+    template: &str,
+    config: &UvinitConfig,
+    policy: OverwritePolicy,
+    journal: &mut Vec<journal::Entry>,
+    errors: &mut RunErrors,
+) {
+    let mut modified: Vec<&Path> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified)
+        .map(|r| r.path.as_path())
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+    modified.sort();
+
+    say!("\n🚀 Writing publish workflows...");
+    let tag_pattern = version_tag(&config.version_tag_format, "*");
+    let mut used_roots: HashSet<PathBuf> = HashSet::new();
+    for file_path in modified {
+        let dir = file_path.parent().unwrap_or(Path::new("."));
+        let real_dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let root = git::toplevel(dir).unwrap_or_else(|_| real_dir.clone());
+        let doc = match load_pyproject(file_path) {
+            Ok(pyproject) => pyproject.doc,
+            Err(e) => {
+                eprintln!("  ❌ {e:#}");
+                continue;
+            }
+        };
+        let project = doc.get("project");
+        let name = project
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("package");
+        let versions: Vec<String> = python_versions(
+            project
+                .and_then(|p| p.get("requires-python"))
+                .and_then(|r| r.as_str()),
+        )
+        .iter()
+        .map(|v| format!("\"{v}\""))
+        .collect();
+        let working_directory = real_dir
+            .strip_prefix(&root)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map_or(".".to_string(), |relative| relative.display().to_string());
+
+        let file_name = if used_roots.insert(root.clone()) {
+            "publish.yml".to_string()
+        } else {
+            format!("publish-{name}.yml")
+        };
+        let workflow_path = root.join(".github").join("workflows").join(file_name);
+        let workflow = templates::render(
+            template,
+            &[
+                ("name", name),
+                ("tag_pattern", &tag_pattern),
+                ("python_versions", &versions.join(", ")),
+                ("working_directory", &working_directory),
+            ],
+        );
+        write_generated_file(&workflow_path, &workflow, policy, journal, errors, None);
+    }
+}
+
+/// Run `pre-commit install` in `dir`, reporting rather than failing on errors
+fn install_precommit(dir: &Path) {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    if !git::is_inside_work_tree(dir) {
+        say!(
+            "  ⚠️  {} is not in a git repository - not installing hooks",
+            dir.display()
+        );
+        return;
+    }
+
+    let output = std::process::Command::new("pre-commit")
+        .arg("install")
+        .current_dir(dir)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            log::info!("pre-commit install succeeded in {}", dir.display());
+            say!("  🪝 Installed hooks in {}", dir.display());
+        }
+        Ok(output) => eprintln!(
+            "  ❌ pre-commit install failed in {}: {}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => eprintln!("  ❌ Failed to run pre-commit; is it installed? ({e})"),
+    }
+}
+
+/// Stage and commit the files written in place, one commit per repository
+///
+/// Nothing is committed when any file or companion write failed, so a commit
+/// never holds half a run.
+fn commit_written_files(
+    reports: &[FileReport],
+    journal: &[journal::Entry],
+    message: &str,
+    errors: &mut RunErrors,
+) {
+    let written = run_written_paths(reports, journal);
+    if written.is_empty() {
+        return;
+    }
+    if !errors.is_empty()
+        || reports
+            .iter()
+            .any(|r| matches!(r.status, FileStatus::Failed(_)))
+    {
+        say!("\n⚠️  Some files failed - not committing");
+        log::warn!("Skipping git commit because some files failed");
+        return;
+    }
+
+    say!("\n📌 Committing modified files...");
+
+    // git runs from each repository root, so relative paths would resolve wrongly
+    let mut by_repo: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    for file in written {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        // A package moved away has no path of its own left to resolve
+        let real_path = fs::canonicalize(file)
+            .or_else(|_| {
+                fs::canonicalize(dir).map(|dir| dir.join(file.file_name().unwrap_or_default()))
+            })
+            .unwrap_or_else(|_| file.to_path_buf());
+        match git::toplevel(dir) {
+            Ok(toplevel) => by_repo.entry(toplevel).or_default().push(real_path),
+            Err(_) => {
+                say!(
+                    "  ⚠️  {} is not in a git repository - skipping",
+                    file.display()
+                );
+                annotations::warning(Some(file), "Not in a git repository; not committed");
+            }
+        }
+    }
+
+    for (toplevel, files) in by_repo {
+        let result = files
+            .iter()
+            .try_for_each(|file| git::add(file))
+            .and_then(|_| {
+                let files: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+                git::commit(&toplevel, &files, message)
+            });
+        match result {
+            Ok(()) => {
+                log::info!(
+                    "Committed {} file(s) in {}",
+                    files.len(),
+                    toplevel.display()
+                );
+                say!("  ✅ {} ({} file(s))", toplevel.display(), files.len());
+            }
+            Err(e) => {
+                eprintln!("  ❌ {e}");
+                record_error(errors, None, &e.to_string());
+            }
+        }
+    }
+}
+
+/// The static version a file declared, from [project] or [tool.poetry]
+fn static_version(doc: &DocumentMut) -> Option<String> {
+    doc.get("project")
+        .and_then(|project| project.get("version"))
+        .or_else(|| {
+            doc.get("tool")
+                .and_then(|tool| tool.get("poetry"))
+                .and_then(|poetry| poetry.get("version"))
+        })
+        .and_then(|version| version.as_str())
+        .map(str::to_string)
+}
+
+/// `format` with `{version}` filled in
+fn version_tag(format: &str, version: &str) -> String {
+    format.replace("{version}", version)
+}
+
+/// Tag each repository that had a file switched to git-derived versions and no
+/// version tag yet, so the first build finds a version
+fn tag_initial_versions(
+    reports: &[FileReport],
+    static_versions: &HashMap<PathBuf, String>,
+    config: &UvinitConfig,
+    errors: &mut RunErrors,
+) {
+    let mut switched: Vec<&FileReport> = reports
+        .iter()
+        .filter(|r| r.status == FileStatus::Modified && r.enables_vcs_versioning())
+        .collect();
+    switched.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut by_repo: BTreeMap<PathBuf, String> = BTreeMap::new();
+    for report in switched {
+        let Ok(toplevel) = git::toplevel(report.path.parent().unwrap_or(Path::new("."))) else {
+            continue;
+        };
+        let version = static_versions
+            .get(&report.path)
+            .unwrap_or(&config.initial_version);
+        // The first path of a repository, usually its root project, names the tag
+        by_repo
+            .entry(toplevel)
+            .or_insert_with(|| version_tag(&config.version_tag_format, version));
+    }
+    if by_repo.is_empty() {
+        return;
+    }
+
+    say!("\n🏷️  Tagging initial versions...");
+    let pattern = version_tag(&config.version_tag_format, "*");
+    for (toplevel, name) in by_repo {
+        let result = git::tags(&toplevel, &pattern).and_then(|existing| {
+            if let Some(existing) = existing.first() {
+                log::info!("{} already has version tag {existing}", toplevel.display());
+                say!("  ⏭️  {} already has {existing}", toplevel.display());
+                return Ok(());
+            }
+            git::tag(&toplevel, &name)?;
+            log::info!("Tagged {} as {name}", toplevel.display());
+            say!("  ✅ {} -> {name}", toplevel.display());
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("  ❌ {e}");
+            record_error(errors, None, &e.to_string());
+        }
+    }
+}
+
+/// Exit status for a `--check` run: failures outrank files that need changes
+fn check_status(reports: &[FileReport]) -> ExitCode {
+    if reports
+        .iter()
+        .any(|r| matches!(r.status, FileStatus::Failed(_)))
+    {
+        ExitCode::from(CHECK_FAILED)
+    } else if reports.iter().any(|r| r.status == FileStatus::WouldChange) {
+        ExitCode::from(CHECK_NEEDS_CHANGES)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Print the summaries the options ask for; returns the process exit status
+fn finish_run(
+    path: &Path,
+    options: &UvinitOptions,
+    reports: &mut [FileReport],
+    errors: &RunErrors,
+) -> Result<ExitCode> {
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if options.tree {
+        say!();
+        say!("{}", render_tree(path, reports).trim_end());
+    }
+
+    if let Some(report_path) = &options.report_markdown {
+        fs::write(report_path, render_markdown(path, reports))
+            .with_context(|| format!("Failed to write report: {}", report_path.display()))?;
+        say!("📝 Wrote report to {}", report_path.display());
+    }
+
+    if let Some(report_path) = &options.report {
+        let document = render_error_report(path, reports, errors);
+        let json = serde_json::to_string_pretty(&document)
+            .with_context(|| "Failed to serialize error report")?;
+        fs::write(report_path, json + "\n")
+            .with_context(|| format!("Failed to write report: {}", report_path.display()))?;
+        say!("📝 Wrote error report to {}", report_path.display());
+    }
+
+    // The tree's first line already carries the totals, unless -q hid it
+    if !output::is_json() && (output::is_quiet() || !options.tree) {
+        say!();
+        println!("{}", render_summary(reports));
+    }
+
+    if output::is_json() {
+        let document = render_json(path, reports);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&document)
+                .with_context(|| "Failed to serialize report")?
+        );
+    }
+
+    if options.next_steps {
+        let steps = next_steps(reports);
+        if !steps.is_empty() {
+            say!("\n📌 Next steps:");
+            for step in steps {
+                say!("  [ ] {step}");
+            }
+        }
+    }
+
+    // Failures outside the pyproject.toml files, like a post-step write,
+    // fail the run as well
+    Ok(if options.check {
+        check_status(reports)
+    } else if !errors.is_empty()
+        || reports
+            .iter()
+            .any(|r| matches!(r.status, FileStatus::Failed(_)))
+    {
+        ExitCode::from(CHECK_FAILED)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_pyproject_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        // Create test directory structure
+        let sub_dir = root_path.join("subdir");
+        let skip_dir = root_path.join(".git");
+        let nested_dir = sub_dir.join("nested");
+        fs::create_dir_all(&nested_dir)?;
+        fs::create_dir_all(&skip_dir)?;
+
+        // Create pyproject.toml files
+        let mut file1 = fs::File::create(root_path.join("pyproject.toml"))?;
+        file1.write_all(b"[project]\nname = \"test1\"")?;
+
+        let mut file2 = fs::File::create(sub_dir.join("pyproject.toml"))?;
+        file2.write_all(b"[project]\nname = \"test2\"")?;
+
+        let mut file3 = fs::File::create(nested_dir.join("pyproject.toml"))?;
+        file3.write_all(b"[project]\nname = \"test3\"")?;
+
+        // Create file in skip directory (should be ignored)
+        let mut file4 = fs::File::create(skip_dir.join("pyproject.toml"))?;
+        file4.write_all(b"[project]\nname = \"skip\"")?;
+
+        let options = WalkOptions {
+            skip_dirs: vec![".git".to_string(), ".venv".to_string()],
+            ..Default::default()
+        };
+        let files = discover_pyproject_files(root_path, &options)?.files;
+
+        assert_eq!(files.len(), 3);
+        assert!(
+            files
+                .iter()
+                .any(|f| f.file_name() == Some("pyproject.toml".as_ref()))
+        );
+        assert!(!files.iter().any(|f| f.to_string_lossy().contains(".git")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pyproject_files_parallel_matches_sequential() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        for dir in ["a", "a/x", "b", "b/y/z", "c", "node_modules/pkg"] {
+            let dir = root_path.join(dir);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("pyproject.toml"), "[project]\nname = \"t\"")?;
+        }
+
+        let sequential = WalkOptions {
+            skip_dirs: vec!["node_modules".to_string()],
+            parallel: false,
+            respect_gitignore: false,
+            max_depth: None,
+            follow_symlinks: true,
+            include: vec![],
+            exclude: vec![],
+        };
+        let parallel = WalkOptions {
+            parallel: true,
+            ..sequential.clone()
+        };
+
+        let expected: Vec<PathBuf> = ["a", "a/x", "b", "b/y/z", "c"]
+            .iter()
+            .map(|dir| root_path.join(dir).join("pyproject.toml"))
+            .collect();
+        assert_eq!(
+            discover_pyproject_files(root_path, &sequential)?.files,
+            expected
+        );
+        assert_eq!(
+            discover_pyproject_files(root_path, &parallel)?.files,
+            expected
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_dir_matcher() -> Result<()> {
+        let matcher = glob_matcher(
+            &["build".to_string(), "*.egg-info".to_string()],
+            "skip_dirs",
+        )?;
+
+        assert!(matcher.is_match("build"));
+        assert!(matcher.is_match("my_pkg.egg-info"));
+        assert!(!matcher.is_match("build-tools"));
+        assert!(!matcher.is_match("src"));
+
+        assert!(glob_matcher(&["[".to_string()], "skip_dirs").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pyproject_files_skip_dir_globs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        for dir in ["pkg", "pkg.egg-info", "build-cache", "build-tools"] {
+            let dir = root_path.join(dir);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("pyproject.toml"), "[project]\nname = \"t\"")?;
+        }
+
+        let options = WalkOptions {
+            skip_dirs: vec!["*.egg-info".to_string(), "build-cache".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            discover_pyproject_files(root_path, &options)?.files,
+            vec![
+                root_path.join("build-tools/pyproject.toml"),
+                root_path.join("pkg/pyproject.toml"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pyproject_files_max_depth() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        for dir in ["", "a", "a/b", "a/b/c", "d"] {
+            let dir = root_path.join(dir);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("pyproject.toml"), "[project]\nname = \"t\"")?;
+        }
+
+        for parallel in [false, true] {
+            let options = WalkOptions {
+                max_depth: Some(1),
+                parallel,
+                ..Default::default()
+            };
+            assert_eq!(
+                discover_pyproject_files(root_path, &options)?.files,
+                vec![
+                    root_path.join("a/pyproject.toml"),
+                    root_path.join("d/pyproject.toml"),
+                    root_path.join("pyproject.toml"),
+                ]
+            );
+        }
+
+        let options = WalkOptions {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            discover_pyproject_files(root_path, &options)?.files,
+            vec![root_path.join("pyproject.toml")]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pyproject_files_respects_gitignore() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        for dir in ["app", "my-env/lib/pkg", "app/generated", "third_party/lib"] {
+            let dir = root_path.join(dir);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("pyproject.toml"), "[project]\nname = \"t\"")?;
+        }
+        fs::write(root_path.join(".gitignore"), "my-env/\n")?;
+        // Nested .gitignore files and .ignore files count too
+        fs::write(root_path.join("app/.gitignore"), "generated/\n")?;
+        fs::write(root_path.join(".ignore"), "third_party/\n")?;
+
+        let options = WalkOptions {
+            respect_gitignore: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            discover_pyproject_files(root_path, &options)?.files,
+            vec![root_path.join("app/pyproject.toml")]
+        );
+
+        let options = WalkOptions {
+            respect_gitignore: false,
+            ..options
+        };
+        assert_eq!(
+            discover_pyproject_files(root_path, &options)?.files.len(),
+            4
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pyproject_files_include_exclude() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        for dir in [
+            "",
+            "packages/a",
+            "packages/nested/b",
+            "packages/examples/c",
+            "examples/d",
+        ] {
+            let dir = root_path.join(dir);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("pyproject.toml"), "[project]\nname = \"t\"")?;
+        }
+
+        let options = WalkOptions {
+            include: vec!["packages/**/pyproject.toml".to_string()],
+            exclude: vec!["examples/**".to_string(), "**/examples".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            discover_pyproject_files(root_path, &options)?.files,
+            vec![
+                root_path.join("packages/a/pyproject.toml"),
+                root_path.join("packages/nested/b/pyproject.toml"),
+            ]
+        );
+
+        let options = WalkOptions {
+            include: vec![],
+            ..options
+        };
+        assert_eq!(
+            discover_pyproject_files(root_path, &options)?.files.len(),
+            3
+        );
+
+        let options = WalkOptions {
+            include: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(discover_pyproject_files(root_path, &options).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_pyproject_files_collisions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        fs::write(root_path.join("pyproject.toml"), "[project]\nname = \"a\"")?;
+        fs::write(root_path.join("PyProject.toml"), "[project]\nname = \"b\"")?;
+
+        let discovery = discover_pyproject_files(root_path, &WalkOptions::default())?;
+
+        // Case-sensitive filesystems see both names; only the lowercase one is kept
+        if fs::read_dir(root_path)?.count() == 2 {
+            assert_eq!(discovery.files, vec![root_path.join("pyproject.toml")]);
+            assert_eq!(discovery.collisions.len(), 1);
+            assert_eq!(
+                discovery.collisions[0].path,
+                root_path.join("PyProject.toml")
+            );
+            assert_eq!(
+                discovery.collisions[0].canonical,
+                Some(root_path.join("pyproject.toml"))
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_pyproject_files_symlink_duplicate() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        let sub_dir = root_path.join("pkg");
+        fs::create_dir_all(&sub_dir)?;
+        fs::write(sub_dir.join("pyproject.toml"), "[project]\nname = \"a\"")?;
+        std::os::unix::fs::symlink(&sub_dir, root_path.join("pkg-link"))?;
+
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let discovery = discover_pyproject_files(root_path, &options)?;
+
+        assert_eq!(discovery.files.len(), 1);
+        assert_eq!(discovery.collisions.len(), 1);
+
+        // Without following, the link is never entered
+        let discovery = discover_pyproject_files(root_path, &WalkOptions::default())?;
+        assert_eq!(discovery.files, vec![sub_dir.join("pyproject.toml")]);
+        assert!(discovery.collisions.is_empty());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_pyproject_files_symlink_loop_terminates() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_path = temp_dir.path();
+
+        let pkg = root_path.join("pkg");
+        fs::create_dir_all(pkg.join("node_modules"))?;
+        fs::write(pkg.join("pyproject.toml"), "[project]\nname = \"a\"")?;
+        // node_modules/repo points back at the root
+        std::os::unix::fs::symlink(root_path, pkg.join("node_modules").join("repo"))?;
+
+        for parallel in [false, true] {
+            let options = WalkOptions {
+                parallel,
+                follow_symlinks: true,
+                ..Default::default()
+            };
+            let discovery = discover_pyproject_files(root_path, &options)?;
+            assert_eq!(discovery.files, vec![pkg.join("pyproject.toml")]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_targets_single_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("pyproject.toml"), "[project]\n")?;
+        let nested = temp_dir.path().join("nested");
+        fs::create_dir(&nested)?;
+        fs::write(nested.join("pyproject.toml"), "[project]\n")?;
+        let custom = temp_dir.path().join("custom.toml");
+        fs::write(&custom, "[project]\n")?;
+
+        let discovery = discover_targets(&custom, &WalkOptions::default())?;
+        assert_eq!(discovery.files, vec![custom]);
+
+        let notes = temp_dir.path().join("notes.txt");
+        fs::write(&notes, "[project]\n")?;
+        let error = discover_targets(&notes, &WalkOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("is not a .toml file"));
+
+        let discovery = discover_targets(temp_dir.path(), &WalkOptions::default())?;
+        assert_eq!(discovery.files.len(), 2);
+
+        assert!(
+            discover_targets(&temp_dir.path().join("missing"), &WalkOptions::default()).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_all_and_common_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        for dir in ["a", "b/c"] {
+            fs::create_dir_all(root.join(dir))?;
+            fs::write(root.join(dir).join("pyproject.toml"), "[project]\n")?;
+        }
+
+        // Overlapping targets reach b/c twice
+        let targets = vec![
+            root.join("b"),
+            root.join("a/pyproject.toml"),
+            root.join("b/c"),
+        ];
+        let discovery = discover_all(&targets, &WalkOptions::default())?;
+        assert_eq!(
+            discovery.files,
+            vec![
+                root.join("b/c/pyproject.toml"),
+                root.join("a/pyproject.toml")
+            ]
+        );
+        assert_eq!(common_dir(&targets), root);
+        assert_eq!(
+            common_dir(&[root.join("b/c/pyproject.toml")]),
+            root.join("b/c")
+        );
+        assert_eq!(
+            common_dir(&[PathBuf::from("a"), PathBuf::from("b")]),
+            PathBuf::from(".")
+        );
+        assert_eq!(common_dir(&[]), PathBuf::from("."));
+
+        let list = root.join("changed.txt");
+        fs::write(
+            &list,
+            format!(
+                "{}\n{}\n\n{}\n",
+                root.join("a/pyproject.toml").display(),
+                root.join("a/module.py").display(),
+                root.join("gone/pyproject.toml").display()
+            ),
+        )?;
+        assert_eq!(read_file_list(&list)?, vec![root.join("a/pyproject.toml")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_document_has_dynamic() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        // Test file with dynamic field
+        let file_with_dynamic = temp_dir.path().join("with_dynamic.toml");
+        fs::write(
+            &file_with_dynamic,
+            r#"
+[project]
+name = "test"
+dynamic = ["version"]
+description = "Test project"
+"#,
+        )?;
+
+        // Test file without dynamic field
+        let file_without_dynamic = temp_dir.path().join("without_dynamic.toml");
+        fs::write(
+            &file_without_dynamic,
+            r#"
+[project]
+name = "test"
+version = "0.1.0"
+description = "Test project"
+"#,
+        )?;
+
+        // Test file with empty project section
+        let file_empty_project = temp_dir.path().join("empty_project.toml");
+        fs::write(
+            &file_empty_project,
+            r#"
+[project]
+name = "test"
+"#,
+        )?;
+
+        // Test file without project section
+        let file_no_project = temp_dir.path().join("no_project.toml");
+        fs::write(
+            &file_no_project,
+            r#"
+[build-system]
+requires = ["hatchling"]
+"#,
+        )?;
+
+        assert!(document_has_dynamic(
+            &load_pyproject(&file_with_dynamic)?.doc
+        ));
+        assert!(!document_has_dynamic(
+            &load_pyproject(&file_without_dynamic)?.doc
+        ));
+        assert!(!document_has_dynamic(
+            &load_pyproject(&file_empty_project)?.doc
+        ));
+        assert!(!document_has_dynamic(
+            &load_pyproject(&file_no_project)?.doc
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test_pyproject.toml");
+
+        // Create initial pyproject.toml
+        fs::write(
+            &test_file,
+            r#"
+[project]
+name = "test-project"
+version = "0.1.0"
+description = "A test project"
+dependencies = ["requests"]
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+"#,
+        )?;
+
+        let config = UvinitConfig {
+            additional_requires: vec!["setuptools-scm".to_string()],
+            ..Default::default()
+        };
+
+        // Modify the file
+        modify_pyproject_toml(&test_file, &config)?;
+
+        // Read and verify the modified content
+        let modified_content = fs::read_to_string(&test_file)?;
+        println!("Modified content:\n{}", modified_content);
+        let doc = modified_content.parse::<DocumentMut>()?;
+
+        // Check that version was removed and dynamic was added
+        let project = doc.get("project").unwrap().as_table().unwrap();
+        assert!(!project.contains_key("version"));
+        assert!(project.contains_key("dynamic"));
+
+        let dynamic = project.get("dynamic").unwrap().as_array().unwrap();
+        assert_eq!(dynamic.len(), 1);
+        assert_eq!(dynamic.get(0).unwrap().as_str().unwrap(), "version");
+
+        // Check build-system.requires
+        let build_system = doc.get("build-system").unwrap().as_table().unwrap();
+        let requires = build_system.get("requires").unwrap().as_array().unwrap();
+
+        let requires_vec: Vec<&str> = requires.iter().map(|v| v.as_str().unwrap()).collect();
+
+        assert!(requires_vec.contains(&"hatchling"));
+        assert!(requires_vec.contains(&"hatch-vcs"));
+        assert!(requires_vec.contains(&"setuptools-scm"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_requirement_name() {
+        assert_eq!(requirement_name("hatch-vcs"), "hatch-vcs");
+        assert_eq!(requirement_name("Hatch_VCS==0.3"), "hatch-vcs");
+        assert_eq!(
+            requirement_name("setuptools-scm[toml] >= 8"),
+            "setuptools-scm"
+        );
+        assert_eq!(
+            requirement_name("hatchling ; python_version > '3.8'"),
+            "hatchling"
+        );
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_pinned_require_supersedes_auto_added() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pinned.toml");
+
+        fs::write(
+            &test_file,
+            r#"
+[project]
+name = "test-project"
+version = "0.1.0"
+
+[build-system]
+requires = ["hatchling", "Setuptools_SCM>=8"]
+build-backend = "hatchling.build"
+"#,
+        )?;
+
+        let config = UvinitConfig {
+            additional_requires: vec!["hatch-vcs==0.3".to_string(), "setuptools-scm".to_string()],
+            ..Default::default()
+        };
+
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let requires_changes: Vec<&Change> = rendered
+            .changes
+            .iter()
+            .filter(|c| c.key() == "build-system.requires")
+            .collect();
+        assert_eq!(
+            requires_changes,
+            vec![
+                &Change::append("build-system.requires", "hatch-vcs"),
+                &Change::replace("build-system.requires", "hatch-vcs", "hatch-vcs==0.3"),
+            ]
+        );
+
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        let requires = doc["build-system"]["requires"].as_array().unwrap();
+        let requires_vec: Vec<&str> = requires.iter().map(|v| v.as_str().unwrap()).collect();
+
+        // The pinned hatch-vcs wins over the bare auto-added one, and the
+        // existing pinned setuptools-scm is not joined by a bare duplicate
+        assert_eq!(
+            requires_vec,
+            vec!["hatchling", "Setuptools_SCM>=8", "hatch-vcs==0.3"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_preserves_array_style() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config = UvinitConfig {
+            additional_requires: vec!["setuptools-scm".to_string()],
+            ..Default::default()
+        };
+
+        let single_line = temp_dir.path().join("single.toml");
+        fs::write(
+            &single_line,
+            "[project]\nname = \"x\"\nversion = \"0.1.0\"\n\n[build-system]\nrequires = [\"hatchling\"]\n",
+        )?;
+        modify_pyproject_toml(&single_line, &config)?;
+        assert!(
+            fs::read_to_string(&single_line)?
+                .contains("requires = [\"hatchling\", \"hatch-vcs\", \"setuptools-scm\"]\n")
+        );
+
+        let multiline = temp_dir.path().join("multiline.toml");
+        fs::write(
+            &multiline,
+            "[project]\nname = \"x\"\nversion = \"0.1.0\"\n\n[build-system]\nrequires = [\n    \"hatchling\",\n]\n",
+        )?;
+        modify_pyproject_toml(&multiline, &config)?;
+        assert!(fs::read_to_string(&multiline)?.contains(
+            "requires = [\n    \"hatchling\",\n    \"hatch-vcs\",\n    \"setuptools-scm\",\n]\n"
+        ));
+
+        let no_trailing_comma = temp_dir.path().join("no_trailing_comma.toml");
+        fs::write(
+            &no_trailing_comma,
+            "[project]\nname = \"x\"\nversion = \"0.1.0\"\n\n[build-system]\nrequires = [\n    \"hatchling\"\n]\n",
+        )?;
+        modify_pyproject_toml(&no_trailing_comma, &config)?;
+        assert!(fs::read_to_string(&no_trailing_comma)?.contains(
+            "requires = [\n    \"hatchling\",\n    \"hatch-vcs\",\n    \"setuptools-scm\"\n]\n"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repair_pyproject_toml() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("partial.toml");
+
+        fs::write(
+            &test_file,
+            r#"
+[project]
+name = "test-project"
+dynamic = ["version"]
+version = "stale"
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+"#,
+        )?;
+
+        let rendered = repair_document(load_pyproject(&test_file)?, &UvinitConfig::default());
+        assert_eq!(
+            rendered.changes,
+            vec![
+                Change::append("build-system.requires", "hatch-vcs"),
+                Change::set("tool.hatch.version.source", "\"vcs\""),
+            ]
+        );
+
+        let doc = rendered.content.parse::<DocumentMut>()?;
+        // [project] is left exactly as it was
+        assert_eq!(doc["project"]["version"].as_str(), Some("stale"));
+        assert!(doc.get("tool").unwrap().get("bandit").is_none());
+
+        // A custom version source is not a gap and is left alone
+        fs::write(
+            &test_file,
+            r#"
+[project]
+name = "test-project"
+dynamic = ["version"]
+
+[build-system]
+requires = ["hatchling", "hatch-vcs"]
+
+[tool.hatch.version]
+source = "code"
+"#,
+        )?;
+        let rendered = repair_document(load_pyproject(&test_file)?, &UvinitConfig::default());
+        assert!(rendered.changes.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_with_existing_dynamic() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("existing_dynamic.toml");
+
+        // Create pyproject.toml with existing dynamic field
+        fs::write(
+            &test_file,
+            r#"
+[project]
+name = "test-project"
+dynamic = ["version", "description"]
+dependencies = ["requests"]
+
+[build-system]
+requires = ["hatchling", "hatch-vcs"]
+build-backend = "hatchling.build"
+
+[tool.hatch.version]
+source = "vcs"
+"#,
+        )?;
+
+        let config = UvinitConfig {
+            additional_requires: vec!["setuptools-scm".to_string()],
+            ..Default::default()
+        };
+
+        // Should not modify file that already has dynamic
+        assert!(document_has_dynamic(&load_pyproject(&test_file)?.doc));
+
+        // But if we force modify it, it should handle gracefully
+        modify_pyproject_toml(&test_file, &config)?;
+
+        let modified_content = fs::read_to_string(&test_file)?;
+        let doc = modified_content.parse::<DocumentMut>()?;
+
+        // Check that dynamic field still exists
+        let project = doc.get("project").unwrap().as_table().unwrap();
+        assert!(project.contains_key("dynamic"));
+
+        // Check that hatch-vcs wasn't duplicated
+        let build_system = doc.get("build-system").unwrap().as_table().unwrap();
+        let requires = build_system.get("requires").unwrap().as_array().unwrap();
+
+        let hatch_vcs_count = requires
+            .iter()
+            .filter(|v| v.as_str() == Some("hatch-vcs"))
+            .count();
+
+        assert_eq!(hatch_vcs_count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_keeps_custom_version_source() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("attr_source.toml");
+        let original = r#"
+[project]
+name = "test-project"
+dynamic = ["version"]
+
+[build-system]
+requires = ["hatchling"]
+
+[tool.hatch.version]
+source = "code"
+path = "src/pkg/__about__.py"
+"#;
+        fs::write(&test_file, original)?;
+
+        let rendered = modify_pyproject_toml(&test_file, &UvinitConfig::default())?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        assert_eq!(
+            doc["tool"]["hatch"]["version"]["source"].as_str(),
+            Some("code")
+        );
+        assert_eq!(rendered.warnings.len(), 1);
+        assert!(rendered.warnings[0].contains("--override-version-source"));
+
+        fs::write(&test_file, original)?;
+        let config = UvinitConfig {
+            override_version_source: true,
+            ..Default::default()
+        };
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        assert_eq!(
+            doc["tool"]["hatch"]["version"]["source"].as_str(),
+            Some("vcs")
+        );
+        assert!(rendered.warnings.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dynamic_takes_version_slot() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\n# bumped by release script\nversion = \"0.1.0\"\ndescription = \"d\"\nreadme = \"README.md\"\n",
+        )?;
+
+        modify_pyproject_toml(&test_file, &UvinitConfig::default())?;
+
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.starts_with(
+            "[project]\nname = \"x\"\n# bumped by release script\ndynamic = [\"version\"]\ndescription = \"d\"\nreadme = \"README.md\"\n"
+        ));
+
+        let mut doc = "[project]\nname = \"x\"\nversion = \"0.1.0\"\nreadme = \"r\"\n"
+            .parse::<DocumentMut>()?;
+        let project = doc["project"].as_table_mut().unwrap();
+        insert_at_key(
+            project,
+            "version",
+            "dynamic",
+            toml_edit::value(Array::new()),
+            true,
+        );
+        let keys: Vec<&str> = project.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["name", "dynamic", "version", "readme"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_version_key_action() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("version_key.toml");
+        let original = "[project]\nname = \"x\"\nversion = \"1.2.3\"\n";
+
+        fs::write(&test_file, original)?;
+        let config = UvinitConfig {
+            version_key_action: VersionKeyAction::Keep,
+            ..Default::default()
+        };
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        assert_eq!(doc["project"]["version"].as_str(), Some("1.2.3"));
+        assert!(doc["project"]["dynamic"].is_array());
+        assert!(
+            !rendered
+                .changes
+                .contains(&Change::remove("project.version"))
+        );
+        assert_eq!(rendered.warnings.len(), 1);
+
+        fs::write(&test_file, original)?;
+        let config = UvinitConfig {
+            version_key_action: VersionKeyAction::MoveToScm,
+            ..Default::default()
+        };
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        assert!(!doc["project"].as_table().unwrap().contains_key("version"));
+        assert_eq!(
+            doc["tool"]["setuptools_scm"]["fallback_version"].as_str(),
+            Some("1.2.3")
+        );
+        assert!(rendered.changes.contains(&Change::set(
+            "tool.setuptools_scm.fallback_version",
+            "\"1.2.3\""
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_ruff() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let config = UvinitConfig {
+            enable_ruff: true,
+            ..Default::default()
+        };
+
+        fs::write(&test_file, "[project]\nname = \"x\"\nversion = \"0.1.0\"\n")?;
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains("[tool.ruff]\nline-length = 88\n"));
+        assert!(content.contains("[tool.ruff.lint]\nselect = [\"E\", \"F\", \"I\"]\n"));
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::set("tool.ruff.line-length", "88"))
+        );
+
+        // A second run finds nothing left to do
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+        assert_eq!(rerun.content, content);
+
+        // User-authored ruff settings are left alone
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\ndynamic = [\"version\"]\n\n[tool.ruff]\nline-length = 120\nselect = [\"ALL\"]\n",
+        )?;
+        let rendered = render_pyproject_toml(&test_file, &config)?;
+        assert!(
+            !rendered
+                .changes
+                .iter()
+                .any(|c| c.key().starts_with("tool.ruff"))
+        );
+        assert!(rendered.content.contains("line-length = 120"));
+        assert!(!rendered.content.contains("[tool.ruff.lint]"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_version_backends() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let original =
+            "[project]\nname = \"x\"\nversion = \"0.1.0\"\n\n[build-system]\nrequires = []\n";
+        let backend_config = |version_backend| UvinitConfig {
+            version_backend,
+            enable_pytest_asyncio: false,
+            enable_bandit: false,
+            ..Default::default()
+        };
+
+        fs::write(&test_file, original)?;
+        let config = backend_config(VersionBackend::SetuptoolsScm);
+        modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        assert_eq!(doc["build-system"]["requires"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            doc["build-system"]["build-backend"].as_str(),
+            Some("setuptools.build_meta")
+        );
+        assert!(doc["tool"]["setuptools_scm"].is_table());
+        assert!(doc["tool"].get("hatch").is_none());
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
+
+        fs::write(&test_file, original)?;
+        let config = backend_config(VersionBackend::Pdm);
+        modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        assert_eq!(
+            doc["build-system"]["build-backend"].as_str(),
+            Some("pdm.backend")
+        );
+        assert_eq!(
+            doc["tool"]["pdm"]["version"]["source"].as_str(),
+            Some("scm")
+        );
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
+
+        // A different backend already in place is kept and reported
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\n\n[build-system]\nrequires = []\nbuild-backend = \"hatchling.build\"\n",
+        )?;
+        let rendered = render_pyproject_toml(&test_file, &config)?;
+        assert!(
+            rendered
+                .content
+                .contains("build-backend = \"hatchling.build\"")
+        );
+        assert_eq!(rendered.warnings.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_poetry() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            r#"[tool.poetry]
+name = "legacy"
+version = "0.0.0"
+
+[build-system]
+requires = ["poetry-core>=1.0.0"]
+build-backend = "poetry.core.masonry.api"
+"#,
+        )?;
+        let config = UvinitConfig {
+            enable_pytest_asyncio: false,
+            enable_bandit: false,
+            ..Default::default()
+        };
+        assert!(!has_dynamic_version(&load_pyproject(&test_file)?.doc));
+
+        modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        let doc = content.parse::<DocumentMut>()?;
+        assert_eq!(
+            doc["build-system"]["build-backend"].as_str(),
+            Some("poetry_dynamic_versioning.backend")
+        );
+        assert!(content.contains(POETRY_VERSIONING_REQUIRE));
+        assert!(!content.contains("hatch"));
+        assert!(has_dynamic_version(&doc));
+
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_mypy_and_coverage() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let config = UvinitConfig {
+            enable_mypy: true,
+            enable_coverage: true,
+            enable_pytest_asyncio: false,
+            enable_bandit: false,
+            ..Default::default()
+        };
+
+        fs::write(&test_file, "[project]\nname = \"x\"\nversion = \"0.1.0\"\n")?;
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains("[tool.mypy]\nstrict = true\npython_version = \"3.12\"\n"));
+        // The source package is derived from project.name
+        assert!(content.contains("[tool.coverage.run]\nsource = [\"x\"]\nomit = [\"tests/*\"]\n"));
+        assert!(content.contains("[tool.coverage.report]\nfail_under = 80\n"));
+        // Only the leaf tables get headers
+        assert!(!content.contains("[tool]\n"));
+        assert!(!content.contains("[tool.coverage]\n"));
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::set("tool.mypy.strict", "true"))
+        );
+
+        // A second run finds nothing left to do
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+        assert_eq!(rerun.content, content);
+
+        // User-authored values are left alone; only missing keys are filled in
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\ndynamic = [\"version\"]\n\n[tool.mypy]\nstrict = false\n\n[tool.coverage.run]\nsource = [\"pkg\"]\n",
+        )?;
+        let rendered = render_pyproject_toml(&test_file, &config)?;
+        let tool_changes: Vec<&Change> = rendered
+            .changes
+            .iter()
+            .filter(|c| c.key().starts_with("tool.mypy") || c.key().starts_with("tool.coverage"))
+            .collect();
+        assert_eq!(
+            tool_changes,
+            vec![
+                &Change::set("tool.mypy.python_version", "\"3.12\""),
+                &Change::set("tool.mypy.ignore_missing_imports", "false"),
+                &Change::set("tool.coverage.run.omit", "[\"tests/*\"]"),
+                &Change::set("tool.coverage.report.fail_under", "80"),
+            ]
+        );
+        assert!(rendered.content.contains("strict = false"));
+        assert!(rendered.content.contains("source = [\"pkg\"]"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_mypy_overrides() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let mut settings = toml::Table::new();
+        settings.insert(
+            "ignore_missing_imports".to_string(),
+            toml::Value::Boolean(true),
+        );
+        let config = UvinitConfig {
+            enable_mypy: true,
+            mypy_overrides: BTreeMap::from([
+                ("requests.*".to_string(), settings.clone()),
+                ("yaml".to_string(), settings),
+            ]),
+            ..Default::default()
+        };
+
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\ndynamic = [\"version\"]\n\n[[tool.mypy.overrides]]\nmodule = \"yaml\"\ndisallow_untyped_defs = false\n",
+        )?;
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        let overrides = doc["tool"]["mypy"]["overrides"]
+            .as_array_of_tables()
+            .unwrap();
+        assert_eq!(overrides.len(), 2);
+        // The user's entry for `yaml` is kept as written
+        assert!(
+            overrides
+                .get(0)
+                .unwrap()
+                .get("ignore_missing_imports")
+                .is_none()
+        );
+        assert_eq!(
+            overrides.get(1).unwrap()["ignore_missing_imports"].as_bool(),
+            Some(true)
+        );
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::append("tool.mypy.overrides", "requests.*"))
+        );
+
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_keeps_crlf_line_endings() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\r\nname = \"x\"\r\nversion = \"0.1.0\"\r\n",
+        )?;
+
+        let rendered = render_pyproject_toml(&test_file, &UvinitConfig::default())?;
+        assert!(!rendered.changes.is_empty());
+        assert!(!rendered.content.replace("\r\n", "").contains('\n'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_requires_python() -> Result<()> {
+        let render = |content: &str, tighten: bool| -> Result<Rendered> {
+            let config = UvinitConfig {
+                requires_python: Some(">=3.11".to_string()),
+                tighten_requires_python: tighten,
+                ..Default::default()
+            };
+            let doc = content.parse::<DocumentMut>()?;
+            Ok(render_document(
+                Pyproject {
+                    path: PathBuf::from("pyproject.toml"),
+                    content: content.to_string(),
+                    doc,
+                },
+                &config,
+                &WorkspaceMembers::new(),
+                &RepoFacts::default(),
+            ))
+        };
+        let requires_python = |rendered: &Rendered| -> Result<Option<String>> {
+            let doc = rendered.content.parse::<DocumentMut>()?;
+            Ok(doc["project"]
+                .get("requires-python")
+                .and_then(|v| v.as_str())
+                .map(str::to_string))
+        };
+
+        let missing = render("[project]\nname = \"x\"\n", false)?;
+        assert_eq!(requires_python(&missing)?.as_deref(), Some(">=3.11"));
+
+        let loose = "[project]\nname = \"x\"\nrequires-python = \">=3.8, <4\"  # floor\n";
+        assert_eq!(
+            requires_python(&render(loose, false)?)?.as_deref(),
+            Some(">=3.8, <4")
+        );
+        let tightened = render(loose, true)?;
+        assert!(
+            tightened
+                .content
+                .contains("requires-python = \">=3.11,<4\"  # floor\n")
+        );
+
+        let stricter = "[project]\nname = \"x\"\nrequires-python = \">=3.12\"\n";
+        assert_eq!(
+            requires_python(&render(stricter, true)?)?.as_deref(),
+            Some(">=3.12")
+        );
+
+        let compatible = render(
+            "[project]\nname = \"x\"\nrequires-python = \"~=3.9\"\n",
+            true,
+        )?;
+        assert_eq!(compatible.warnings.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fill_metadata() -> Result<()> {
+        let config = UvinitConfig {
+            enable_metadata: true,
+            license: Some("MIT".to_string()),
+            classifiers: vec![
+                "Programming Language :: Python :: 3".to_string(),
+                "Typing :: Typed".to_string(),
+            ],
+            ..Default::default()
+        };
+        let mut doc =
+            "[project]\nname = \"x\"\nlicense = \"Apache-2.0\"\n".parse::<DocumentMut>()?;
+        let mut changes = Vec::new();
+
+        fill_metadata(
+            doc["project"].as_table_mut().unwrap(),
+            &config,
+            Some("https://github.com/owner/x"),
+            &mut changes,
+        );
+        assert_eq!(
+            doc.to_string(),
+            r#"[project]
+name = "x"
+license = "Apache-2.0"
+classifiers = [
+    "Programming Language :: Python :: 3",
+    "Typing :: Typed",
+]
+
+[project.urls]
+Homepage = "https://github.com/owner/x"
+Repository = "https://github.com/owner/x"
+Issues = "https://github.com/owner/x/issues"
+"#
+        );
+        assert!(!changes.iter().any(|c| c.key() == "project.license"));
+
+        // Nothing is filled in twice
+        let mut again = Vec::new();
+        fill_metadata(
+            doc["project"].as_table_mut().unwrap(),
+            &config,
+            Some("https://github.com/owner/x"),
+            &mut again,
+        );
+        assert!(again.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_python_versions() {
+        assert_eq!(
+            python_versions(Some(">=3.12")),
+            vec!["3.12", "3.13", "3.14"]
+        );
+        assert_eq!(
+            python_versions(Some(">=3.9, <3.12")),
+            vec!["3.9", "3.10", "3.11"]
+        );
+        assert_eq!(python_versions(Some(">=3.11,<=3.12")), vec!["3.11", "3.12"]);
+        assert_eq!(
+            python_versions(None).first().map(String::as_str),
+            Some("3.10")
+        );
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_hatch_envs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let envs: BTreeMap<String, toml::Table> = toml::from_str(
+            r#"
+[default]
+dependencies = ["pytest", "pytest-cov"]
+
+[default.scripts]
+test = "pytest {args}"
+cov = "pytest --cov {args}"
+
+[lint]
+detached = true
+"#,
+        )?;
+        let config = UvinitConfig {
+            hatch_envs: envs,
+            ..Default::default()
+        };
+        fs::write(
+            &test_file,
+            r#"[project]
+name = "x"
+version = "0.1.0"
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+
+[tool.hatch.envs.default.scripts]
+test = "pytest -x {args}"
+"#,
+        )?;
+
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        let default = &doc["tool"]["hatch"]["envs"]["default"];
+        // The project's own script wins
+        assert_eq!(
+            default["scripts"]["test"].as_str(),
+            Some("pytest -x {args}")
+        );
+        assert_eq!(
+            default["scripts"]["cov"].as_str(),
+            Some("pytest --cov {args}")
+        );
+        assert_eq!(default["dependencies"].as_array().map(Array::len), Some(2));
+        assert_eq!(
+            doc["tool"]["hatch"]["envs"]["lint"]["detached"].as_bool(),
+            Some(true)
+        );
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::set("tool.hatch.envs.lint.detached", "true"))
+        );
+
+        // Other backends are left alone
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\n\n[build-system]\nrequires = [\"setuptools\"]\nbuild-backend = \"setuptools.build_meta\"\n",
+        )?;
+        let rendered = render_pyproject_toml(&test_file, &config)?;
+        assert!(!rendered.content.contains("tool.hatch.envs"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_uv_settings() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let config = UvinitConfig {
+            uv_settings: toml::from_str(
+                r#"
+package = true
+default-groups = ["dev", "lint"]
+
+[sources]
+shared = { workspace = true }
+tooling = { git = "https://github.com/owner/tooling" }
+"#,
+            )?,
+            ..Default::default()
+        };
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\ndynamic = [\"version\"]\n\n[tool.uv]\npackage = false\n\n[tool.uv.sources]\ntooling = { path = \"../tooling\" }\n",
+        )?;
+
+        modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(
+            content.contains("[tool.uv]\npackage = false\ndefault-groups = [\"dev\", \"lint\"]\n")
+        );
+        assert!(content.contains(
+            "[tool.uv.sources]\ntooling = { path = \"../tooling\" }\nshared = { workspace = true }\n"
+        ));
+
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_workspace_root() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = fs::canonicalize(temp_dir.path())?;
+        fs::write(
+            root.join("pyproject.toml"),
+            "[tool.uv.workspace]\nmembers = [\"packages/*\"]\nexclude = [\"packages/legacy\"]\n",
+        )?;
+        for member in [
+            "packages/a",
+            "packages/legacy",
+            "packages/a/nested",
+            "tools",
+        ] {
+            fs::create_dir_all(root.join(member))?;
+        }
+
+        assert_eq!(
+            workspace_root(&root.join("packages/a/pyproject.toml")),
+            Some(root.clone())
+        );
+        assert_eq!(
+            workspace_root(&root.join("packages/legacy/pyproject.toml")),
+            None
+        );
+        assert_eq!(workspace_root(&root.join("tools/pyproject.toml")), None);
+        assert_eq!(workspace_root(&root.join("pyproject.toml")), None);
+
+        // The nearest workspace decides, even when it does not list the project
+        fs::write(
+            root.join("packages/a/pyproject.toml"),
+            "[tool.uv.workspace]\nmembers = []\n",
+        )?;
+        assert_eq!(
+            workspace_root(&root.join("packages/a/nested/pyproject.toml")),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_workspace_member() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root_file = temp_dir.path().join("pyproject.toml");
+        let member_dir = temp_dir.path().join("packages").join("a");
+        fs::create_dir_all(&member_dir)?;
+        let member_file = member_dir.join("pyproject.toml");
+        fs::write(
+            &root_file,
+            "[project]\nname = \"root\"\nversion = \"0.1.0\"\n\n[tool.uv.workspace]\nmembers = [\"packages/*\"]\n",
+        )?;
+        let member = "[project]\nname = \"a\"\nversion = \"0.1.0\"\n\n[project.optional-dependencies]\ndev = [\"pytest\"]\n\n[build-system]\nrequires = [\"hatchling\"]\nbuild-backend = \"hatchling.build\"\n";
+        fs::write(&member_file, member)?;
+        let config = UvinitConfig {
+            enable_dependency_groups: true,
+            uv_settings: toml::from_str("package = true\n\n[sources]\na = { workspace = true }\n")?,
+            ..Default::default()
+        };
+
+        modify_pyproject_toml(&member_file, &config)?;
+        let content = fs::read_to_string(&member_file)?;
+        assert!(content.contains("dynamic = [\"version\"]"));
+        assert!(content.contains("hatch-vcs"));
+        assert!(content.contains("[project.optional-dependencies]\ndev = [\"pytest\"]\n"));
+        assert!(!content.contains("[dependency-groups]"));
+        assert!(content.contains("[tool.uv]\npackage = true\n"));
+        assert!(!content.contains("sources"));
+
+        modify_pyproject_toml(&root_file, &config)?;
+        let content = fs::read_to_string(&root_file)?;
+        assert!(content.contains("[tool.uv.sources]\na = { workspace = true }\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_type_checker() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::create_dir_all(temp_dir.path().join("src"))?;
+        let original =
+            "[project]\nname = \"x\"\ndynamic = [\"version\"]\nrequires-python = \">=3.10.2,<4\"\n";
+        fs::write(&test_file, original)?;
+        let config = UvinitConfig {
+            type_checker: TypeChecker::Pyright,
+            type_checker_exclude: vec!["build".to_string()],
+            ..Default::default()
+        };
+
+        let rendered = render_pyproject_toml(&test_file, &config)?;
+        assert!(rendered.content.contains(
+            "[tool.pyright]\ntypeCheckingMode = \"standard\"\ninclude = [\"src\"]\nexclude = [\"build\"]\npythonVersion = \"3.10\"\n"
+        ));
+
+        fs::write(
+            &test_file,
+            format!("{original}\n[tool.ty.environment]\npython-version = \"3.12\"\n"),
+        )?;
+        let config = UvinitConfig {
+            type_checker: TypeChecker::Ty,
+            type_checking_mode: TypeCheckingMode::Strict,
+            ..Default::default()
+        };
+        modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains("[tool.ty.environment]\npython-version = \"3.12\"\n"));
+        assert!(content.contains("[tool.ty.src]\ninclude = [\"src\"]\n"));
+        assert!(content.contains("[tool.ty.terminal]\nerror-on-warning = true\n"));
+        assert!(!content.contains("[tool.ty]\n"));
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_workspace_sources() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"root\"\ndynamic = [\"version\"]\ndependencies = [\"api\"]\n\n[tool.uv.workspace]\nmembers = [\"packages/*\"]\n\n[tool.uv.sources]\nshared = { workspace = true }\n",
+        )?;
+        for (member, content) in [
+            (
+                "api",
+                "[project]\nname = \"api\"\ndynamic = [\"version\"]\ndependencies = [\"Core_Lib>=1\", \"shared\", \"httpx\"]\n",
+            ),
+            (
+                "core",
+                "[project]\nname = \"core-lib\"\ndynamic = [\"version\"]\n",
+            ),
+            (
+                "shared",
+                "[project]\nname = \"shared\"\ndynamic = [\"version\"]\n",
+            ),
+            // Inside skip_dirs, so not a member the search would find
+            (
+                "build",
+                "[project]\nname = \"httpx\"\ndynamic = [\"version\"]\n",
+            ),
+        ] {
+            fs::create_dir_all(root.join("packages").join(member))?;
+            fs::write(
+                root.join("packages").join(member).join("pyproject.toml"),
+                content,
+            )?;
+        }
+        let api_file = root.join("packages/api/pyproject.toml");
+
+        let config = UvinitConfig {
+            enable_workspace_sources: true,
+            ..Default::default()
+        };
+        let api = load_pyproject(&api_file)?;
+        let members = workspace_members([(api_file.as_path(), &api.doc)], &walk_options(&config));
+        assert_eq!(members.len(), 1);
+        // shared already has a source at the workspace root
+        assert_eq!(
+            missing_workspace_sources(&api_file, &api.doc, &members),
+            vec!["core-lib"]
+        );
+
+        modify_pyproject_toml(&api_file, &config)?;
+        let content = fs::read_to_string(&api_file)?;
+        assert!(content.contains("[tool.uv.sources]\ncore-lib = { workspace = true }\n"));
+        assert!(
+            render_pyproject_toml(&api_file, &config)?
+                .changes
+                .is_empty()
+        );
+
+        // The workspace root counts as a member of its own workspace
+        modify_pyproject_toml(root.join("pyproject.toml"), &config)?;
+        let content = fs::read_to_string(root.join("pyproject.toml"))?;
+        assert!(content.contains("shared = { workspace = true }\napi = { workspace = true }\n"));
+
+        // Outside a workspace nothing is wired
+        let standalone = TempDir::new()?;
+        let standalone_file = standalone.path().join("pyproject.toml");
+        fs::write(
+            &standalone_file,
+            "[project]\nname = \"x\"\ndependencies = [\"api\"]\n",
+        )?;
+        let standalone = load_pyproject(&standalone_file)?;
+        assert!(missing_workspace_sources(&standalone_file, &standalone.doc, &members).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_src_layout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let test_file = project.join("pyproject.toml");
+        fs::write(
+            &test_file,
+            r#"[project]
+name = "my-pkg"
+dynamic = ["version"]
+
+[build-system]
+requires = ["hatchling", "hatch-vcs"]
+build-backend = "hatchling.build"
+
+[tool.hatch.build.targets.wheel]
+packages = ["my_pkg"]
+
+[tool.pytest.ini_options]
+pythonpath = ["."]
+"#,
+        )?;
+        fs::create_dir_all(project.join("my_pkg"))?;
+        fs::write(project.join("my_pkg").join("__init__.py"), "")?;
+        fs::create_dir_all(project.join("tests"))?;
+        fs::write(
+            project.join("tests").join("test_data.py"),
+            "DATA = Path(\"my_pkg/data.json\")\n",
+        )?;
+        let config = UvinitConfig {
+            migrate_src_layout: true,
+            backup_before_write: false,
+            ..Default::default()
+        };
+
+        let report = process_file(
+            &test_file,
+            load_pyproject(&test_file)?,
+            &WorkspaceMembers::new(),
+            &RepoFactsByRoot::new(),
+            &config,
+            &UvinitOptions::default(),
+        );
+        assert_eq!(report.status, FileStatus::Modified);
+        assert!(project.join("src/my_pkg/__init__.py").is_file());
+        assert!(!project.join("my_pkg").exists());
+        let moved = report.moved.as_ref().unwrap();
+        assert_eq!(moved.to, project.join("src").join("my_pkg"));
+        assert_eq!(
+            moved.rewritten,
+            vec![project.join("tests").join("test_data.py")]
+        );
+        assert_eq!(
+            report.rewritten[0].before.as_deref(),
+            Some("DATA = Path(\"my_pkg/data.json\")\n")
+        );
+        assert_eq!(
+            fs::read_to_string(project.join("tests").join("test_data.py"))?,
+            "DATA = Path(\"src/my_pkg/data.json\")\n"
+        );
+        let content = fs::read_to_string(&test_file)?;
+        assert!(
+            content.contains("[tool.hatch.build.targets.wheel]\npackages = [\"src/my_pkg\"]\n")
+        );
+        assert!(content.contains("pythonpath = [\".\", \"src\"]"));
+
+        // Nothing is left to move on a second run
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.src_layout_package.is_none());
+        assert!(rerun.changes.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_migrates_legacy_setup() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        fs::write(
+            project.join("setup.cfg"),
+            "[metadata]\nname = legacy-app\nversion = 1.2.0\n\n[options]\ninstall_requires =\n    requests>=2\n    click\n\n[options.entry_points]\nconsole_scripts =\n    legacy = legacy_app.cli:main\n",
+        )?;
+        let config = UvinitConfig {
+            migrate_setup: true,
+            ..Default::default()
+        };
+        let walk_options = WalkOptions::default();
+        let planned = find_legacy_projects(&[project.to_path_buf()], &walk_options)?;
+        assert_eq!(planned, vec![project.join("pyproject.toml")]);
+
+        let rendered = render_document(
+            Pyproject {
+                path: planned[0].clone(),
+                content: String::new(),
+                doc: DocumentMut::new(),
+            },
+            &config,
+            &WorkspaceMembers::new(),
+            &RepoFacts::default(),
+        );
+        assert!(rendered.content.starts_with("[project]\n"));
+        let doc = rendered.content.parse::<DocumentMut>()?;
+        assert_eq!(doc["project"]["name"].as_str(), Some("legacy-app"));
+        assert!(doc["project"].get("version").is_none());
+        assert!(
+            rendered
+                .content
+                .contains("dependencies = [\n    \"requests>=2\",\n    \"click\",\n]\n")
+        );
+        assert_eq!(
+            doc["project"]["scripts"]["legacy"].as_str(),
+            Some("legacy_app.cli:main")
+        );
+        assert_eq!(
+            doc["build-system"]["build-backend"].as_str(),
+            Some("hatchling.build")
+        );
+        assert!(
+            rendered
+                .content
+                .contains(r#"requires = ["hatchling", "hatch-vcs"]"#)
+        );
+        assert_eq!(
+            doc["tool"]["hatch"]["version"]["source"].as_str(),
+            Some("vcs")
+        );
+
+        // An existing pyproject.toml is augmented, and no longer planned
+        fs::write(
+            project.join("pyproject.toml"),
+            "[project]\nname = \"legacy-app\"\ndependencies = [\"click>=8\"]\n",
+        )?;
+        assert!(find_legacy_projects(&[project.to_path_buf()], &walk_options)?.is_empty());
+        let rendered = render_pyproject_toml(&project.join("pyproject.toml"), &config)?;
+        assert!(
+            rendered
+                .content
+                .contains(r#"dependencies = ["click>=8", "requests>=2"]"#)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_rows() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("app"))?;
+        fs::create_dir_all(root.join("lib"))?;
+        let app = root.join("app").join("pyproject.toml");
+        let lib = root.join("lib").join("pyproject.toml");
+        fs::write(&app, "[project]\nname = \"app\"\n")?;
+        fs::write(&lib, "[project]\nname = \"lib\"\ndynamic = [\"version\"]\n")?;
+
+        let pyproject_files = vec![app.clone(), lib.clone()];
+        let files_to_process = vec![(&pyproject_files[0], load_pyproject(&app)?)];
+        let reports = vec![FileReport::new(&lib, FileStatus::AlreadyConfigured)];
+        let names = HashMap::from([(app.as_path(), "app".to_string())]);
+        let config = UvinitConfig {
+            enable_ruff: true,
+            enable_bandit: false,
+            enable_pytest_asyncio: false,
+            ..Default::default()
+        };
+        let options = UvinitOptions::default();
+
+        let rows = plan_rows(
+            &pyproject_files,
+            &reports,
+            &files_to_process,
+            &names,
+            &WorkspaceMembers::new(),
+            &config,
+            &options,
+        );
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].package.starts_with("app ("));
+        assert_eq!(rows[0].state, "no project.dynamic");
+        assert_eq!(rows[0].actions, vec!["dynamic version", "ruff"]);
+        assert_eq!(rows[1].state, "already configured");
+        assert!(rows[1].actions.is_empty());
+        // Both files share a repository, or neither is in one
+        assert_eq!(rows[0].repo, rows[1].repo);
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_status() {
+        let failed = ExitCode::from(CHECK_FAILED);
+        let needs_changes = ExitCode::from(CHECK_NEEDS_CHANGES);
+
+        assert_eq!(
+            watch_status(ExitCode::SUCCESS, Ok(ExitCode::SUCCESS)),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(
+            watch_status(ExitCode::SUCCESS, Ok(needs_changes)),
+            needs_changes
+        );
+        // An errored batch fails the watch, also under --check
+        assert_eq!(
+            watch_status(ExitCode::SUCCESS, Err(anyhow::anyhow!("boom"))),
+            failed
+        );
+        assert_eq!(
+            watch_status(needs_changes, Err(anyhow::anyhow!("boom"))),
+            failed
+        );
+        // A later, milder batch keeps the worst status
+        assert_eq!(watch_status(failed, Ok(needs_changes)), failed);
+        assert_eq!(watch_status(failed, Ok(ExitCode::SUCCESS)), failed);
+        assert_eq!(
+            watch_status(needs_changes, Ok(ExitCode::SUCCESS)),
+            needs_changes
+        );
+    }
+
+    #[test]
+    fn test_is_watched() {
+        let root = Path::new("repo");
+        let options = WalkOptions {
+            skip_dirs: vec![".venv".to_string()],
+            exclude: vec!["legacy/**".to_string()],
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let watched = |relative: &str| is_watched(&root.join(relative), root, &options);
+
+        assert!(watched("pyproject.toml"));
+        assert!(watched("packages/api/pyproject.toml"));
+        assert!(!watched(".venv/lib/pkg/pyproject.toml"));
+        assert!(!watched("legacy/app/pyproject.toml"));
+        assert!(!watched("packages/api/nested/pyproject.toml"));
+        assert!(!is_watched(
+            Path::new("elsewhere/pyproject.toml"),
+            root,
+            &options
+        ));
+
+        let options = WalkOptions {
+            include: vec!["services/*/pyproject.toml".to_string()],
+            ..Default::default()
+        };
+        assert!(is_watched(
+            &root.join("services/a/pyproject.toml"),
+            root,
+            &options
+        ));
+        assert!(!is_watched(
+            &root.join("tools/a/pyproject.toml"),
+            root,
+            &options
+        ));
+    }
+
+    #[test]
+    fn test_dependency_update_config() -> Result<()> {
+        let projects = ["".to_string(), "packages/api".to_string()];
+        assert_eq!(
+            dependency_update_config(DependencyUpdates::None, &projects),
+            None
+        );
+
+        let (path, dependabot) =
+            dependency_update_config(DependencyUpdates::Dependabot, &projects).unwrap_or_default();
+        assert_eq!(path, ".github/dependabot.yml");
+        assert!(
+            dependabot.contains("    directories:\n      - \"/\"\n      - \"/packages/api\"\n")
+        );
+
+        let (path, renovate) =
+            dependency_update_config(DependencyUpdates::Renovate, &projects).unwrap_or_default();
+        assert_eq!(path, "renovate.json");
+        let renovate: serde_json::Value = serde_json::from_str(&renovate)?;
+        assert_eq!(
+            renovate["pep621"]["managerFilePatterns"],
+            serde_json::json!(["/^pyproject\\.toml$/", "/^packages/api/pyproject\\.toml$/"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_commitizen() -> Result<()> {
+        let mut doc = "[project]\nname = \"x\"\n\n[tool.commitizen]\nname = \"cz_customize\"\n"
+            .parse::<DocumentMut>()?;
+        let mut changes = Vec::new();
+        set_commitizen(&mut doc, "release-{version}", &mut changes);
+        let commitizen = &doc["tool"]["commitizen"];
+        assert_eq!(commitizen["name"].as_str(), Some("cz_customize"));
+        assert_eq!(commitizen["version_provider"].as_str(), Some("scm"));
+        assert_eq!(commitizen["tag_format"].as_str(), Some("release-$version"));
+        assert_eq!(changes.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scripts_and_main_modules() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let file = project.join("pyproject.toml");
+        fs::create_dir_all(project.join("src/my_app"))?;
+        fs::write(project.join("src/my_app/__init__.py"), "")?;
+        fs::write(
+            &file,
+            "[project]\nname = \"my-app\"\n\n[project.scripts]\nexisting = \"my_app.cli:run\"\n",
+        )?;
+        let config = UvinitConfig {
+            scripts: BTreeMap::from([
+                (
+                    "{{name}}".to_string(),
+                    "{{module}}.__main__:main".to_string(),
+                ),
+                ("existing".to_string(), "my_app.__main__:main".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let rendered = modify_pyproject_toml(&file, &config)?;
+        let doc = rendered.content.parse::<DocumentMut>()?;
+        assert_eq!(
+            doc["project"]["scripts"]["my-app"].as_str(),
+            Some("my_app.__main__:main")
+        );
+        assert_eq!(
+            doc["project"]["scripts"]["existing"].as_str(),
+            Some("my_app.cli:run")
+        );
+
+        let mut journal = Vec::new();
+        write_main_modules(
+            &[FileReport::new(&file, FileStatus::Modified)],
+            &mut journal,
+            &mut Vec::new(),
+        );
+        let stub = fs::read_to_string(project.join("src/my_app/__main__.py"))?;
+        assert!(stub.contains("def main() -> None:"));
+        assert!(stub.contains("Hello from my-app!"));
+        assert_eq!(journal.len(), 1);
+
+        assert_eq!(main_module_target("pkg.__main__:cli"), Some(("pkg", "cli")));
+        assert_eq!(main_module_target("pkg.cli:main"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_dockerfiles() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &file,
+            "[project]\nname = \"my-service\"\nrequires-python = \">=3.11.4\"\n\n[project.scripts]\nserve = \"my_service.app:main\"\n",
+        )?;
+        let reports = vec![FileReport::new(&file, FileStatus::Modified)];
+        let mut journal = Vec::new();
+
+        write_dockerfiles(
+            &reports,
+            templates::DOCKERFILE,
+            None,
+            OverwritePolicy::SkipExisting,
+            &mut journal,
+            &mut Vec::new(),
+        );
+        let dockerfile = fs::read_to_string(temp_dir.path().join("Dockerfile"))?;
+        assert!(
+            dockerfile.contains("FROM ghcr.io/astral-sh/uv:python3.11-bookworm-slim AS builder")
+        );
+        assert!(dockerfile.contains("uv sync --frozen --no-install-project --no-dev"));
+        assert!(dockerfile.contains("FROM python:3.11-slim-bookworm\n"));
+        assert!(dockerfile.ends_with("CMD [\"serve\"]\n"));
+        assert!(!dockerfile.contains("{{"));
+        assert_eq!(journal.len(), 1);
+
+        // Without scripts the package runs as a module, on the configured Python
+        let doc = "[project]\nname = \"my-service\"\n".parse::<DocumentMut>()?;
+        let dockerfile = render_dockerfile(templates::DOCKERFILE, &doc, Some("3.13.1"));
+        assert!(dockerfile.contains("FROM python:3.13-slim-bookworm\n"));
+        assert!(dockerfile.ends_with("CMD [\"python\", \"-m\", \"my_service\"]\n"));
+
+        // An existing Dockerfile is kept
+        write_dockerfiles(
+            &reports,
+            "changed",
+            None,
+            OverwritePolicy::SkipExisting,
+            &mut journal,
+            &mut Vec::new(),
+        );
+        assert_ne!(
+            fs::read_to_string(temp_dir.path().join("Dockerfile"))?,
+            "changed"
+        );
+        assert_eq!(journal.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_devcontainers() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &file,
+            "[project]\nname = \"my-service\"\nrequires-python = \">=3.11\"\n",
+        )?;
+        let reports = vec![FileReport::new(&file, FileStatus::Modified)];
+        let mut journal = Vec::new();
+        let extensions = UvinitConfig::default().devcontainer_extensions;
+
+        write_devcontainers(
+            &reports,
+            templates::DEVCONTAINER,
+            &extensions,
+            None,
+            OverwritePolicy::SkipExisting,
+            &mut journal,
+            &mut Vec::new(),
+        );
+        let target = temp_dir
+            .path()
+            .join(".devcontainer")
+            .join("devcontainer.json");
+        let devcontainer: serde_json::Value = serde_json::from_str(&fs::read_to_string(&target)?)?;
+        assert_eq!(devcontainer["name"], "my-service");
+        assert_eq!(
+            devcontainer["image"],
+            "mcr.microsoft.com/devcontainers/python:1-3.11-bookworm"
+        );
+        assert_eq!(
+            devcontainer["customizations"]["vscode"]["extensions"],
+            serde_json::json!(["charliermarsh.ruff", "ms-pyright.pyright"])
+        );
+        assert_eq!(journal.len(), 1);
+
+        // A team template with the configured Python
+        let doc = "[project]\nname = \"my-service\"\n".parse::<DocumentMut>()?;
+        assert_eq!(
+            render_devcontainer(
+                "{{name}} {{python_version}} {{extensions}}",
+                &doc,
+                &["ms-python.python".to_string()],
+                Some("3.13.1"),
+            ),
+            "my-service 3.13 [\"ms-python.python\"]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_license_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let licensed = temp_dir.path().join("licensed");
+        let shipped = temp_dir.path().join("shipped");
+        let kept = temp_dir.path().join("kept");
+        for (dir, license) in [(&licensed, "MIT"), (&shipped, "MIT"), (&kept, "Apache-2.0")] {
+            fs::create_dir_all(dir)?;
+            fs::write(
+                dir.join("pyproject.toml"),
+                format!(
+                    "[project]\nname = \"x\"\nlicense = \"{license}\"\nauthors = [{{ name = \"Ada Lovelace\" }}]\n"
+                ),
+            )?;
+        }
+        fs::write(shipped.join("COPYING"), "custom")?;
+
+        let mut set_license =
+            FileReport::new(&licensed.join("pyproject.toml"), FileStatus::Modified);
+        set_license
+            .changes
+            .push(Change::set("project.license", "\"MIT\""));
+        let mut shipped_report =
+            FileReport::new(&shipped.join("pyproject.toml"), FileStatus::Modified);
+        shipped_report.changes = set_license.changes.clone();
+        // The license was already there, so the project's files are its own business
+        let kept_report = FileReport::new(&kept.join("pyproject.toml"), FileStatus::Modified);
+        let mut journal = Vec::new();
+
+        write_license_files(
+            &[set_license, shipped_report, kept_report],
+            OverwritePolicy::SkipExisting,
+            &mut journal,
+            &mut Vec::new(),
+        );
+        let year = chrono::Local::now().format("%Y").to_string();
+        assert!(
+            fs::read_to_string(licensed.join("LICENSE"))?
+                .contains(&format!("Copyright (c) {year} Ada Lovelace\n"))
+        );
+        assert!(!shipped.join("LICENSE").exists());
+        assert!(!kept.join("LICENSE").exists());
+        assert_eq!(journal.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_python_version_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("pyproject.toml");
+        let pinned = temp_dir.path().join(".python-version");
+        let reports = [FileReport::new(&file, FileStatus::Modified)];
+        let mut journal = Vec::new();
+
+        fs::write(&file, "[project]\nname = \"x\"\n")?;
+        write_python_version_files(&reports, None, &mut journal, &mut Vec::new());
+        assert!(!pinned.exists());
+
+        fs::write(
+            &file,
+            "[project]\nname = \"x\"\nrequires-python = \">=3.11, <4\"\n",
+        )?;
+        write_python_version_files(&reports, None, &mut journal, &mut Vec::new());
+        assert_eq!(fs::read_to_string(&pinned)?, "3.11\n");
+
+        // The configured version wins and updates the file
+        write_python_version_files(&reports, Some("3.13"), &mut journal, &mut Vec::new());
+        assert_eq!(fs::read_to_string(&pinned)?, "3.13\n");
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal[1].before.as_deref(), Some("3.11\n"));
+
+        write_python_version_files(&reports, Some("3.13"), &mut journal, &mut Vec::new());
+        assert_eq!(journal.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        let (requirements, unsupported) = parse_requirements(
+            "# Pinned for prod\n-r base.txt\n--index-url https://pypi.org/simple\nrequests>=2.31  # http\nDjango==5.0 \\\n    --hash=sha256:abc\nuvicorn[standard]; python_version >= \"3.10\"\nmylib @ git+https://example.com/mylib.git\n-e ./local\nhttps://example.com/pkg.whl\n\n",
+        );
+        assert_eq!(
+            requirements,
+            vec![
+                "requests>=2.31",
+                "Django==5.0",
+                "uvicorn[standard]; python_version >= \"3.10\"",
+                "mylib @ git+https://example.com/mylib.git",
+            ]
+        );
+        assert_eq!(
+            unsupported,
+            vec!["-e ./local", "https://example.com/pkg.whl"]
         );
-        assert!(!files.iter().any(|f| f.to_string_lossy().contains(".git")));
-
-        Ok(())
     }
 
     #[test]
-    fn test_has_project_dynamic() -> Result<()> {
+    fn test_modify_pyproject_toml_import_requirements() -> Result<()> {
         let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let test_file = project.join("pyproject.toml");
+        fs::write(project.join("requirements.txt"), "Requests>=2.31\nrich\n")?;
+        fs::write(
+            project.join("requirements-dev.txt"),
+            "-r requirements.txt\npytest>=8\n-e .\n",
+        )?;
+        fs::write(
+            &test_file,
+            r#"[project]
+name = "app"
+dynamic = ["version"]
+dependencies = ["requests"]
+"#,
+        )?;
+        let config = UvinitConfig {
+            import_requirements: true,
+            ..Default::default()
+        };
+
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        // The pinned requirement replaces the bare one
+        assert!(content.contains(r#"dependencies = ["Requests>=2.31", "rich"]"#));
+        assert!(content.contains("[dependency-groups]\ndev = [\n    \"pytest>=8\",\n]\n"));
+        assert_eq!(
+            rendered.warnings,
+            vec!["requirements-dev.txt: skipped `-e .`, which is not a PEP 508 requirement"]
+        );
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_py_typed() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let test_file = project.join("pyproject.toml");
+        fs::create_dir_all(project.join("src").join("typed_lib"))?;
+        fs::write(project.join("src/typed_lib/__init__.py"), "")?;
+        let config = UvinitConfig {
+            enable_py_typed: true,
+            ..Default::default()
+        };
 
-        // Test file with dynamic field
-        let file_with_dynamic = temp_dir.path().join("with_dynamic.toml");
         fs::write(
-            &file_with_dynamic,
-            r#"
-[project]
-name = "test"
+            &test_file,
+            r#"[project]
+name = "typed-lib"
 dynamic = ["version"]
-description = "Test project"
+classifiers = ["Programming Language :: Python :: 3"]
+
+[build-system]
+requires = ["hatchling", "hatch-vcs"]
+build-backend = "hatchling.build"
+
+[tool.hatch.build.targets.wheel]
+only-include = ["src/typed_lib/core.py"]
 "#,
         )?;
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains(
+            "classifiers = [\"Programming Language :: Python :: 3\", \"Typing :: Typed\"]"
+        ));
+        assert!(
+            content
+                .contains("only-include = [\"src/typed_lib/core.py\", \"src/typed_lib/py.typed\"]")
+        );
+        assert_eq!(
+            rendered
+                .changes
+                .iter()
+                .filter(|c| c.to_string().contains("py.typed"))
+                .count(),
+            1
+        );
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
 
-        // Test file without dynamic field
-        let file_without_dynamic = temp_dir.path().join("without_dynamic.toml");
         fs::write(
-            &file_without_dynamic,
-            r#"
-[project]
-name = "test"
-version = "0.1.0"
-description = "Test project"
+            &test_file,
+            "[project]\nname = \"typed-lib\"\ndynamic = [\"version\"]\n\n[build-system]\nrequires = [\"setuptools\", \"setuptools-scm\"]\nbuild-backend = \"setuptools.build_meta\"\n",
+        )?;
+        let config = UvinitConfig {
+            version_backend: VersionBackend::SetuptoolsScm,
+            ..config
+        };
+        modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains("classifiers = [\n    \"Typing :: Typed\",\n]\n"));
+        assert!(content.contains("[tool.setuptools.package-data]\ntyped_lib = [\"py.typed\"]\n"));
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_namespace_packages() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let test_file = project.join("pyproject.toml");
+        fs::create_dir_all(project.join("src").join("acme").join("tools"))?;
+        fs::write(project.join("src/acme/tools/__init__.py"), "")?;
+        let config = UvinitConfig {
+            detect_namespace_packages: true,
+            ..Default::default()
+        };
+
+        fs::write(
+            &test_file,
+            "[project]\nname = \"acme-tools\"\ndynamic = [\"version\"]\n\n[build-system]\nrequires = [\"hatchling\", \"hatch-vcs\"]\nbuild-backend = \"hatchling.build\"\n",
+        )?;
+        modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains("[tool.hatch.build.targets.wheel]\npackages = [\"src/acme\"]\n"));
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
+
+        // Wheel file selections the project already made are left alone
+        fs::write(
+            &test_file,
+            "[project]\nname = \"acme-tools\"\ndynamic = [\"version\"]\n\n[build-system]\nrequires = [\"hatchling\", \"hatch-vcs\"]\nbuild-backend = \"hatchling.build\"\n\n[tool.hatch.build.targets.wheel]\nonly-include = [\"src/acme\"]\n",
+        )?;
+        assert!(
+            !render_pyproject_toml(&test_file, &config)?
+                .changes
+                .iter()
+                .any(|c| c.key() == "tool.hatch.build.targets.wheel.packages")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_derive_classifiers() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let test_file = project.join("pyproject.toml");
+        fs::create_dir_all(project.join("src").join("typed_lib"))?;
+        fs::write(project.join("src/typed_lib/__init__.py"), "")?;
+        fs::write(project.join("src/typed_lib/py.typed"), "")?;
+        let config = UvinitConfig {
+            derive_classifiers: true,
+            ..Default::default()
+        };
+
+        fs::write(
+            &test_file,
+            r#"[project]
+name = "typed-lib"
+dynamic = ["version"]
+requires-python = ">=3.12,<3.14"
+license = "MIT"
+classifiers = ["Programming Language :: Python :: 3", "Framework :: Django"]
+
+[build-system]
+requires = ["hatchling", "hatch-vcs"]
+build-backend = "hatchling.build"
 "#,
         )?;
+        modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains(
+            "classifiers = [\"Programming Language :: Python :: 3\", \"Framework :: Django\", \"Programming Language :: Python :: 3.12\", \"Programming Language :: Python :: 3.13\", \"License :: OSI Approved :: MIT License\", \"Typing :: Typed\"]"
+        ));
+        assert!(
+            render_pyproject_toml(&test_file, &config)?
+                .changes
+                .is_empty()
+        );
 
-        // Test file with empty project section
-        let file_empty_project = temp_dir.path().join("empty_project.toml");
+        // setuptools refuses license classifiers alongside a license expression
+        fs::remove_file(project.join("src/typed_lib/py.typed"))?;
         fs::write(
-            &file_empty_project,
-            r#"
-[project]
-name = "test"
+            &test_file,
+            "[project]\nname = \"typed-lib\"\ndynamic = [\"version\"]\nrequires-python = \">=3.13\"\nlicense = \"MIT\"\n\n[build-system]\nrequires = [\"setuptools\", \"setuptools-scm\"]\nbuild-backend = \"setuptools.build_meta\"\n",
+        )?;
+        let config = UvinitConfig {
+            version_backend: VersionBackend::SetuptoolsScm,
+            ..config
+        };
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains(
+            "classifiers = [\n    \"Programming Language :: Python :: 3\",\n    \"Programming Language :: Python :: 3.13\",\n    \"Programming Language :: Python :: 3.14\",\n]\n"
+        ));
+        assert!(rendered.warnings.iter().any(|w| w.contains("MIT License")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_pytest_ini_options() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            r#"[project]
+name = "x"
+dynamic = ["version"]
+
+[tool.pytest.ini_options]
+addopts = "-q"
+markers = [
+    "slow: long-running tests",
+]
 "#,
         )?;
+        let config = UvinitConfig {
+            pytest_ini_options: toml::from_str(
+                r#"
+testpaths = ["tests"]
+addopts = "-ra --strict-markers"
+markers = ["slow: long-running tests", "integration: needs services"]
+filterwarnings = ["error"]
+"#,
+            )?,
+            ..Default::default()
+        };
 
-        // Test file without project section
-        let file_no_project = temp_dir.path().join("no_project.toml");
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains(
+            r#"[tool.pytest.ini_options]
+addopts = "-q"
+markers = [
+    "slow: long-running tests",
+    "integration: needs services",
+]
+asyncio_mode = "auto"
+filterwarnings = ["error"]
+testpaths = ["tests"]
+"#
+        ));
+        assert!(rendered.changes.contains(&Change::append(
+            "tool.pytest.ini_options.markers",
+            "integration: needs services"
+        )));
+
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_modify_pyproject_toml_patches() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
         fs::write(
-            &file_no_project,
+            &test_file,
+            r#"[project]
+name = "x"
+dynamic = ["version"]
+
+[tool.ruff]
+line-length = 100
+
+[tool.ruff.lint]
+ignore = ["E501"]
+"#,
+        )?;
+        let config: UvinitConfig = toml::from_str(
             r#"
-[build-system]
-requires = ["hatchling"]
+[[patches]]
+key = "tool.ruff.line-length"
+value = 120
+
+[[patches]]
+key = "tool.ruff.lint.ignore"
+value = ["E501", "D203"]
+strategy = "append-unique"
+
+[[patches]]
+key = "tool.ruff.target-version"
+value = "py312"
+strategy = "overwrite"
+
+[[patches]]
+key = "tool.codespell"
+value = { skip = "*.lock", quiet-level = 3 }
+
+[[patches]]
+key = "project.name.oops"
+value = 1
 "#,
         )?;
 
-        assert!(has_project_dynamic(&file_with_dynamic)?);
-        assert!(!has_project_dynamic(&file_without_dynamic)?);
-        assert!(!has_project_dynamic(&file_empty_project)?);
-        assert!(!has_project_dynamic(&file_no_project)?);
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains(
+            r#"[tool.ruff]
+line-length = 100
+target-version = "py312"
 
+[tool.ruff.lint]
+ignore = ["E501", "D203"]
+"#
+        ));
+        assert!(content.contains("[tool.codespell]\nquiet-level = 3\nskip = \"*.lock\"\n"));
+        assert_eq!(
+            rendered.warnings,
+            vec!["ignored patch for project.name.oops: project.name is not a table"]
+        );
+
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
         Ok(())
     }
 
     #[test]
-    fn test_modify_pyproject_toml() -> Result<()> {
+    fn test_modify_pyproject_toml_hatch_vcs_options() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test_pyproject.toml");
-
-        // Create initial pyproject.toml
+        let test_file = temp_dir.path().join("pyproject.toml");
         fs::write(
             &test_file,
-            r#"
-[project]
-name = "test-project"
+            r#"[project]
+name = "x"
 version = "0.1.0"
-description = "A test project"
-dependencies = ["requests"]
 
 [build-system]
 requires = ["hatchling"]
 build-backend = "hatchling.build"
+
+[tool.hatch.version.raw-options]
+version_scheme = "guess-next-dev"
 "#,
         )?;
-
         let config = UvinitConfig {
-            additional_requires: vec!["setuptools-scm".to_string()],
+            hatch_vcs_fallback_version: Some("0.0.0".to_string()),
+            hatch_vcs_raw_options: toml::from_str(
+                "local_scheme = \"no-local-version\"\nversion_scheme = \"post-release\"\n",
+            )?,
             ..Default::default()
         };
 
-        // Modify the file
         modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains(
+            r#"[tool.hatch.version]
+source = "vcs"
+fallback-version = "0.0.0"
 
-        // Read and verify the modified content
-        let modified_content = fs::read_to_string(&test_file)?;
-        println!("Modified content:\n{}", modified_content);
-        let doc = modified_content.parse::<DocumentMut>()?;
+[tool.hatch.version.raw-options]
+version_scheme = "guess-next-dev"
+local_scheme = "no-local-version"
+"#
+        ));
 
-        // Check that version was removed and dynamic was added
-        let project = doc.get("project").unwrap().as_table().unwrap();
-        assert!(!project.contains_key("version"));
-        assert!(project.contains_key("dynamic"));
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
+        Ok(())
+    }
 
-        let dynamic = project.get("dynamic").unwrap().as_array().unwrap();
-        assert_eq!(dynamic.len(), 1);
-        assert_eq!(dynamic.get(0).unwrap().as_str().unwrap(), "version");
+    #[test]
+    fn test_version_file_hook() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        let test_file = project.join("pyproject.toml");
+        fs::create_dir_all(project.join("src").join("lib"))?;
+        fs::write(
+            project.join("src/lib/__init__.py"),
+            "\"\"\"A library.\"\"\"\n",
+        )?;
+        fs::write(
+            &test_file,
+            "[project]\nname = \"lib\"\nversion = \"0.1.0\"\n\n[build-system]\nrequires = [\"hatchling\"]\nbuild-backend = \"hatchling.build\"\n",
+        )?;
+        let config = UvinitConfig {
+            enable_version_file: true,
+            backup_before_write: false,
+            ..Default::default()
+        };
 
-        // Check build-system.requires
-        let build_system = doc.get("build-system").unwrap().as_table().unwrap();
-        let requires = build_system.get("requires").unwrap().as_array().unwrap();
+        let report = process_file(
+            &test_file,
+            load_pyproject(&test_file)?,
+            &WorkspaceMembers::new(),
+            &RepoFactsByRoot::new(),
+            &config,
+            &UvinitOptions::default(),
+        );
+        assert_eq!(report.status, FileStatus::Modified);
+        assert!(
+            fs::read_to_string(&test_file)?
+                .contains("[tool.hatch.build.hooks.vcs]\nversion-file = \"src/lib/_version.py\"\n")
+        );
 
-        let requires_vec: Vec<&str> = requires.iter().map(|v| v.as_str().unwrap()).collect();
+        let mut journal = Vec::new();
+        write_version_imports(&[report], &mut journal, &mut Vec::new());
+        assert_eq!(
+            fs::read_to_string(project.join("src/lib/__init__.py"))?,
+            "\"\"\"A library.\"\"\"\n\nfrom ._version import __version__\n"
+        );
+        assert_eq!(journal.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_opted_out() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let opted_out = |content: &str| -> Result<bool> {
+            fs::write(&test_file, content)?;
+            Ok(is_opted_out(&load_pyproject(&test_file)?))
+        };
+
+        assert!(opted_out(
+            "# Vendored copy\n# post-init: ignore\n\n[project]\nname = \"x\"\n"
+        )?);
+        assert!(opted_out(
+            "[project]\nname = \"x\"\n\n[tool.post-init]\nignore = true\n"
+        )?);
+        assert!(!opted_out(
+            "[project]\nname = \"x\"\n\n[tool.post-init]\nignore = false\n"
+        )?);
+        // Only the leading comments count
+        assert!(!opted_out(
+            "[project]\nname = \"x\"\n# post-init: ignore\n"
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_competing_version_sources() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let doc = |content: &str| content.parse::<DocumentMut>();
+
+        let scm =
+            doc("[project]\nname = \"x\"\n\n[tool.setuptools_scm]\nversion_file = \"x/_v.py\"\n")?;
+        assert_eq!(
+            competing_version_sources(&test_file, &scm, VersionBackend::HatchVcs),
+            vec!["tool.setuptools_scm"]
+        );
+        assert!(
+            competing_version_sources(&test_file, &scm, VersionBackend::SetuptoolsScm).is_empty()
+        );
+
+        // The fallback uvinit records itself is not a competitor
+        let fallback = doc("[tool.setuptools_scm]\nfallback_version = \"0.1.0\"\n")?;
+        assert!(
+            competing_version_sources(&test_file, &fallback, VersionBackend::HatchVcs).is_empty()
+        );
+
+        let pdv = doc("[tool.poetry-dynamic-versioning]\nenable = true\n")?;
+        assert_eq!(
+            competing_version_sources(&test_file, &pdv, VersionBackend::HatchVcs),
+            vec!["tool.poetry-dynamic-versioning"]
+        );
+        let poetry = doc(
+            "[tool.poetry]\nname = \"x\"\n\n[tool.poetry-dynamic-versioning]\nenable = true\n",
+        )?;
+        assert!(
+            competing_version_sources(&test_file, &poetry, VersionBackend::HatchVcs).is_empty()
+        );
+
+        let plain = doc("[project]\nname = \"x\"\n")?;
+        assert!(competing_version_sources(&test_file, &plain, VersionBackend::HatchVcs).is_empty());
+        fs::write(
+            temp_dir.path().join("setup.cfg"),
+            "[versioneer]\nVCS = git\n",
+        )?;
+        assert_eq!(
+            competing_version_sources(&test_file, &plain, VersionBackend::HatchVcs),
+            vec!["versioneer"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_repo_facts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let git = |args: &[&str]| Command::new("git").arg("-C").arg(root).args(args).status();
+        git(&["init", "-q"])?;
+        git(&["config", "user.name", "Ada Lovelace"])?;
+        git(&["config", "user.email", "ada@example.com"])?;
+        git(&["remote", "add", "origin", "git@github.com:ada/engine.git"])?;
+        let files = [
+            root.join("pyproject.toml"),
+            root.join("packages/api/pyproject.toml"),
+        ];
+        fs::create_dir_all(root.join("packages/api"))?;
+
+        let config = UvinitConfig {
+            enable_metadata: true,
+            fill_authors: true,
+            ..Default::default()
+        };
+        // Both projects share one lookup
+        let facts = repo_facts(&files, &config);
+        assert_eq!(facts.len(), 1);
+        let repo = &facts[&git::work_tree_root(root)];
+        assert_eq!(
+            repo.repository.as_deref(),
+            Some("https://github.com/ada/engine")
+        );
+        assert_eq!(repo.user_name.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(repo.user_email.as_deref(), Some("ada@example.com"));
+
+        // Nothing is asked for what the config leaves off
+        let facts = repo_facts(&files, &UvinitConfig::default());
+        let repo = &facts[&git::work_tree_root(root)];
+        assert_eq!(repo.repository, None);
+        assert_eq!(repo.user_name, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_authors() -> Result<()> {
+        let mut doc = "[project]\nname = \"x\"\n".parse::<DocumentMut>()?;
+        let mut changes = Vec::new();
+        let project_table = doc["project"].as_table_mut().unwrap();
 
-        assert!(requires_vec.contains(&"hatchling"));
-        assert!(requires_vec.contains(&"hatch-vcs"));
-        assert!(requires_vec.contains(&"setuptools-scm"));
+        set_authors(project_table, None, None, &mut changes);
+        assert!(changes.is_empty());
 
+        set_authors(
+            project_table,
+            Some("Ada Lovelace"),
+            Some("ada@example.com"),
+            &mut changes,
+        );
+        set_authors(project_table, Some("Someone Else"), None, &mut changes);
+        assert_eq!(
+            doc.to_string(),
+            "[project]\nname = \"x\"\nauthors = [{ name = \"Ada Lovelace\", email = \"ada@example.com\" }]\n"
+        );
+        assert_eq!(changes.len(), 1);
         Ok(())
     }
 
     #[test]
-    fn test_modify_pyproject_toml_with_existing_dynamic() -> Result<()> {
+    fn test_modify_pyproject_toml_dependency_groups() -> Result<()> {
         let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("existing_dynamic.toml");
-
-        // Create pyproject.toml with existing dynamic field
+        let test_file = temp_dir.path().join("pyproject.toml");
         fs::write(
             &test_file,
-            r#"
-[project]
-name = "test-project"
-dynamic = ["version", "description"]
-dependencies = ["requests"]
+            r#"[project]
+name = "x"
+dynamic = ["version"]
 
-[build-system]
-requires = ["hatchling", "hatch-vcs"]
-build-backend = "hatchling.build"
+[project.optional-dependencies]
+dev = [
+    "pytest>=8",
+    "ruff",
+]
+docs = ["mkdocs"]
 
-[tool.hatch.version]
-source = "vcs"
+[tool.uv]
+dev-dependencies = ["pytest", "mypy"]
 "#,
         )?;
-
         let config = UvinitConfig {
-            additional_requires: vec!["setuptools-scm".to_string()],
+            enable_dependency_groups: true,
             ..Default::default()
         };
 
-        // Should not modify file that already has dynamic
-        assert!(has_project_dynamic(&test_file)?);
-
-        // But if we force modify it, it should handle gracefully
-        modify_pyproject_toml(&test_file, &config)?;
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let content = fs::read_to_string(&test_file)?;
+        assert!(content.contains("[project.optional-dependencies]\ndocs = [\"mkdocs\"]\n"));
+        assert!(content.ends_with(
+            r#"[dependency-groups]
+dev = [
+    "pytest>=8",
+    "ruff",
+    "mypy",
+]
+"#
+        ));
+        assert!(!content.contains("[tool.uv]"));
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::remove("tool.uv.dev-dependencies"))
+        );
+        assert_eq!(rendered.warnings.len(), 1);
 
-        let modified_content = fs::read_to_string(&test_file)?;
-        let doc = modified_content.parse::<DocumentMut>()?;
+        let rerun = render_pyproject_toml(&test_file, &config)?;
+        assert!(rerun.changes.is_empty());
 
-        // Check that dynamic field still exists
-        let project = doc.get("project").unwrap().as_table().unwrap();
-        assert!(project.contains_key("dynamic"));
+        Ok(())
+    }
 
-        // Check that hatch-vcs wasn't duplicated
-        let build_system = doc.get("build-system").unwrap().as_table().unwrap();
-        let requires = build_system.get("requires").unwrap().as_array().unwrap();
+    #[test]
+    fn test_modify_pyproject_toml_custom_bandit() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\nversion = \"0.1.0\"\n\n[tool.bandit]\nskips = [\"B404\", \"B603\"]\n",
+        )?;
+        let config = UvinitConfig {
+            bandit_skips: vec!["B101".to_string(), "B603".to_string()],
+            bandit_exclude_dirs: vec!["scripts".to_string()],
+            ..Default::default()
+        };
 
-        let hatch_vcs_count = requires
-            .iter()
-            .filter(|v| v.as_str() == Some("hatch-vcs"))
-            .count();
+        let rendered = modify_pyproject_toml(&test_file, &config)?;
+        let doc = fs::read_to_string(&test_file)?.parse::<DocumentMut>()?;
+        let values = |key: &str| -> Vec<String> {
+            doc["tool"]["bandit"][key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(values("skips"), vec!["B404", "B603", "B101"]);
+        assert_eq!(values("exclude_dirs"), vec!["scripts"]);
+        assert!(
+            !rendered
+                .changes
+                .contains(&Change::append("tool.bandit.skips", "B603"))
+        );
 
-        assert_eq!(hatch_vcs_count, 1);
+        // Empty lists leave no bare [tool.bandit] table behind
+        fs::write(&test_file, "[project]\nname = \"x\"\nversion = \"0.1.0\"\n")?;
+        let config = UvinitConfig {
+            bandit_skips: Vec::new(),
+            bandit_exclude_dirs: Vec::new(),
+            ..Default::default()
+        };
+        modify_pyproject_toml(&test_file, &config)?;
+        assert!(!fs::read_to_string(&test_file)?.contains("[tool.bandit]"));
 
         Ok(())
     }
@@ -562,6 +8401,79 @@ build-backend = "hatchling.build"
             add_hatch_vcs: false,
             additional_requires: vec![],
             skip_dirs: vec![],
+            blank_line_between_tables: true,
+            override_version_source: false,
+            version_key_action: VersionKeyAction::Remove,
+            version_backend: VersionBackend::HatchVcs,
+            hatch_vcs_fallback_version: None,
+            hatch_vcs_raw_options: toml::Table::new(),
+            enable_version_file: false,
+            backup_before_write: false,
+            journal_runs: false,
+            validate: Validation::None,
+            git_commit: false,
+            git_commit_message: String::new(),
+            create_version_tag: false,
+            version_tag_format: "v{version}".to_string(),
+            initial_version: "0.1.0".to_string(),
+            respect_gitignore: true,
+            parallel_walk: true,
+            scan_cache: true,
+            max_depth: None,
+            follow_symlinks: true,
+            include: vec![],
+            exclude: vec![],
+            enable_ruff: false,
+            ruff_line_length: 88,
+            ruff_select: vec![],
+            bandit_skips: vec![],
+            bandit_exclude_dirs: vec![],
+            enable_mypy: false,
+            mypy_strict: true,
+            mypy_python_version: "3.12".to_string(),
+            mypy_ignore_missing_imports: false,
+            type_checker: TypeChecker::None,
+            type_checking_mode: TypeCheckingMode::Standard,
+            type_checker_include: Vec::new(),
+            type_checker_exclude: Vec::new(),
+            enable_coverage: false,
+            coverage_source: vec![],
+            coverage_omit: vec![],
+            coverage_fail_under: 80,
+            requires_python: None,
+            tighten_requires_python: false,
+            enable_metadata: false,
+            license: None,
+            classifiers: vec![],
+            fill_authors: false,
+            enable_precommit: false,
+            precommit_hooks: vec![],
+            precommit_install: false,
+            enable_commitizen: false,
+            dependency_updates: DependencyUpdates::None,
+            enable_publish_workflow: false,
+            publish_workflow_template: None,
+            enable_dockerfile: false,
+            dockerfile_template: None,
+            enable_devcontainer: false,
+            devcontainer_template: None,
+            devcontainer_extensions: vec![],
+            hatch_envs: BTreeMap::new(),
+            enable_py_typed: false,
+            derive_classifiers: false,
+            detect_namespace_packages: false,
+            migrate_src_layout: false,
+            pytest_ini_options: toml::Table::new(),
+            uv_settings: toml::Table::new(),
+            enable_dependency_groups: false,
+            enable_workspace_sources: false,
+            import_requirements: false,
+            migrate_setup: false,
+            enable_python_version_file: false,
+            python_version: None,
+            scripts: BTreeMap::new(),
+            mypy_overrides: BTreeMap::new(),
+            patches: Vec::new(),
         };
 
         modify_pyproject_toml(&test_file, &config)?;
@@ -588,6 +8500,365 @@ build-backend = "hatchling.build"
         Ok(())
     }
 
+    #[test]
+    fn test_proposed_path_and_render() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let original = r#"
+[project]
+name = "test-project"
+version = "0.1.0"
+"#;
+        fs::write(&test_file, original)?;
+
+        let out_path = proposed_path(&test_file, ".proposed");
+        assert_eq!(out_path, temp_dir.path().join("pyproject.toml.proposed"));
+
+        let rendered = render_pyproject_toml(&test_file, &UvinitConfig::default())?;
+        assert!(rendered.content.contains("dynamic"));
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::set("project.dynamic", r#"["version"]"#))
+        );
+        assert!(
+            rendered
+                .changes
+                .contains(&Change::remove("project.version"))
+        );
+
+        // Rendering alone must leave the original untouched
+        assert_eq!(fs::read_to_string(&test_file)?, original);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_static_version_and_tag_name() -> Result<()> {
+        let doc: DocumentMut = "[project]\nname = \"a\"\nversion = \"1.2.3\"\n".parse()?;
+        assert_eq!(static_version(&doc), Some("1.2.3".to_string()));
+        let doc: DocumentMut = "[tool.poetry]\nversion = \"0.4.0\"\n".parse()?;
+        assert_eq!(static_version(&doc), Some("0.4.0".to_string()));
+        let doc: DocumentMut = "[project]\ndynamic = [\"version\"]\n".parse()?;
+        assert_eq!(static_version(&doc), None);
+
+        assert_eq!(version_tag("v{version}", "1.2.3"), "v1.2.3");
+        assert_eq!(version_tag("release-{version}", "*"), "release-*");
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_status() {
+        let path = Path::new("pyproject.toml");
+        let configured = FileReport::new(path, FileStatus::AlreadyConfigured);
+        let would_change = FileReport::new(path, FileStatus::WouldChange);
+        let failed = FileReport::new(path, FileStatus::Failed("bad toml".to_string()));
+
+        assert_eq!(check_status(&[]), ExitCode::SUCCESS);
+        assert_eq!(
+            check_status(std::slice::from_ref(&configured)),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(
+            check_status(&[configured.clone(), would_change.clone()]),
+            ExitCode::from(CHECK_NEEDS_CHANGES)
+        );
+        assert_eq!(
+            check_status(&[would_change, failed, configured]),
+            ExitCode::from(CHECK_FAILED)
+        );
+    }
+
+    #[test]
+    fn test_run_uvinit_partial_failure() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let config_path = root.join("post-init.toml");
+        // Nothing may reach the real backup store, journal or scan cache
+        fs::write(
+            &config_path,
+            "[uvinit]\nbackup_before_write = false\njournal_runs = false\nscan_cache = false\n",
+        )?;
+        fs::create_dir_all(root.join("good"))?;
+        fs::create_dir_all(root.join("bad"))?;
+        fs::write(
+            root.join("good/pyproject.toml"),
+            "[project]\nname = \"good\"\nversion = \"0.1.0\"\n",
+        )?;
+        fs::write(
+            root.join("bad/pyproject.toml"),
+            "[project\nname = \"bad\"\n",
+        )?;
+
+        let report_path = root.join("report.json");
+        let options = UvinitOptions {
+            yes: true,
+            report: Some(report_path.clone()),
+            ..Default::default()
+        };
+        let config_args = ConfigArgs {
+            config_path: Some(config_path),
+            ..Default::default()
+        };
+        let code = run_uvinit(&[root.to_path_buf()], &options, &config_args)?;
+
+        assert_eq!(code, ExitCode::from(CHECK_FAILED));
+        assert!(fs::read_to_string(root.join("good/pyproject.toml"))?.contains("dynamic"));
+        let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&report_path)?)?;
+        assert_eq!(report["totals"]["failed"], 1);
+        assert_eq!(report["totals"]["changed"], 1);
+        let files = report["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0]["path"],
+            root.join("bad/pyproject.toml").display().to_string()
+        );
+        assert_eq!(files[0]["status"], "failed");
+        assert!(
+            files[0]["errors"][0]
+                .as_str()
+                .unwrap()
+                .starts_with("Failed to check:")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_uvinit_commits_companion_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let git = |args: &[&str]| Command::new("git").arg("-C").arg(root).args(args).output();
+        git(&["init", "-q"])?;
+        git(&["config", "user.name", "Ada Lovelace"])?;
+        git(&["config", "user.email", "ada@example.com"])?;
+        git(&["config", "commit.gpgsign", "false"])?;
+        let config_path = root.join(".git").join("post-init.toml");
+        // Nothing may reach the real backup store, journal or scan cache
+        fs::write(
+            &config_path,
+            "[uvinit]\nbackup_before_write = false\njournal_runs = false\nscan_cache = false\n\
+             git_commit = true\nenable_python_version_file = true\npython_version = \"3.12\"\n",
+        )?;
+        fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )?;
+
+        let options = UvinitOptions {
+            yes: true,
+            ..Default::default()
+        };
+        let config_args = ConfigArgs {
+            config_path: Some(config_path),
+            ..Default::default()
+        };
+        let code = run_uvinit(&[root.to_path_buf()], &options, &config_args)?;
+        assert_eq!(code, ExitCode::SUCCESS);
+
+        // The companion file goes into the same commit as pyproject.toml
+        assert!(root.join(".python-version").is_file());
+        let committed = git(&["show", "--name-only", "--format=", "HEAD"])?;
+        let committed = String::from_utf8_lossy(&committed.stdout);
+        assert!(committed.lines().any(|line| line == ".python-version"));
+        assert!(committed.lines().any(|line| line == "pyproject.toml"));
+        let status = git(&["status", "--porcelain"])?;
+        assert_eq!(String::from_utf8_lossy(&status.stdout), "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_uvinit_stages_companion_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let git = |args: &[&str]| Command::new("git").arg("-C").arg(root).args(args).output();
+        git(&["init", "-q"])?;
+        let config_path = root.join(".git").join("post-init.toml");
+        // Nothing may reach the real backup store, journal or scan cache
+        fs::write(
+            &config_path,
+            "[uvinit]\nbackup_before_write = false\njournal_runs = false\nscan_cache = false\n\
+             enable_python_version_file = true\npython_version = \"3.12\"\n",
+        )?;
+        fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )?;
+
+        let options = UvinitOptions {
+            yes: true,
+            git_add: true,
+            ..Default::default()
+        };
+        let config_args = ConfigArgs {
+            config_path: Some(config_path),
+            ..Default::default()
+        };
+        run_uvinit(&[root.to_path_buf()], &options, &config_args)?;
+
+        let staged = git(&["diff", "--cached", "--name-only"])?;
+        let staged = String::from_utf8_lossy(&staged.stdout);
+        assert_eq!(
+            staged.lines().collect::<Vec<_>>(),
+            [".python-version", "pyproject.toml"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dirty_files_by_repository() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let git =
+            |dir: &Path, args: &[&str]| Command::new("git").arg("-C").arg(dir).args(args).output();
+        for repo in ["a", "b", "a/nested"] {
+            fs::create_dir_all(root.join(repo))?;
+            git(&root.join(repo), &["init", "-q"])?;
+            fs::write(root.join(repo).join("pyproject.toml"), "[project]\n")?;
+        }
+        let a = root.join("a");
+        git(&a, &["config", "user.name", "Ada Lovelace"])?;
+        git(&a, &["config", "user.email", "ada@example.com"])?;
+        git(&a, &["config", "commit.gpgsign", "false"])?;
+        git(&a, &["add", "pyproject.toml"])?;
+        git(&a, &["commit", "-q", "-m", "init"])?;
+
+        // Two repositories side by side, one of them holding a third
+        let files = [
+            a.join("pyproject.toml"),
+            root.join("b/pyproject.toml"),
+            a.join("nested/pyproject.toml"),
+        ];
+        let dirty = dirty_files_by_repository(&files)?;
+        assert!(!dirty.contains(&fs::canonicalize(&files[0])?));
+        assert!(dirty.contains(&fs::canonicalize(&files[1])?));
+        assert!(dirty.contains(&fs::canonicalize(&files[2])?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nothing_to_process_message() {
+        let reports = [
+            FileReport::new(Path::new("a/pyproject.toml"), FileStatus::AlreadyConfigured),
+            FileReport::new(
+                Path::new("b/pyproject.toml"),
+                FileStatus::Failed("bad".to_string()),
+            ),
+            FileReport::new(
+                Path::new("c/pyproject.toml"),
+                FileStatus::Skipped("opted out".to_string()),
+            ),
+        ];
+        let (configured, failed, opted_out) = (&reports[..1], &reports[1..2], &reports[2..]);
+        let options = UvinitOptions::default();
+        assert!(nothing_to_process_message(configured, &options).is_some());
+        assert_eq!(nothing_to_process_message(&reports[..2], &options), None);
+        assert_eq!(nothing_to_process_message(opted_out, &options), None);
+
+        let repair = UvinitOptions {
+            fix_requires_only: true,
+            ..Default::default()
+        };
+        assert!(nothing_to_process_message(opted_out, &repair).is_some());
+        assert_eq!(nothing_to_process_message(failed, &repair), None);
+    }
+
+    #[test]
+    fn test_process_file_dry_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        let original = "[project]\nname = \"x\"\nversion = \"0.1.0\"\n";
+        fs::write(&test_file, original)?;
+
+        let options = UvinitOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = process_file(
+            &test_file,
+            load_pyproject(&test_file)?,
+            &WorkspaceMembers::new(),
+            &RepoFactsByRoot::new(),
+            &UvinitConfig::default(),
+            &options,
+        );
+
+        assert_eq!(report.status, FileStatus::WouldChange);
+        assert_eq!(fs::read_to_string(&test_file)?, original);
+        let diff = report.diff.unwrap();
+        assert!(diff.contains("-version = \"0.1.0\""));
+        assert!(diff.contains("+dynamic = [\"version\"]"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_file_leaves_configured_file_alone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(&test_file, "[project]\nname = \"x\"\nversion = \"0.1.0\"\n")?;
+        let config = UvinitConfig {
+            backup_before_write: false,
+            ..Default::default()
+        };
+        modify_pyproject_toml(&test_file, &config)?;
+        let configured = fs::read_to_string(&test_file)?;
+
+        let report = process_file(
+            &test_file,
+            load_pyproject(&test_file)?,
+            &WorkspaceMembers::new(),
+            &RepoFactsByRoot::new(),
+            &config,
+            &UvinitOptions::default(),
+        );
+
+        assert_eq!(report.status, FileStatus::AlreadyConfigured);
+        assert!(report.changes.is_empty());
+        assert_eq!(fs::read_to_string(&test_file)?, configured);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blank_line_between_tables() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\nversion = \"0.1.0\"\n[build-system]\nrequires = [\"hatchling\"]",
+        )?;
+
+        let rendered = render_pyproject_toml(&test_file, &UvinitConfig::default())?;
+        assert!(
+            rendered
+                .content
+                .contains("build-backend = \"hatchling.build\"\n\n[tool.hatch.version]")
+        );
+        assert!(
+            rendered
+                .content
+                .contains("\"vcs\"\n\n[tool.pytest.ini_options]")
+        );
+        assert!(rendered.content.contains("\"auto\"\n\n[tool.bandit]"));
+        // Existing tables keep their original spacing
+        assert!(rendered.content.contains("[\"version\"]\n[build-system]"));
+
+        let config = UvinitConfig {
+            blank_line_between_tables: false,
+            ..Default::default()
+        };
+        let rendered = render_pyproject_toml(&test_file, &config)?;
+        assert!(
+            rendered
+                .content
+                .contains("\"vcs\"\n[tool.pytest.ini_options]")
+        );
+        assert!(rendered.content.contains("\"auto\"\n[tool.bandit]"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_config_load_and_save() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -621,4 +8892,132 @@ build-backend = "hatchling.build"
 
         Ok(())
     }
+
+    #[test]
+    fn test_plan_file_reprocess() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\nname = \"test-project\"\ndynamic = [\"version\"]\n",
+        )?;
+        let pyproject = load_pyproject(&test_file)?;
+        let config = UvinitConfig {
+            detect_namespace_packages: false,
+            ..Default::default()
+        };
+        let plan = |options: &UvinitOptions| {
+            plan_file(
+                &test_file,
+                &pyproject,
+                &WorkspaceMembers::new(),
+                &config,
+                options,
+            )
+        };
+
+        // --force only governs overwriting, it doesn't reopen configured files
+        let force = UvinitOptions {
+            policy: OverwritePolicy::Force,
+            ..Default::default()
+        };
+        assert!(matches!(plan(&force), FilePlan::Configured));
+        assert!(scan_cache_applies(&config, &force));
+
+        let reprocess = UvinitOptions {
+            reprocess: true,
+            ..Default::default()
+        };
+        assert!(matches!(plan(&reprocess), FilePlan::Process(_)));
+        assert!(!scan_cache_applies(&config, &reprocess));
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_competing_sources_reprocess() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\nname = \"x\"\n\n[tool.setuptools_scm]\nversion_file = \"x/_v.py\"\n",
+        )?;
+        let config = UvinitConfig::default();
+        let kept = |options: &UvinitOptions| -> Result<usize> {
+            let mut to_process = vec![(&test_file, load_pyproject(&test_file)?)];
+            let mut reports = Vec::new();
+            skip_competing_sources(&mut to_process, &mut reports, &config, options);
+            assert_eq!(to_process.len() + reports.len(), 1);
+            Ok(to_process.len())
+        };
+
+        let force = UvinitOptions {
+            policy: OverwritePolicy::Force,
+            ..Default::default()
+        };
+        assert_eq!(kept(&force)?, 0);
+        let reprocess = UvinitOptions {
+            reprocess: true,
+            ..Default::default()
+        };
+        assert_eq!(kept(&reprocess)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_files_scan_cache_follows_config() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache_dir = temp_dir.path().join("cache");
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join("src/acme/tools"))?;
+        fs::write(project.join("src/acme/tools/__init__.py"), "")?;
+        let test_file = project.join("pyproject.toml");
+        fs::write(
+            &test_file,
+            "[project]\nname = \"acme-tools\"\ndynamic = [\"version\"]\n\n[build-system]\nrequires = [\"hatchling\"]\nbuild-backend = \"hatchling.build\"\n",
+        )?;
+        let files = vec![test_file.clone()];
+        let options = UvinitOptions::default();
+        let processed = |config: &UvinitConfig| {
+            let cache = scan_cache_applies(config, &options)
+                .then(|| ScanCache::in_dir(&cache_dir, &project, &scan_cache_settings(config)));
+            let selection = select_files(
+                &files,
+                cache,
+                &walk_options(config),
+                config,
+                &options,
+                &mut RunErrors::new(),
+            );
+            selection.to_process.len()
+        };
+
+        // Found configured while namespace packages are not looked for
+        let without_namespaces = UvinitConfig {
+            detect_namespace_packages: false,
+            ..Default::default()
+        };
+        assert_eq!(processed(&without_namespaces), 0);
+        assert!(
+            ScanCache::in_dir(
+                &cache_dir,
+                &project,
+                &scan_cache_settings(&without_namespaces)
+            )
+            .is_configured(&test_file)
+        );
+
+        // Looking for them reads the package directories, not the cache
+        assert_eq!(processed(&UvinitConfig::default()), 1);
+
+        // A cache written under other settings is not trusted either
+        let other_backend = UvinitConfig {
+            version_backend: VersionBackend::SetuptoolsScm,
+            ..without_namespaces
+        };
+        assert!(
+            !ScanCache::in_dir(&cache_dir, &project, &scan_cache_settings(&other_backend))
+                .is_configured(&test_file)
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::uvinit::modify_pyproject_toml;
+use crate::config::*;
+use crate::files::{OverwritePolicy, WriteOutcome, write_file};
+use crate::infer::ProjectInfo;
+use crate::output::say;
+
+/// Importable module name for a project name, e.g. `My-Tool` -> `my_tool`
+pub(crate) fn module_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '-' | '.' | ' ' => '_',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Create the `src/<module>` package and a `tests/` skeleton, keeping anything uv already wrote
+fn scaffold_layout(project_dir: &Path, module: &str) -> Result<Vec<PathBuf>> {
+    let files = [
+        (
+            project_dir.join("src").join(module).join("__init__.py"),
+            String::new(),
+        ),
+        (project_dir.join("tests").join("__init__.py"), String::new()),
+        (
+            project_dir.join("tests").join(format!("test_{module}.py")),
+            format!("import {module}\n\n\ndef test_import():\n    assert {module}\n"),
+        ),
+    ];
+
+    let mut created = Vec::new();
+    for (path, content) in files {
+        if write_file(&path, &content, OverwritePolicy::SkipExisting)? == WriteOutcome::Created {
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+pub fn run_uvnew(project: &ProjectInfo, config_args: &ConfigArgs) -> Result<()> {
+    let config = load_config(config_args, Some("uvinit"))?;
+
+    let project_dir = Path::new(&project.name);
+    if project_dir.exists() {
+        return Err(anyhow::anyhow!(
+            "Destination {} already exists",
+            project_dir.display()
+        ));
+    }
+
+    say!("📦 Creating uv project: {}", project.name);
+
+    // hatch-vcs needs the hatchling backend rather than uv's default
+    let mut command = Command::new("uv");
+    command.args([
+        "init",
+        &project.name,
+        "--package",
+        "--build-backend",
+        "hatch",
+    ]);
+    if let Some(description) = &project.description {
+        command.args(["--description", description]);
+    }
+    let status = command
+        .status()
+        .with_context(|| "Failed to run uv; is it installed?")?;
+    log::info!("uv init {} --package exited with {}", project.name, status);
+    if !status.success() {
+        return Err(anyhow::anyhow!("uv init failed with {status}"));
+    }
+
+    let pyproject = project_dir.join("pyproject.toml");
+    let rendered = modify_pyproject_toml(&pyproject, &config.uvinit)
+        .with_context(|| format!("Failed to update {}", pyproject.display()))?;
+    say!("  🔧 {}", pyproject.display());
+    for change in &rendered.changes {
+        say!("     {change}");
+    }
+    for warning in &rendered.warnings {
+        say!("  ⚠️  {warning}");
+    }
+
+    for path in scaffold_layout(project_dir, &module_name(&project.name))? {
+        say!("  ✅ {}", path.display());
+    }
+
+    say!("\n🎉 Done!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name("my-tool"), "my_tool");
+        assert_eq!(module_name("My.Tool"), "my_tool");
+    }
+
+    #[test]
+    fn test_scaffold_layout_keeps_existing_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let init = temp_dir.path().join("src/my_tool/__init__.py");
+        fs::create_dir_all(init.parent().unwrap())?;
+        fs::write(&init, "def main(): ...\n")?;
+
+        let created = scaffold_layout(temp_dir.path(), "my_tool")?;
+
+        assert_eq!(fs::read_to_string(&init)?, "def main(): ...\n");
+        assert_eq!(
+            created,
+            vec![
+                temp_dir.path().join("tests/__init__.py"),
+                temp_dir.path().join("tests/test_my_tool.py"),
+            ]
+        );
+        assert!(
+            fs::read_to_string(temp_dir.path().join("tests/test_my_tool.py"))?
+                .contains("import my_tool")
+        );
+        Ok(())
+    }
+}
@@ -1,21 +1,33 @@
 use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 #[derive(Deserialize, Serialize, Default)]
 pub struct Config {
+    /// Base config (path or http(s) URL, fetched with curl) that this file is merged over
+    #[serde(default)]
+    pub extends: Option<String>,
+    #[serde(default)]
     pub uvinit: UvinitConfig,
+    #[serde(default)]
     pub cargonew: CargonewConfig,
+    #[serde(default)]
     pub tuarinew: TuarinewConfig,
+    /// Named overlays selected with `--profile`, e.g. `[profiles.strict.uvinit]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct UvinitConfig {
-    /// Directories to skip during search
+    /// Directories to skip during search, as exact names or globs like `*.egg-info`
     #[serde(default = "default_skip_dirs")]
     pub skip_dirs: Vec<String>,
-    /// Whether to add hatch-vcs to build-system.requires
+    /// Whether to add the version backend's plugin (hatch-vcs by default) to build-system.requires
     #[serde(default = "default_true")]
     pub add_hatch_vcs: bool,
     /// Whether to set dynamic versioning
@@ -30,6 +42,371 @@ pub struct UvinitConfig {
     /// Additional build system requirements
     #[serde(default)]
     pub additional_requires: Vec<String>,
+    /// Whether newly inserted tables are preceded by a blank line
+    #[serde(default = "default_true")]
+    pub blank_line_between_tables: bool,
+    /// Whether to replace an existing non-vcs tool.hatch.version.source
+    #[serde(default)]
+    pub override_version_source: bool,
+    /// What happens to the static project.version when dynamic versioning is enabled
+    #[serde(default)]
+    pub version_key_action: VersionKeyAction,
+    /// Build plugin that derives the version from git; Poetry projects always
+    /// use poetry-dynamic-versioning
+    #[serde(default)]
+    pub version_backend: VersionBackend,
+    /// tool.hatch.version.fallback-version, used when git has no version to
+    /// offer, e.g. in shallow CI clones
+    #[serde(default)]
+    pub hatch_vcs_fallback_version: Option<String>,
+    /// setuptools-scm options written to tool.hatch.version.raw-options, e.g.
+    /// local_scheme or version_scheme
+    #[serde(default)]
+    pub hatch_vcs_raw_options: toml::Table,
+    /// Whether hatch-vcs also writes the version to `<package>/_version.py`,
+    /// imported as `__version__` by the package's `__init__.py`
+    #[serde(default)]
+    pub enable_version_file: bool,
+    /// Whether to back up each file modified in place so `restore` can undo it
+    #[serde(default = "default_true")]
+    pub backup_before_write: bool,
+    /// Whether to record each run's writes so `post-init undo` can revert them
+    #[serde(default = "default_true")]
+    pub journal_runs: bool,
+    /// uv command run on each modified project to catch broken edits
+    #[serde(default)]
+    pub validate: Validation,
+    /// Whether to commit the modified files after a run without failures
+    #[serde(default)]
+    pub git_commit: bool,
+    /// Message of the commit made when git_commit is enabled
+    #[serde(default = "default_git_commit_message")]
+    pub git_commit_message: String,
+    /// Whether to tag HEAD when a repository switched to git-derived versions
+    /// has no version tag yet
+    #[serde(default)]
+    pub create_version_tag: bool,
+    /// Name of that tag; `{version}` is replaced by the removed static version
+    #[serde(default = "default_version_tag_format")]
+    pub version_tag_format: String,
+    /// Version tagged when the project had no static version
+    #[serde(default = "default_initial_version")]
+    pub initial_version: String,
+    /// Whether the search skips paths excluded by .gitignore and .ignore files
+    #[serde(default = "default_true")]
+    pub respect_gitignore: bool,
+    /// Whether to walk the directory tree on multiple threads
+    #[serde(default = "default_true")]
+    pub parallel_walk: bool,
+    /// Whether to remember already configured files per repository, so
+    /// later scans skip parsing them while they are unchanged (unused while
+    /// detect_namespace_packages or another check reading beyond
+    /// pyproject.toml is on)
+    #[serde(default = "default_true")]
+    pub scan_cache: bool,
+    /// How many directory levels below the search root to look; 0 is the root only
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    /// Whether the search descends into symlinked directories
+    #[serde(default = "default_true")]
+    pub follow_symlinks: bool,
+    /// Globs (e.g. `packages/**/pyproject.toml`) relative to the search root;
+    /// when set, only matching files are processed
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs (e.g. `examples/**`) relative to the search root for files and
+    /// directories to leave out
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Whether to add tool.ruff settings
+    #[serde(default)]
+    pub enable_ruff: bool,
+    /// tool.ruff.line-length for files without one
+    #[serde(default = "default_ruff_line_length")]
+    pub ruff_line_length: i64,
+    /// tool.ruff.lint.select for files without one
+    #[serde(default = "default_ruff_select")]
+    pub ruff_select: Vec<String>,
+    /// Entries merged into tool.pytest.ini_options, e.g. testpaths, addopts,
+    /// filterwarnings or markers; arrays gain missing items, other values are
+    /// only set when absent
+    #[serde(default)]
+    pub pytest_ini_options: toml::Table,
+    /// Bandit test IDs added to tool.bandit.skips
+    #[serde(default = "default_bandit_skips")]
+    pub bandit_skips: Vec<String>,
+    /// Directories added to tool.bandit.exclude_dirs
+    #[serde(default = "default_bandit_exclude_dirs")]
+    pub bandit_exclude_dirs: Vec<String>,
+    /// Whether to add tool.mypy settings
+    #[serde(default)]
+    pub enable_mypy: bool,
+    /// Value written to tool.mypy.strict
+    #[serde(default = "default_true")]
+    pub mypy_strict: bool,
+    /// Value written to tool.mypy.python_version
+    #[serde(default = "default_mypy_python_version")]
+    pub mypy_python_version: String,
+    /// Value written to tool.mypy.ignore_missing_imports
+    #[serde(default)]
+    pub mypy_ignore_missing_imports: bool,
+    /// Type checker to configure alongside the other tool tables
+    #[serde(default)]
+    pub type_checker: TypeChecker,
+    /// Strictness written for the type checker
+    #[serde(default)]
+    pub type_checking_mode: TypeCheckingMode,
+    /// Directories the type checker checks; empty means `src` and `tests`,
+    /// where they exist
+    #[serde(default)]
+    pub type_checker_include: Vec<String>,
+    /// Directories the type checker skips
+    #[serde(default)]
+    pub type_checker_exclude: Vec<String>,
+    /// Whether to add tool.coverage.run settings
+    #[serde(default)]
+    pub enable_coverage: bool,
+    /// Value written to tool.coverage.run.source; empty means the package named
+    /// after project.name
+    #[serde(default)]
+    pub coverage_source: Vec<String>,
+    /// Value written to tool.coverage.run.omit
+    #[serde(default = "default_coverage_omit")]
+    pub coverage_omit: Vec<String>,
+    /// Value written to tool.coverage.report.fail_under
+    #[serde(default = "default_coverage_fail_under")]
+    pub coverage_fail_under: i64,
+    /// project.requires-python written when a file has none, e.g. `>=3.11`
+    #[serde(default)]
+    pub requires_python: Option<String>,
+    /// Whether to raise an existing requires-python lower bound that is below
+    /// the one in requires_python
+    #[serde(default)]
+    pub tighten_requires_python: bool,
+    /// Whether to write `.python-version` next to each modified project
+    #[serde(default)]
+    pub enable_python_version_file: bool,
+    /// Version written to `.python-version`, e.g. `3.12`; without it the
+    /// lower bound of the project's requires-python is used
+    #[serde(default)]
+    pub python_version: Option<String>,
+    /// Whether to fill in missing project.urls (from the git remote),
+    /// project.license and project.classifiers
+    #[serde(default)]
+    pub enable_metadata: bool,
+    /// SPDX expression written to project.license, e.g. `MIT`
+    #[serde(default)]
+    pub license: Option<String>,
+    /// Trove classifiers written to project.classifiers
+    #[serde(default)]
+    pub classifiers: Vec<String>,
+    /// Whether to fill in a missing project.authors from git's user.name and user.email
+    #[serde(default)]
+    pub fill_authors: bool,
+    /// Whether to write a .pre-commit-config.yaml next to each modified pyproject.toml
+    #[serde(default)]
+    pub enable_precommit: bool,
+    /// Hook ids in that file, e.g. `ruff`, `ruff-format`, `uv-lock`, `check-toml`
+    #[serde(default = "default_precommit_hooks")]
+    pub precommit_hooks: Vec<String>,
+    /// Whether to run `pre-commit install` after writing it
+    #[serde(default)]
+    pub precommit_install: bool,
+    /// Whether to configure commitizen in `[tool.commitizen]` to bump versions
+    /// from git tags, adding its commit-msg hook to the pre-commit config
+    #[serde(default)]
+    pub enable_commitizen: bool,
+    /// Dependency update bot to configure at the root of each repository
+    /// with a modified project
+    #[serde(default)]
+    pub dependency_updates: DependencyUpdates,
+    /// Whether to write a GitHub Actions workflow that publishes to PyPI on version tags
+    #[serde(default)]
+    pub enable_publish_workflow: bool,
+    /// Template replacing the built-in workflow; `{{name}}`, `{{tag_pattern}}`,
+    /// `{{python_versions}}` and `{{working_directory}}` are filled in
+    #[serde(default)]
+    pub publish_workflow_template: Option<PathBuf>,
+    /// Whether to write a Dockerfile next to each modified project
+    #[serde(default)]
+    pub enable_dockerfile: bool,
+    /// Template replacing the built-in Dockerfile; `{{name}}`, `{{module}}`,
+    /// `{{python_version}}` and `{{command}}` are filled in
+    #[serde(default)]
+    pub dockerfile_template: Option<PathBuf>,
+    /// Whether to write `.devcontainer/devcontainer.json` next to each modified project
+    #[serde(default)]
+    pub enable_devcontainer: bool,
+    /// Template replacing the built-in devcontainer.json; `{{name}}`,
+    /// `{{python_version}}` and `{{extensions}}` are filled in
+    #[serde(default)]
+    pub devcontainer_template: Option<PathBuf>,
+    /// VS Code extensions the dev container recommends
+    #[serde(default = "default_devcontainer_extensions")]
+    pub devcontainer_extensions: Vec<String>,
+    /// `[tool.hatch.envs.<name>]` tables for hatchling projects, as env name ->
+    /// settings (dependencies, scripts, ...); keys a project already sets are kept
+    #[serde(default)]
+    pub hatch_envs: BTreeMap<String, toml::Table>,
+    /// Whether to add a py.typed marker to the package, make sure wheels ship
+    /// it and classify the project as `Typing :: Typed`
+    #[serde(default)]
+    pub enable_py_typed: bool,
+    /// Whether to add Trove classifiers derived from requires-python,
+    /// project.license and a shipped py.typed marker
+    #[serde(default)]
+    pub derive_classifiers: bool,
+    /// Whether to list namespace packages, and packages not named after the
+    /// project, in `[tool.hatch.build.targets.wheel] packages` so hatchling
+    /// builds find them
+    #[serde(default = "default_true")]
+    pub detect_namespace_packages: bool,
+    /// Whether to move a flat-layout package into `src/<package>`
+    #[serde(default)]
+    pub migrate_src_layout: bool,
+    /// `[tool.uv]` settings such as `package`, `default-groups` and `sources`;
+    /// keys a project already sets are kept
+    #[serde(default)]
+    pub uv_settings: toml::Table,
+    /// Whether to move project.optional-dependencies.dev and
+    /// tool.uv.dev-dependencies into the PEP 735 `[dependency-groups]` table
+    #[serde(default)]
+    pub enable_dependency_groups: bool,
+    /// Whether to add `[tool.uv.sources] <name> = { workspace = true }` for
+    /// project.dependencies that are other members of the same uv workspace
+    #[serde(default)]
+    pub enable_workspace_sources: bool,
+    /// Whether to merge requirements.txt into project.dependencies and
+    /// requirements-dev.txt into `[dependency-groups] dev`
+    #[serde(default)]
+    pub import_requirements: bool,
+    /// Whether to fill `[project]` from legacy setup.cfg and setup.py files,
+    /// creating pyproject.toml where only those exist
+    #[serde(default)]
+    pub migrate_setup: bool,
+    /// `[project.scripts]` entries, as script name -> object reference, where
+    /// `{{name}}` and `{{module}}` stand for the project and package names, e.g.
+    /// `"{{name}}" = "{{module}}.__main__:main"`; scripts a project already has
+    /// are kept, and missing `__main__.py` targets get a stub
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+    /// `[[tool.mypy.overrides]]` entries, as module pattern -> settings
+    #[serde(default)]
+    pub mypy_overrides: BTreeMap<String, toml::Table>,
+    /// `[[uvinit.patches]]` rules, applied in order after the built-in edits
+    #[serde(default)]
+    pub patches: Vec<Patch>,
+}
+
+/// A config-defined edit of one pyproject.toml key
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Patch {
+    /// Dotted path from the document root, e.g. `tool.ruff.lint.ignore`
+    pub key: String,
+    pub value: toml::Value,
+    #[serde(default)]
+    pub strategy: PatchStrategy,
+}
+
+/// How a patch combines its value with what the file already has
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PatchStrategy {
+    /// Set the key only when it is absent; tables are filled key by key
+    #[default]
+    SetIfMissing,
+    /// Add the array items (or the single value) the existing array lacks
+    AppendUnique,
+    /// Replace whatever the key holds
+    Overwrite,
+}
+
+/// What to do with a static `project.version` once it becomes dynamic
+#[derive(Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionKeyAction {
+    /// Drop the key
+    #[default]
+    Remove,
+    /// Leave the key in place
+    Keep,
+    /// Drop the key and record it as tool.setuptools_scm.fallback_version
+    MoveToScm,
+}
+
+/// How uvinit checks a modified project with uv
+#[derive(Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Validation {
+    /// Don't validate
+    #[default]
+    None,
+    /// `uv lock --check`: the lockfile still matches the project
+    Lock,
+    /// `uv build --no-build-isolation -q`: the project still builds
+    Build,
+}
+
+/// Which bot keeps a repository's dependencies up to date
+#[derive(Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependencyUpdates {
+    /// Don't write a config
+    #[default]
+    None,
+    /// `.github/dependabot.yml` for pip and GitHub Actions
+    Dependabot,
+    /// `renovate.json` for pyproject.toml, uv.lock and GitHub Actions
+    Renovate,
+}
+
+/// Which type checker gets a `[tool.*]` table
+#[derive(Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TypeChecker {
+    /// Don't configure one
+    #[default]
+    None,
+    /// `[tool.pyright]`
+    Pyright,
+    /// `[tool.ty.environment]`, `[tool.ty.src]` and `[tool.ty.terminal]`
+    Ty,
+}
+
+/// Strictness written for the type checker, named after pyright's
+/// typeCheckingMode; ty only distinguishes strict, as error-on-warning
+#[derive(Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TypeCheckingMode {
+    Off,
+    Basic,
+    #[default]
+    Standard,
+    Strict,
+}
+
+impl TypeCheckingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TypeCheckingMode::Off => "off",
+            TypeCheckingMode::Basic => "basic",
+            TypeCheckingMode::Standard => "standard",
+            TypeCheckingMode::Strict => "strict",
+        }
+    }
+}
+
+/// Which build backend and plugin derive the version from git
+#[derive(Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionBackend {
+    /// hatchling with hatch-vcs and tool.hatch.version
+    #[default]
+    HatchVcs,
+    /// setuptools with setuptools-scm and tool.setuptools_scm
+    SetuptoolsScm,
+    /// pdm-backend with tool.pdm.version
+    Pdm,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -40,6 +417,15 @@ pub struct CargonewConfig {
     /// Whether to initialize git repository
     #[serde(default = "default_true")]
     pub init_git: bool,
+    /// .gitignore template written into new projects, e.g. `rust` or `rust+vscode`
+    #[serde(default = "default_gitignore_template")]
+    pub gitignore_template: String,
+    /// Dev-dependencies added to new projects, as crate name -> version requirement
+    #[serde(default)]
+    pub dev_dependencies: BTreeMap<String, String>,
+    /// Settings written to `[profile.release]` of new projects
+    #[serde(default = "default_release_profile")]
+    pub release_profile: toml::Table,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -61,6 +447,79 @@ impl Default for UvinitConfig {
             enable_pytest_asyncio: true,
             enable_bandit: true,
             additional_requires: Vec::new(),
+            blank_line_between_tables: true,
+            override_version_source: false,
+            version_key_action: VersionKeyAction::Remove,
+            version_backend: VersionBackend::HatchVcs,
+            hatch_vcs_fallback_version: None,
+            hatch_vcs_raw_options: toml::Table::new(),
+            enable_version_file: false,
+            backup_before_write: true,
+            journal_runs: true,
+            validate: Validation::None,
+            git_commit: false,
+            git_commit_message: default_git_commit_message(),
+            create_version_tag: false,
+            version_tag_format: default_version_tag_format(),
+            initial_version: default_initial_version(),
+            respect_gitignore: true,
+            parallel_walk: true,
+            scan_cache: true,
+            max_depth: None,
+            follow_symlinks: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            enable_ruff: false,
+            ruff_line_length: default_ruff_line_length(),
+            ruff_select: default_ruff_select(),
+            pytest_ini_options: toml::Table::new(),
+            bandit_skips: default_bandit_skips(),
+            bandit_exclude_dirs: default_bandit_exclude_dirs(),
+            enable_mypy: false,
+            mypy_strict: true,
+            mypy_python_version: default_mypy_python_version(),
+            mypy_ignore_missing_imports: false,
+            type_checker: TypeChecker::None,
+            type_checking_mode: TypeCheckingMode::Standard,
+            type_checker_include: Vec::new(),
+            type_checker_exclude: Vec::new(),
+            enable_coverage: false,
+            coverage_source: Vec::new(),
+            coverage_omit: default_coverage_omit(),
+            coverage_fail_under: default_coverage_fail_under(),
+            requires_python: None,
+            tighten_requires_python: false,
+            enable_python_version_file: false,
+            python_version: None,
+            enable_metadata: false,
+            license: None,
+            classifiers: Vec::new(),
+            fill_authors: false,
+            enable_precommit: false,
+            precommit_hooks: default_precommit_hooks(),
+            precommit_install: false,
+            enable_commitizen: false,
+            dependency_updates: DependencyUpdates::None,
+            enable_publish_workflow: false,
+            publish_workflow_template: None,
+            enable_dockerfile: false,
+            dockerfile_template: None,
+            enable_devcontainer: false,
+            devcontainer_template: None,
+            devcontainer_extensions: default_devcontainer_extensions(),
+            hatch_envs: BTreeMap::new(),
+            enable_py_typed: false,
+            derive_classifiers: false,
+            detect_namespace_packages: true,
+            migrate_src_layout: false,
+            uv_settings: toml::Table::new(),
+            enable_dependency_groups: false,
+            enable_workspace_sources: false,
+            import_requirements: false,
+            migrate_setup: false,
+            scripts: BTreeMap::new(),
+            mypy_overrides: BTreeMap::new(),
+            patches: Vec::new(),
         }
     }
 }
@@ -70,6 +529,9 @@ impl Default for CargonewConfig {
         Self {
             default_template: default_cargo_template(),
             init_git: true,
+            gitignore_template: default_gitignore_template(),
+            dev_dependencies: BTreeMap::new(),
+            release_profile: default_release_profile(),
         }
     }
 }
@@ -102,50 +564,441 @@ fn default_skip_dirs() -> Vec<String> {
 fn default_true() -> bool {
     true
 }
+fn default_bandit_skips() -> Vec<String> {
+    vec!["B101".to_string()]
+}
+fn default_bandit_exclude_dirs() -> Vec<String> {
+    vec![".venv".to_string(), "venv".to_string(), "tests".to_string()]
+}
+fn default_git_commit_message() -> String {
+    "Derive the package version from git tags".to_string()
+}
+
+fn default_version_tag_format() -> String {
+    "v{version}".to_string()
+}
+
+fn default_initial_version() -> String {
+    "0.1.0".to_string()
+}
+
+fn default_precommit_hooks() -> Vec<String> {
+    ["ruff", "ruff-format", "uv-lock"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_devcontainer_extensions() -> Vec<String> {
+    ["charliermarsh.ruff", "ms-pyright.pyright"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_ruff_line_length() -> i64 {
+    88
+}
+fn default_ruff_select() -> Vec<String> {
+    vec!["E".to_string(), "F".to_string(), "I".to_string()]
+}
+fn default_mypy_python_version() -> String {
+    "3.12".to_string()
+}
+fn default_coverage_fail_under() -> i64 {
+    80
+}
+fn default_coverage_omit() -> Vec<String> {
+    vec!["tests/*".to_string()]
+}
 fn default_cargo_template() -> String {
     "bin".to_string()
 }
+fn default_gitignore_template() -> String {
+    "rust".to_string()
+}
+fn default_release_profile() -> toml::Table {
+    let mut profile = toml::Table::new();
+    profile.insert("lto".to_string(), toml::Value::Boolean(true));
+    profile.insert("codegen-units".to_string(), toml::Value::Integer(1));
+    profile.insert("strip".to_string(), toml::Value::Boolean(true));
+    profile
+}
 fn default_tauri_frontend() -> String {
     "vanilla".to_string()
 }
 
-pub fn get_config_path() -> Result<PathBuf> {
-    let home_dir =
-        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+/// What to write when the config file doesn't exist yet
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InitConfig {
+    /// Every section with its defaults
+    #[default]
+    Full,
+    /// Only the section for the command being run
+    Command,
+    /// An empty file; built-in defaults apply
+    Empty,
+}
+
+/// Environment variable naming the config file when `--config` isn't given
+pub const CONFIG_ENV_VAR: &str = "POST_INIT_CONFIG";
+
+/// Project-local config file picked up from the current directory
+const LOCAL_CONFIG_FILE_NAME: &str = "post-init.toml";
+
+/// Seconds a remote base config may take to download before loading fails
+const FETCH_TIMEOUT_SECS: &str = "30";
+
+#[derive(Args, Debug, Default)]
+pub struct ConfigArgs {
+    /// Config file to use instead of ./post-init.toml or ~/.config/post-init.toml
+    /// (also POST_INIT_CONFIG)
+    #[arg(long = "config", global = true, value_name = "PATH")]
+    pub config_path: Option<PathBuf>,
+    /// Fail instead of warning when the config file contains unknown keys
+    #[arg(long, global = true)]
+    pub config_key_check: bool,
+    /// What to write when creating the config file on first run
+    #[arg(long, global = true, value_enum, default_value_t = InitConfig::Full)]
+    pub init_config: InitConfig,
+    /// Merge the named `[profiles.<NAME>]` table over the rest of the config
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+}
+
+/// Per-run overrides of `[uvinit]` feature toggles
+///
+/// Unset flags keep the config file value, which in turn falls back to the built-in default.
+#[derive(Args, Debug, Default, Clone)]
+#[command(next_help_heading = "Feature overrides (take precedence over the config file)")]
+pub struct UvinitFeatureArgs {
+    /// Don't add hatch-vcs to build-system.requires
+    #[arg(long)]
+    pub no_hatch_vcs: bool,
+    /// Don't switch project.version to dynamic versioning
+    #[arg(long)]
+    pub no_dynamic_version: bool,
+    /// Don't set tool.pytest.ini_options.asyncio_mode
+    #[arg(long)]
+    pub no_pytest_asyncio: bool,
+    /// Don't add tool.bandit settings
+    #[arg(long)]
+    pub no_bandit: bool,
+    /// Don't list namespace packages in tool.hatch.build.targets.wheel.packages
+    #[arg(long)]
+    pub no_namespace_packages: bool,
+    /// Add Python version, license and typing classifiers derived from the
+    /// project's metadata
+    #[arg(long)]
+    pub derive_classifiers: bool,
+    /// Extra build requirement, appended to additional_requires (repeatable)
+    #[arg(long = "add-require", value_name = "PKG")]
+    pub add_requires: Vec<String>,
+}
+
+impl UvinitFeatureArgs {
+    pub fn apply(&self, config: &mut UvinitConfig) {
+        let overrides = [
+            (self.no_hatch_vcs, &mut config.add_hatch_vcs),
+            (self.no_dynamic_version, &mut config.enable_dynamic_version),
+            (self.no_pytest_asyncio, &mut config.enable_pytest_asyncio),
+            (self.no_bandit, &mut config.enable_bandit),
+            (
+                self.no_namespace_packages,
+                &mut config.detect_namespace_packages,
+            ),
+        ];
+        for (disabled, enabled) in overrides {
+            if disabled {
+                *enabled = false;
+            }
+        }
+        if self.derive_classifiers {
+            config.derive_classifiers = true;
+        }
+        for req in &self.add_requires {
+            if !config.additional_requires.contains(req) {
+                config.additional_requires.push(req.clone());
+            }
+        }
+    }
+}
+
+/// Config file for this run: `--config`, then `POST_INIT_CONFIG`, then
+/// `./post-init.toml` if present, then `~/.config/post-init.toml`
+pub fn get_config_path(args: &ConfigArgs) -> Result<PathBuf> {
+    let local = Path::new(LOCAL_CONFIG_FILE_NAME);
+    resolve_config_path(
+        args.config_path.as_deref(),
+        std::env::var_os(CONFIG_ENV_VAR).map(PathBuf::from),
+        local.is_file().then(|| local.to_path_buf()),
+        dirs::home_dir(),
+    )
+}
+
+fn resolve_config_path(
+    explicit: Option<&Path>,
+    from_env: Option<PathBuf>,
+    local: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Result<PathBuf> {
+    if let Some(path) = explicit {
+        return Ok(path.to_path_buf());
+    }
+    if let Some(path) = from_env.filter(|p| !p.as_os_str().is_empty()) {
+        return Ok(path);
+    }
+    if let Some(path) = local {
+        return Ok(path);
+    }
 
+    let home_dir = home_dir.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Could not find a home directory for ~/.config/post-init.toml; \
+             pass --config <PATH> or set {CONFIG_ENV_VAR}"
+        )
+    })?;
     Ok(home_dir.join(".config").join("post-init.toml"))
 }
 
-pub fn load_config() -> Result<Config> {
-    let config_path = get_config_path()?;
+/// Load the config, creating it on first run
+///
+/// `section` names the config section used by the running command, if any.
+pub fn load_config(args: &ConfigArgs, section: Option<&str>) -> Result<Config> {
+    let config_path = get_config_path(args)?;
 
     if !config_path.exists() {
+        // The default config has no profiles, so fail before writing anything
+        if let Some(profile) = &args.profile {
+            return Err(anyhow::anyhow!(
+                "Unknown profile '{profile}'; {} does not exist yet, so it defines no profiles",
+                config_path.display()
+            ));
+        }
         // Create default config if it doesn't exist
         let default_config = Config::default();
-        save_config(&default_config)?;
+        let content = initial_config_content(&default_config, args.init_config, section)?;
+        write_config_file(&config_path, &content)?;
         return Ok(default_config);
     }
 
     let content = fs::read_to_string(&config_path)
         .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
 
-    let config: Config = toml::from_str(&content).with_context(|| "Failed to parse config file")?;
+    let raw: toml::Value =
+        toml::from_str(&content).with_context(|| "Failed to parse config file")?;
+    let mut chain = vec![config_location(&config_path)];
+    let mut raw = resolve_extends(raw, config_path.parent(), &mut chain)?;
+    if let Some(profile) = &args.profile {
+        apply_profile(&mut raw, profile)?;
+    }
+    let config: Config = raw
+        .clone()
+        .try_into()
+        .with_context(|| "Failed to parse config file")?;
+
+    // Unknown keys are silently dropped by serde, so compare against a round-trip
+    let known = toml::Value::try_from(&config).with_context(|| "Failed to serialize config")?;
+    let unknown = unknown_keys(&raw, &known);
+    if !unknown.is_empty() {
+        let message = format!(
+            "Unknown key(s) in config file {}: {}",
+            config_path.display(),
+            unknown.join(", ")
+        );
+        if args.config_key_check {
+            return Err(anyhow::anyhow!(message));
+        }
+        eprintln!("⚠️  {message}");
+        crate::annotations::warning(Some(&config_path), &message);
+    }
 
     Ok(config)
 }
 
-pub fn save_config(config: &Config) -> Result<()> {
-    let config_path = get_config_path()?;
+fn config_location(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// Merge `raw` over the config named by its `extends` key, following the chain
+///
+/// `base_dir` is where relative `extends` paths are resolved from; `chain`
+/// holds every location visited so far and is used to detect cycles.
+fn resolve_extends(
+    raw: toml::Value,
+    base_dir: Option<&Path>,
+    chain: &mut Vec<String>,
+) -> Result<toml::Value> {
+    let Some(extends) = raw.get("extends").and_then(|v| v.as_str()) else {
+        return Ok(raw);
+    };
+
+    let (location, content, next_dir) = if is_url(extends) {
+        let output = Command::new("curl")
+            .args(["-fsSL", "--max-time", FETCH_TIMEOUT_SECS, extends])
+            .output()
+            .with_context(|| {
+                format!("Failed to run curl for base config (is curl installed?): {extends}")
+            })?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "Failed to fetch base config {}: {}",
+                extends,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let content = String::from_utf8(output.stdout)
+            .with_context(|| format!("Base config is not valid UTF-8: {extends}"))?;
+        (extends.to_string(), content, None)
+    } else {
+        let path = match base_dir {
+            Some(dir) => dir.join(extends),
+            None => PathBuf::from(extends),
+        };
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read base config: {}", path.display()))?;
+        let next_dir = path.parent().map(Path::to_path_buf);
+        (config_location(&path), content, next_dir)
+    };
+
+    if chain.contains(&location) {
+        chain.push(location);
+        return Err(anyhow::anyhow!(
+            "Cycle in config extends chain: {}",
+            chain.join(" -> ")
+        ));
+    }
+    chain.push(location.clone());
+
+    let base: toml::Value = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse base config: {location}"))?;
+    if is_url(&location) {
+        check_remote_extends(&base, &location)?;
+    }
+    let mut merged = resolve_extends(base, next_dir.as_deref(), chain)?;
+    deep_merge(&mut merged, raw);
+
+    Ok(merged)
+}
+
+/// A remote base config has no directory to resolve a relative `extends`
+/// against, so it may only extend another URL
+fn check_remote_extends(base: &toml::Value, url: &str) -> Result<()> {
+    match base.get("extends").and_then(|v| v.as_str()) {
+        Some(extends) if !is_url(extends) => Err(anyhow::anyhow!(
+            "Base config {url} extends the relative path '{extends}'; remote configs may only extend URLs"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Merge `[profiles.<name>]` over the rest of `raw`, one field at a time
+fn apply_profile(raw: &mut toml::Value, name: &str) -> Result<()> {
+    let profiles = raw.get("profiles").and_then(|p| p.as_table());
+    let Some(profile) = profiles.and_then(|p| p.get(name)).cloned() else {
+        let available: Vec<&str> = profiles
+            .map(|p| p.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        return Err(anyhow::anyhow!(
+            "Unknown profile '{name}'; available profiles: {}",
+            if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            }
+        ));
+    };
+
+    deep_merge(raw, profile);
+    Ok(())
+}
+
+/// Merge `overlay` into `base`: tables merge key by key, anything else replaces
+fn deep_merge(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base_table), toml::Value::Table(overlay_table)) => {
+            for (key, value) in overlay_table {
+                match base_table.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_table.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Dotted paths of keys present in `raw` but not in `known`
+fn unknown_keys(raw: &toml::Value, known: &toml::Value) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_unknown_keys(raw, known, "", &mut keys);
+    keys
+}
+
+fn collect_unknown_keys(
+    raw: &toml::Value,
+    known: &toml::Value,
+    prefix: &str,
+    keys: &mut Vec<String>,
+) {
+    let (Some(raw_table), Some(known_table)) = (raw.as_table(), known.as_table()) else {
+        return;
+    };
+
+    for (key, value) in raw_table {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match known_table.get(key) {
+            Some(known_value) => collect_unknown_keys(value, known_value, &path, keys),
+            None => keys.push(path),
+        }
+    }
+}
+
+fn initial_config_content(
+    config: &Config,
+    mode: InitConfig,
+    section: Option<&str>,
+) -> Result<String> {
+    let full = toml::to_string_pretty(config).with_context(|| "Failed to serialize config")?;
+
+    match (mode, section) {
+        (InitConfig::Full, _) | (InitConfig::Command, None) => Ok(full),
+        (InitConfig::Command, Some(section)) => {
+            // Edit the serialized document so field order matches the full config
+            let mut doc = full
+                .parse::<toml_edit::DocumentMut>()
+                .with_context(|| "Failed to serialize config")?;
+            doc.retain(|key, _| key == section);
+            Ok(doc.to_string())
+        }
+        (InitConfig::Empty, _) => Ok(String::new()),
+    }
+}
+
+/// Overwrite the config file with `content`
+pub fn save_config(args: &ConfigArgs, content: &str) -> Result<()> {
+    write_config_file(&get_config_path(args)?, content)
+}
 
+fn write_config_file(config_path: &Path, content: &str) -> Result<()> {
     // Create .config directory if it doesn't exist
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
     }
 
-    let content = toml::to_string_pretty(config).with_context(|| "Failed to serialize config")?;
-
-    fs::write(&config_path, content)
+    fs::write(config_path, content)
         .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
 
     Ok(())
@@ -163,4 +1016,255 @@ mod tests {
         assert!(!config.uvinit.skip_dirs.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_initial_config_content() -> Result<()> {
+        let config = Config::default();
+
+        let full = initial_config_content(&config, InitConfig::Full, Some("uvinit"))?;
+        assert!(full.contains("[uvinit]") && full.contains("[cargonew]"));
+
+        let command = initial_config_content(&config, InitConfig::Command, Some("uvinit"))?;
+        assert!(command.contains("[uvinit]"));
+        assert!(!command.contains("[cargonew]") && !command.contains("[tuarinew]"));
+
+        let empty = initial_config_content(&config, InitConfig::Empty, Some("uvinit"))?;
+        assert!(empty.trim().is_empty());
+
+        // Partial files still load with defaults for the missing sections
+        let loaded: Config = toml::from_str(&command)?;
+        assert_eq!(loaded.cargonew.default_template, "bin");
+        let loaded: Config = toml::from_str(&empty)?;
+        assert!(loaded.uvinit.add_hatch_vcs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_extends() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let root = temp_dir.path();
+
+        fs::write(
+            root.join("org.toml"),
+            r#"
+[uvinit]
+enable_bandit = false
+additional_requires = ["org-plugin"]
+"#,
+        )?;
+        fs::create_dir_all(root.join("team"))?;
+        fs::write(
+            root.join("team").join("team.toml"),
+            r#"
+extends = "../org.toml"
+
+[uvinit]
+enable_pytest_asyncio = false
+"#,
+        )?;
+
+        let local: toml::Value = toml::from_str(
+            r#"
+extends = "team/team.toml"
+
+[uvinit]
+enable_bandit = true
+"#,
+        )?;
+
+        let mut chain = Vec::new();
+        let merged = resolve_extends(local, Some(root), &mut chain)?;
+        let config: Config = merged.try_into()?;
+
+        assert!(config.uvinit.enable_bandit);
+        assert!(!config.uvinit.enable_pytest_asyncio);
+        assert_eq!(config.uvinit.additional_requires, vec!["org-plugin"]);
+        assert_eq!(chain.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_extends_cycle() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let root = temp_dir.path();
+
+        fs::write(root.join("a.toml"), "extends = \"b.toml\"\n")?;
+        fs::write(root.join("b.toml"), "extends = \"a.toml\"\n")?;
+
+        let local: toml::Value = toml::from_str("extends = \"a.toml\"\n")?;
+        let err = resolve_extends(local, Some(root), &mut Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("Cycle in config extends chain"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_remote_extends() -> Result<()> {
+        let url = "https://example.com/base.toml";
+        let remote: toml::Value = toml::from_str("extends = \"https://example.com/org.toml\"\n")?;
+        assert!(check_remote_extends(&remote, url).is_ok());
+        let relative: toml::Value = toml::from_str("extends = \"org.toml\"\n")?;
+        let err = check_remote_extends(&relative, url).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("remote configs may only extend URLs")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_uvinit_feature_args_override_config() {
+        let mut config = UvinitConfig {
+            enable_pytest_asyncio: false,
+            additional_requires: vec!["setuptools-scm".to_string()],
+            ..Default::default()
+        };
+        let args = UvinitFeatureArgs {
+            no_bandit: true,
+            derive_classifiers: true,
+            add_requires: vec!["setuptools-scm".to_string(), "wheel".to_string()],
+            ..Default::default()
+        };
+
+        args.apply(&mut config);
+
+        assert!(!config.enable_bandit);
+        assert!(config.derive_classifiers);
+        // Unset flags leave config values alone rather than resetting them
+        assert!(config.add_hatch_vcs);
+        assert!(!config.enable_pytest_asyncio);
+        assert_eq!(config.additional_requires, vec!["setuptools-scm", "wheel"]);
+    }
+
+    #[test]
+    fn test_resolve_config_path_precedence() -> Result<()> {
+        let explicit = Path::new("explicit.toml");
+        let env = || Some(PathBuf::from("env.toml"));
+        let local = || Some(PathBuf::from("post-init.toml"));
+        let home = || Some(PathBuf::from("/home/me"));
+
+        assert_eq!(
+            resolve_config_path(Some(explicit), env(), local(), home())?,
+            explicit
+        );
+        assert_eq!(
+            resolve_config_path(None, env(), local(), home())?,
+            PathBuf::from("env.toml")
+        );
+        assert_eq!(
+            resolve_config_path(None, Some(PathBuf::new()), local(), home())?,
+            PathBuf::from("post-init.toml")
+        );
+        assert_eq!(
+            resolve_config_path(None, None, None, home())?,
+            PathBuf::from("/home/me/.config/post-init.toml")
+        );
+
+        let error = resolve_config_path(None, None, None, None).unwrap_err();
+        assert!(error.to_string().contains("--config"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bandit_settings_round_trip() -> Result<()> {
+        let defaults = UvinitConfig::default();
+        assert_eq!(defaults.bandit_skips, vec!["B101"]);
+        assert_eq!(defaults.bandit_exclude_dirs, vec![".venv", "venv", "tests"]);
+
+        let config = Config {
+            uvinit: UvinitConfig {
+                enable_bandit: false,
+                bandit_skips: vec!["B101".to_string(), "B603".to_string()],
+                bandit_exclude_dirs: vec!["build".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let content = toml::to_string_pretty(&config)?;
+        assert!(content.contains("bandit_skips"));
+        assert!(content.contains("enable_pytest_asyncio"));
+
+        let loaded: Config = toml::from_str(&content)?;
+        assert!(!loaded.uvinit.enable_bandit);
+        assert_eq!(loaded.uvinit.bandit_skips, config.uvinit.bandit_skips);
+        assert_eq!(
+            loaded.uvinit.bandit_exclude_dirs,
+            config.uvinit.bandit_exclude_dirs
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_profile() -> Result<()> {
+        let mut raw: toml::Value = toml::from_str(
+            r#"
+[uvinit]
+skip_dirs = ["vendor"]
+enable_bandit = true
+
+[profiles.loose.uvinit]
+enable_bandit = false
+"#,
+        )?;
+
+        apply_profile(&mut raw, "loose")?;
+        let config: Config = raw.clone().try_into()?;
+        assert!(!config.uvinit.enable_bandit);
+        // Fields the profile doesn't set keep the base value
+        assert_eq!(config.uvinit.skip_dirs, vec!["vendor"]);
+
+        let error = apply_profile(&mut raw, "strict").unwrap_err();
+        assert!(error.to_string().contains("available profiles: loose"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_profile_on_first_run() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let config_path = temp_dir.path().join("post-init.toml");
+        let mut args = ConfigArgs {
+            config_path: Some(config_path.clone()),
+            profile: Some("strict".to_string()),
+            ..Default::default()
+        };
+
+        // A failing load leaves nothing behind
+        let Err(error) = load_config(&args, None) else {
+            panic!("an unknown profile should fail");
+        };
+        assert!(error.to_string().contains("Unknown profile 'strict'"));
+        assert!(!config_path.exists());
+
+        args.profile = None;
+        load_config(&args, None)?;
+        assert!(config_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_keys() -> Result<()> {
+        let raw: toml::Value = toml::from_str(
+            r#"
+typo_section = 1
+
+[uvinit]
+enabl_bandit = true
+enable_bandit = false
+"#,
+        )?;
+        let config: Config = raw.clone().try_into()?;
+        let known = toml::Value::try_from(&config)?;
+
+        assert_eq!(
+            unknown_keys(&raw, &known),
+            vec![
+                "typo_section".to_string(),
+                "uvinit.enabl_bandit".to_string()
+            ]
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use std::borrow::Cow;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::output;
+
+/// What to do when a file we want to write already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Leave existing files untouched
+    #[default]
+    SkipExisting,
+    /// Always overwrite existing files
+    Force,
+    /// Ask before overwriting each existing file
+    Prompt,
+}
+
+/// Outcome of a `write_file` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+    Created,
+    Overwritten,
+    Skipped,
+}
+
+#[derive(Args, Debug, Default)]
+#[group(multiple = false)]
+pub struct OverwriteArgs {
+    /// Overwrite files that already exist
+    #[arg(long, global = true, visible_alias = "yes-to-overwrites")]
+    pub force: bool,
+    /// Leave files that already exist untouched (default)
+    #[arg(long, global = true)]
+    pub skip_existing: bool,
+    /// Ask before overwriting each file that already exists
+    #[arg(long, global = true)]
+    pub prompt_overwrite: bool,
+}
+
+impl OverwriteArgs {
+    pub fn policy(&self) -> OverwritePolicy {
+        if self.force {
+            OverwritePolicy::Force
+        } else if self.prompt_overwrite {
+            OverwritePolicy::Prompt
+        } else {
+            OverwritePolicy::SkipExisting
+        }
+    }
+}
+
+/// Write `content` to `path`, honoring `policy` if the file already exists.
+pub fn write_file<P: AsRef<Path>>(
+    path: P,
+    content: &str,
+    policy: OverwritePolicy,
+) -> Result<WriteOutcome> {
+    let path = path.as_ref();
+
+    let outcome = if path.exists() {
+        let overwrite = match policy {
+            OverwritePolicy::SkipExisting => false,
+            OverwritePolicy::Force => true,
+            OverwritePolicy::Prompt => confirm_overwrite(path)?,
+        };
+        if !overwrite {
+            return Ok(WriteOutcome::Skipped);
+        }
+        WriteOutcome::Overwritten
+    } else {
+        WriteOutcome::Created
+    };
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let existing = fs::read_to_string(path).ok();
+    write_atomic(path, &with_line_endings(existing.as_deref(), content))?;
+
+    Ok(outcome)
+}
+
+/// A new hidden file next to `name` in `dir`, named so concurrent writers
+/// never share one
+fn create_temp(dir: &Path, name: &str) -> std::io::Result<(PathBuf, fs::File)> {
+    let mut n = 0;
+    loop {
+        let temp_path = dir.join(format!(".{name}.{}.{n}.tmp", std::process::id()));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// `content` with CRLF line endings when `existing` uses them, so rewriting a
+/// Windows file keeps its style
+pub fn with_line_endings<'a>(existing: Option<&str>, content: &'a str) -> Cow<'a, str> {
+    if existing.is_some_and(|existing| existing.contains("\r\n")) {
+        Cow::Owned(content.replace("\r\n", "\n").replace('\n', "\r\n"))
+    } else {
+        Cow::Borrowed(content)
+    }
+}
+
+/// Replace `path` with `content` through a temporary file in the same
+/// directory, so a crash leaves either the old or the new file, never a
+/// truncated one
+///
+/// An existing file keeps its permissions, and a symlink keeps pointing at
+/// its target, which is what gets replaced.
+pub fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let target = if path.is_symlink() {
+        fs::canonicalize(path)
+            .with_context(|| format!("Failed to resolve symlink: {}", path.display()))?
+    } else {
+        path.to_path_buf()
+    };
+    let permissions = fs::metadata(&target).ok().map(|meta| meta.permissions());
+
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let (temp_path, mut temp) = create_temp(dir, &name)
+        .with_context(|| format!("Failed to create a temporary file in {}", dir.display()))?;
+
+    let result = (|| -> std::io::Result<()> {
+        temp.write_all(content.as_bytes())?;
+        temp.sync_all()?;
+        if let Some(permissions) = permissions {
+            fs::set_permissions(&temp_path, permissions)?;
+        }
+        fs::rename(&temp_path, &target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("Failed to write file: {}", path.display()))
+}
+
+fn confirm_overwrite(path: &Path) -> Result<bool> {
+    Ok(output::confirm(&format!(
+        "❓ {} already exists. Overwrite?",
+        path.display()
+    ))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_file_policies() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("nested").join("LICENSE");
+
+        assert_eq!(
+            write_file(&path, "first", OverwritePolicy::SkipExisting)?,
+            WriteOutcome::Created
+        );
+        assert_eq!(
+            write_file(&path, "second", OverwritePolicy::SkipExisting)?,
+            WriteOutcome::Skipped
+        );
+        assert_eq!(fs::read_to_string(&path)?, "first");
+
+        assert_eq!(
+            write_file(&path, "third", OverwritePolicy::Force)?,
+            WriteOutcome::Overwritten
+        );
+        assert_eq!(fs::read_to_string(&path)?, "third");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_file_keeps_line_endings_and_permissions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("pyproject.toml");
+        fs::write(&path, "[project]\r\nname = \"a\"\r\n")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640))?;
+        }
+
+        write_file(
+            &path,
+            "[project]\r\nname = \"a\"\ndynamic = [\"version\"]\n",
+            OverwritePolicy::Force,
+        )?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "[project]\r\nname = \"a\"\r\ndynamic = [\"version\"]\r\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o640);
+        }
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+
+        assert_eq!(with_line_endings(Some("a\nb\n"), "c\n"), "c\n");
+        assert_eq!(with_line_endings(None, "c\n"), "c\n");
+        Ok(())
+    }
+}
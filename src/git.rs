@@ -0,0 +1,379 @@
+use anyhow::{Context, Result};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Run `git -C <dir> <args>`, logging the command and its exit status
+fn git<I, S>(dir: &Path, args: I) -> Result<Output>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let args: Vec<_> = args.into_iter().map(|a| a.as_ref().to_owned()).collect();
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(&args)
+        .output()
+        .with_context(|| "Failed to run git; is it installed?")?;
+    log::debug!(
+        "git -C {} {} exited with {}",
+        dir.display(),
+        args.iter()
+            .map(|a| a.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" "),
+        output.status
+    );
+    Ok(output)
+}
+
+/// Value of the git config `key` as seen from `dir`, if set and not empty
+pub fn config_value(dir: &Path, key: &str) -> Option<String> {
+    let output = git(dir, ["config", "--get", key]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Whether `dir` is inside a git work tree
+pub fn is_inside_work_tree(dir: &Path) -> bool {
+    git(dir, ["rev-parse", "--is-inside-work-tree"]).is_ok_and(|output| output.status.success())
+}
+
+/// Stage `file` in the repository that contains it
+pub fn add(file: &Path) -> Result<()> {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let name = file.file_name().unwrap_or(file.as_os_str());
+
+    let output = git(dir, [OsStr::new("add"), OsStr::new("--"), name])?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git add failed for {}: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `path`, or anything under it, is tracked in the repository containing it
+pub fn is_tracked(path: &Path) -> bool {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let name = path.file_name().unwrap_or(path.as_os_str());
+    git(
+        dir,
+        [
+            OsStr::new("ls-files"),
+            OsStr::new("--error-unmatch"),
+            OsStr::new("--"),
+            name,
+        ],
+    )
+    .is_ok_and(|output| output.status.success())
+}
+
+/// Commit `files`, which must already be staged, in the repository rooted at `toplevel`
+///
+/// Only those paths are committed; anything else already staged stays staged.
+pub fn commit(toplevel: &Path, files: &[&Path], message: &str) -> Result<()> {
+    let mut args = vec![
+        OsStr::new("commit"),
+        OsStr::new("--quiet"),
+        OsStr::new("-m"),
+        OsStr::new(message),
+        OsStr::new("--"),
+    ];
+    args.extend(files.iter().map(|file| file.as_os_str()));
+
+    let output = git(toplevel, args)?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git commit failed in {}: {}",
+            toplevel.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Tags in the repository containing `dir` that match the glob `pattern`
+pub fn tags(dir: &Path, pattern: &str) -> Result<Vec<String>> {
+    let output = git(dir, ["tag", "--list", pattern])?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git tag --list failed in {}: {}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Create the lightweight tag `name` on HEAD of the repository containing `dir`
+pub fn tag(dir: &Path, name: &str) -> Result<()> {
+    let output = git(dir, ["tag", name])?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git tag {name} failed in {}: {}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Root of the work tree containing `dir`
+pub fn toplevel(dir: &Path) -> Result<PathBuf> {
+    let output = git(dir, ["rev-parse", "--show-toplevel"])?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{} is not inside a git repository",
+            dir.display()
+        ));
+    }
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
+
+/// Nearest directory at or above `dir` with a `.git` entry, found without
+/// running git; good for grouping paths by repository
+pub fn work_tree_root(dir: &Path) -> Option<PathBuf> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let dir = std::fs::canonicalize(dir).ok()?;
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Files with uncommitted changes (including untracked files) in the
+/// repository containing `dir`, as absolute paths
+pub fn dirty_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let toplevel = toplevel(dir)?;
+
+    let output = git(
+        dir,
+        ["status", "--porcelain", "-z", "--untracked-files=all"],
+    )?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git status failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(
+        parse_porcelain_paths(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .map(|path| toplevel.join(path))
+            .collect(),
+    )
+}
+
+/// Paths from `git status --porcelain -z` output; for renames only the new path is kept
+fn parse_porcelain_paths(output: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut entries = output.split('\0').filter(|e| !e.is_empty());
+
+    while let Some(entry) = entries.next() {
+        let Some((status, path)) = entry.split_at_checked(3) else {
+            continue;
+        };
+        paths.push(path.to_string());
+        // Renames and copies are followed by the original path
+        if status.starts_with('R') || status.starts_with('C') {
+            entries.next();
+        }
+    }
+
+    paths
+}
+
+/// URL of the `origin` remote of the repository containing `dir`, if any
+pub fn remote_url(dir: &Path) -> Option<String> {
+    let output = git(dir, ["remote", "get-url", "origin"]).ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Browser URL of a repository from its remote URL, e.g.
+/// `git@github.com:owner/repo.git` -> `https://github.com/owner/repo`
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/').trim_end_matches(".git");
+    let (host, path) = if let Some((_, rest)) = remote.split_once("://") {
+        // ssh://git@host:2222/path and https://user@host/path
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+        let (host, path) = rest.split_once('/')?;
+        (host.split(':').next()?, path)
+    } else {
+        // scp-like git@host:path
+        let (host, path) = remote.split_once(':')?;
+        (host.rsplit('@').next()?, path)
+    };
+
+    (!host.is_empty() && !path.is_empty()).then(|| format!("https://{host}/{path}"))
+}
+
+/// Repository name from a remote URL, e.g. `git@github.com:owner/repo.git` -> `repo`
+pub fn repo_name_from_url(url: &str) -> Option<String> {
+    let last = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()?
+        .trim_end_matches(".git");
+
+    (!last.is_empty()).then(|| last.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_porcelain_paths() {
+        let output = " M pkg/pyproject.toml\0R  new/pyproject.toml\0old/pyproject.toml\0?? a b/pyproject.toml\0";
+        assert_eq!(
+            parse_porcelain_paths(output),
+            vec![
+                "pkg/pyproject.toml".to_string(),
+                "new/pyproject.toml".to_string(),
+                "a b/pyproject.toml".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_add_outside_and_inside_repo() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("pyproject.toml");
+        std::fs::write(&file, "[project]\n")?;
+
+        if !is_inside_work_tree(temp_dir.path()) {
+            assert!(add(&file).is_err());
+        }
+
+        git(temp_dir.path(), ["init", "-q"])?;
+        assert!(is_inside_work_tree(temp_dir.path()));
+        add(&file)?;
+
+        let status = git(temp_dir.path(), ["status", "--porcelain"])?;
+        assert_eq!(
+            String::from_utf8_lossy(&status.stdout).trim(),
+            "A  pyproject.toml"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_work_tree_root() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = std::fs::canonicalize(temp_dir.path())?;
+        let nested = root.join("packages").join("api");
+        std::fs::create_dir_all(&nested)?;
+        std::fs::create_dir(root.join(".git"))?;
+
+        assert_eq!(work_tree_root(&nested), Some(root.clone()));
+        assert_eq!(work_tree_root(&root), Some(root.clone()));
+        assert_eq!(work_tree_root(&root.join("missing")), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_only_given_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        git(root, ["init", "-q"])?;
+        git(root, ["config", "user.name", "Test"])?;
+        git(root, ["config", "user.email", "test@example.com"])?;
+        assert_eq!(
+            config_value(root, "user.email"),
+            Some("test@example.com".to_string())
+        );
+        assert_eq!(config_value(root, "post-init.unset"), None);
+
+        let file = root.join("pyproject.toml");
+        let other = root.join("other.txt");
+        std::fs::write(&file, "[project]\n")?;
+        std::fs::write(&other, "staged\n")?;
+        add(&file)?;
+        add(&other)?;
+
+        commit(
+            &toplevel(root)?,
+            &[file.as_path()],
+            "Enable dynamic versioning",
+        )?;
+
+        let log = git(root, ["log", "--format=%s"])?;
+        assert_eq!(
+            String::from_utf8_lossy(&log.stdout).trim(),
+            "Enable dynamic versioning"
+        );
+        let status = git(root, ["status", "--porcelain"])?;
+        assert_eq!(
+            String::from_utf8_lossy(&status.stdout).trim(),
+            "A  other.txt"
+        );
+
+        assert!(tags(root, "v*")?.is_empty());
+        tag(root, "v0.1.0")?;
+        tag(root, "docs")?;
+        assert_eq!(tags(root, "v*")?, vec!["v0.1.0".to_string()]);
+        assert!(tag(root, "v0.1.0").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_web_url() {
+        assert_eq!(
+            web_url("git@github.com:lisoboss/post-init.git"),
+            Some("https://github.com/lisoboss/post-init".to_string())
+        );
+        assert_eq!(
+            web_url("https://github.com/lisoboss/post-init/"),
+            Some("https://github.com/lisoboss/post-init".to_string())
+        );
+        assert_eq!(
+            web_url("ssh://git@host:2222/group/sub/tool.git"),
+            Some("https://host/group/sub/tool".to_string())
+        );
+        assert_eq!(web_url("/srv/git/tool.git"), None);
+    }
+
+    #[test]
+    fn test_repo_name_from_url() {
+        assert_eq!(
+            repo_name_from_url("git@github.com:lisoboss/post-init.git"),
+            Some("post-init".to_string())
+        );
+        assert_eq!(
+            repo_name_from_url("https://github.com/lisoboss/post-init/"),
+            Some("post-init".to_string())
+        );
+        assert_eq!(
+            repo_name_from_url("ssh://git@host:2222/group/sub/tool.git"),
+            Some("tool".to_string())
+        );
+        assert_eq!(repo_name_from_url(""), None);
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::git;
+use crate::output::say;
+
+/// Defaults derived from the surrounding repository for scaffolding commands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl ProjectInfo {
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: None,
+        }
+    }
+}
+
+/// Resolve the project to scaffold from the CLI name and the `--infer` flag
+///
+/// An explicit name always wins; inference only fills in what wasn't given.
+pub fn resolve_project_info(name: Option<&str>, infer: bool) -> Result<ProjectInfo> {
+    if !infer {
+        let name = name.ok_or_else(|| anyhow::anyhow!("A project name is required"))?;
+        return Ok(ProjectInfo::named(name));
+    }
+
+    let inferred = infer_project_info(&std::env::current_dir()?);
+    match (name, inferred) {
+        (Some(name), Some(inferred)) => Ok(ProjectInfo {
+            name: name.to_string(),
+            description: inferred.description,
+        }),
+        (Some(name), None) => Ok(ProjectInfo::named(name)),
+        (None, Some(inferred)) => {
+            say!("💡 Inferred project name: {}", inferred.name);
+            Ok(inferred)
+        }
+        (None, None) => Err(anyhow::anyhow!(
+            "Could not infer a project name; pass one explicitly"
+        )),
+    }
+}
+
+/// Infer a project name from the git remote of `dir`, falling back to the directory name
+pub fn infer_project_info(dir: &Path) -> Option<ProjectInfo> {
+    let raw_name = git::remote_url(dir)
+        .and_then(|url| git::repo_name_from_url(&url))
+        .or_else(|| {
+            dir.canonicalize()
+                .ok()?
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+        })?;
+
+    let name = sanitize_name(&raw_name);
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(ProjectInfo {
+        description: Some(format!("A CLI for {name}")),
+        name,
+    })
+}
+
+/// Lowercase and replace anything that isn't a valid package name character with `-`
+fn sanitize_name(raw: &str) -> String {
+    raw.trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(sanitize_name("My Project"), "my-project");
+        assert_eq!(sanitize_name("post_init"), "post_init");
+        assert_eq!(sanitize_name(" .hidden "), "hidden");
+    }
+
+    #[test]
+    fn test_infer_project_info_from_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("Cool Tool");
+        std::fs::create_dir_all(&project_dir).unwrap();
+
+        let info = infer_project_info(&project_dir).unwrap();
+        assert_eq!(info.name, "cool-tool");
+        assert_eq!(info.description.as_deref(), Some("A CLI for cool-tool"));
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::files;
+use crate::layout::SrcMove;
+
+/// Environment variable naming the journal directory instead of the data directory
+pub const JOURNAL_DIR_ENV_VAR: &str = "POST_INIT_JOURNAL_DIR";
+
+/// A file written during a run, with its content on both sides of the write
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub path: PathBuf,
+    /// Content before the run; `None` when the run created the file
+    pub before: Option<String>,
+    pub after: String,
+}
+
+impl Entry {
+    /// Entry for writing `after` to `path`; call it before the write happens
+    pub fn before_write(path: &Path, after: &str) -> Self {
+        let before = fs::read_to_string(path).ok();
+        Self {
+            path: std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            // What write_file will actually leave on disk
+            after: files::with_line_endings(before.as_deref(), after).into_owned(),
+            before,
+        }
+    }
+}
+
+/// Every file one uvinit run wrote, in write order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Run {
+    /// Local start time, e.g. `20261016T101112.345`; also the journal file name
+    pub id: String,
+    pub entries: Vec<Entry>,
+    /// Packages moved into `src/` by `--migrate-src-layout`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moves: Vec<SrcMove>,
+}
+
+/// Runs kept as `<root>/<id>.json`, where ids sort oldest first
+#[derive(Debug, Clone)]
+pub struct Journal {
+    root: PathBuf,
+}
+
+impl Journal {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The journal in POST_INIT_JOURNAL_DIR, or `~/.local/share/post-init/journal`
+    pub fn open() -> Result<Self> {
+        if let Some(dir) = std::env::var_os(JOURNAL_DIR_ENV_VAR).filter(|dir| !dir.is_empty()) {
+            return Ok(Self::new(PathBuf::from(dir)));
+        }
+        let data_dir = dirs::data_local_dir().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Could not find a data directory for the journal; set {JOURNAL_DIR_ENV_VAR}"
+            )
+        })?;
+        Ok(Self::new(data_dir.join("post-init").join("journal")))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn run_path(&self, id: &str) -> PathBuf {
+        self.root.join(format!("{id}.json"))
+    }
+
+    /// Save `entries` and `moves` as a new run
+    pub fn record(&self, entries: Vec<Entry>, moves: Vec<SrcMove>) -> Result<Run> {
+        fs::create_dir_all(&self.root).with_context(|| {
+            format!(
+                "Failed to create journal directory: {}",
+                self.root.display()
+            )
+        })?;
+        let stamp = chrono::Local::now().format("%Y%m%dT%H%M%S%.3f").to_string();
+        let id = (0..)
+            .map(|n| match n {
+                0 => stamp.clone(),
+                n => format!("{stamp}-{n}"),
+            })
+            .find(|id| !self.run_path(id).exists())
+            .unwrap_or(stamp);
+
+        let run = Run { id, entries, moves };
+        let path = self.run_path(&run.id);
+        let json = serde_json::to_string_pretty(&run).with_context(|| "Failed to serialize run")?;
+        fs::write(&path, json)
+            .with_context(|| format!("Failed to write journal: {}", path.display()))?;
+        Ok(run)
+    }
+
+    /// The run recorded as `id`
+    pub fn load(&self, id: &str) -> Result<Run> {
+        let path = self.run_path(id);
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("No run {id} in {}", self.root.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse journal: {}", path.display()))
+    }
+
+    /// Ids of every recorded run, oldest first
+    pub fn ids(&self) -> Result<Vec<String>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let entries = fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read journal: {}", self.root.display()))?;
+        let mut ids = Vec::new();
+        for entry in entries {
+            let path = entry.with_context(|| "Failed to read journal")?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(id) = path.file_stem().and_then(|s| s.to_str())
+            {
+                ids.push(id.to_string());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// The most recent run, if any
+    pub fn latest(&self) -> Result<Option<Run>> {
+        self.ids()?.last().map(|id| self.load(id)).transpose()
+    }
+
+    /// Drop the run recorded as `id`
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let path = self.run_path(id);
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove journal: {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_load_runs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let journal = Journal::new(temp_dir.path().join("journal"));
+        assert_eq!(journal.latest()?, None);
+
+        let file = temp_dir.path().join("pyproject.toml");
+        fs::write(&file, "old")?;
+        let modified = Entry::before_write(&file, "new");
+        let created = Entry::before_write(&temp_dir.path().join("py.typed"), "");
+        assert_eq!(modified.before.as_deref(), Some("old"));
+        assert_eq!(created.before, None);
+
+        let moved = SrcMove {
+            from: temp_dir.path().join("pkg"),
+            to: temp_dir.path().join("src").join("pkg"),
+            rewritten: Vec::new(),
+        };
+
+        let first = journal.record(vec![modified], vec![moved.clone()])?;
+        let second = journal.record(vec![created], Vec::new())?;
+        assert!(first.id < second.id);
+        assert_eq!(journal.ids()?, vec![first.id.clone(), second.id.clone()]);
+        assert_eq!(journal.latest()?, Some(second.clone()));
+        assert_eq!(journal.load(&first.id)?, first);
+        assert_eq!(first.moves, vec![moved]);
+
+        journal.remove(&second.id)?;
+        assert_eq!(journal.latest()?, Some(first));
+        assert!(journal.load(&second.id).is_err());
+        Ok(())
+    }
+}
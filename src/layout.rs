@@ -0,0 +1,483 @@
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::files::{OverwritePolicy, write_file};
+
+/// Whether `project_dir/<module>` is a flat-layout package that can move to
+/// `src/<module>`: it has an `__init__.py` and nothing sits at the target yet
+pub fn has_flat_package(project_dir: &Path, module: &str) -> bool {
+    project_dir.join(module).join("__init__.py").is_file()
+        && !project_dir.join("src").join(module).exists()
+}
+
+/// Directory of the package `module` in `project_dir`, preferring
+/// `src/<module>` over a flat `<module>`
+pub fn package_dir(project_dir: &Path, module: &str) -> Option<PathBuf> {
+    [
+        project_dir.join("src").join(module),
+        project_dir.join(module),
+    ]
+    .into_iter()
+    .find(|dir| dir.join("__init__.py").is_file())
+}
+
+/// Top-level directories that hold tooling or docs rather than packages
+const NON_PACKAGE_DIRS: &[&str] = &[
+    "benchmarks",
+    "build",
+    "dist",
+    "doc",
+    "docs",
+    "examples",
+    "scripts",
+    "site-packages",
+    "test",
+    "tests",
+    "venv",
+];
+
+/// Whether `name` can be imported as a top-level package
+fn is_package_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !NON_PACKAGE_DIRS.contains(&name)
+}
+
+/// Whether any Python file sits below `dir`
+fn has_python_files(dir: &Path) -> bool {
+    WalkBuilder::new(dir).build().flatten().any(|entry| {
+        entry.path().extension().is_some_and(|ext| ext == "py") && entry.path().is_file()
+    })
+}
+
+/// Package directories, relative to `project_dir` and searched in `src/` when
+/// it exists, that hatchling cannot find from the project name `module`:
+/// namespace packages without a top-level `__init__.py`, or several
+/// top-level packages
+///
+/// Empty when `<module>/__init__.py` or `<module>.py` exists, since hatchling
+/// picks those up by itself.
+pub fn namespace_packages(project_dir: &Path, module: &str) -> Vec<String> {
+    let src = project_dir.join("src");
+    if package_dir(project_dir, module).is_some()
+        || [&src, project_dir]
+            .iter()
+            .any(|root| root.join(format!("{module}.py")).is_file())
+    {
+        return Vec::new();
+    }
+
+    let (root, prefix) = if src.is_dir() {
+        (src, "src/")
+    } else {
+        (project_dir.to_path_buf(), "")
+    };
+    let Ok(entries) = fs::read_dir(&root) else {
+        return Vec::new();
+    };
+    let mut packages: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| is_package_name(name) && has_python_files(&root.join(name)))
+        .map(|name| format!("{prefix}{name}"))
+        .collect();
+    packages.sort();
+    packages
+}
+
+/// `__init__.py` content that imports `__version__` from the sibling module
+/// `version_module`, or `None` when it already mentions `__version__`
+///
+/// The import goes after a leading module docstring and `__future__` imports.
+pub fn add_version_import(content: &str, version_module: &str) -> Option<String> {
+    if content.contains("__version__") {
+        return None;
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut insert_at = 0;
+    let mut in_docstring: Option<&str> = None;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if let Some(quote) = in_docstring {
+            if trimmed.ends_with(quote) {
+                in_docstring = None;
+                insert_at = i + 1;
+            }
+            continue;
+        }
+        if let Some(quote) = ["\"\"\"", "'''"]
+            .into_iter()
+            .find(|quote| trimmed.starts_with(quote))
+        {
+            // A docstring closing on its opening line ends there
+            if trimmed.len() >= 2 * quote.len() && trimmed.ends_with(quote) {
+                insert_at = i + 1;
+            } else {
+                in_docstring = Some(quote);
+            }
+        } else if trimmed.starts_with("from __future__") {
+            insert_at = i + 1;
+        } else if !(trimmed.is_empty() || trimmed.starts_with('#')) {
+            break;
+        }
+    }
+
+    let import = format!("from .{version_module} import __version__");
+    let mut out: Vec<&str> = lines[..insert_at].to_vec();
+    if insert_at > 0 {
+        out.push("");
+    }
+    out.push(&import);
+    let rest = &lines[insert_at..];
+    if let Some(start) = rest.iter().position(|l| !l.trim().is_empty()) {
+        out.push("");
+        out.extend(&rest[start..]);
+    }
+    Some(out.join("\n") + "\n")
+}
+
+/// Calls whose string argument is taken to be a path
+const PATH_CALLS: &[&str] = &[
+    "open",
+    "io.open",
+    "os.path.join",
+    "os.path.exists",
+    "os.path.isfile",
+    "os.path.isdir",
+];
+
+/// Path types, matched by their last dotted segment, e.g. `pathlib.Path`
+const PATH_TYPES: &[&str] = &["Path", "PurePath", "PosixPath", "WindowsPath"];
+
+/// Whether a literal right after `before` is the first argument of a path call
+fn is_path_argument(before: &str) -> bool {
+    let Some(call) = before.trim_end().strip_suffix('(') else {
+        return false;
+    };
+    let callee = &call[call
+        .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '.')
+        .len()..];
+    PATH_CALLS.contains(&callee)
+        || callee
+            .rsplit('.')
+            .next()
+            .is_some_and(|name| PATH_TYPES.contains(&name))
+}
+
+/// Quoted paths into the package passed to a path call, like
+/// `Path("pkg/data.json")`, pointed at `src/pkg/`
+///
+/// Other literals starting with `pkg/` may be URLs, routes or messages, so they
+/// are left alone and their line numbers returned instead.
+fn rewrite_package_paths(content: &str, module: &str) -> (Option<String>, Vec<usize>) {
+    // Where each literal starts, and the length of its quote and `./`
+    let mut starts = Vec::new();
+    for quote in ['"', '\''] {
+        for prefix in ["", "./"] {
+            let needle = format!("{quote}{prefix}{module}/");
+            starts.extend(
+                content
+                    .match_indices(&needle)
+                    .map(|(i, _)| (i, 1 + prefix.len())),
+            );
+        }
+    }
+    starts.sort();
+
+    let mut rewritten = String::with_capacity(content.len());
+    let mut copied = 0;
+    let mut skipped = Vec::new();
+    for (start, prefix_len) in starts {
+        if !is_path_argument(&content[..start]) {
+            skipped.push(content[..start].matches('\n').count() + 1);
+            continue;
+        }
+        let at = start + prefix_len;
+        rewritten.push_str(&content[copied..at]);
+        rewritten.push_str("src/");
+        copied = at;
+    }
+    if copied == 0 {
+        return (None, skipped);
+    }
+    rewritten.push_str(&content[copied..]);
+    (Some(rewritten), skipped)
+}
+
+/// A package moved into `src/` by `--migrate-src-layout`, with the test
+/// files rewritten to match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SrcMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    #[serde(default)]
+    pub rewritten: Vec<PathBuf>,
+}
+
+impl SrcMove {
+    /// Whether the package still sits where the move put it
+    pub fn is_in_place(&self) -> bool {
+        self.to.exists() && !self.from.exists()
+    }
+
+    /// Move the package back, dropping `src/` when nothing else is left in it
+    pub fn revert(&self) -> Result<()> {
+        fs::rename(&self.to, &self.from).with_context(|| {
+            format!(
+                "Failed to move {} back to {}",
+                self.to.display(),
+                self.from.display()
+            )
+        })?;
+        if let Some(src) = self.to.parent() {
+            // Fails, as intended, when src/ holds anything else
+            let _ = fs::remove_dir(src);
+        }
+        log::info!(
+            "Moved {} back to {}",
+            self.to.display(),
+            self.from.display()
+        );
+        Ok(())
+    }
+}
+
+/// A test file's content before and after pointing its package paths at `src/`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    pub path: PathBuf,
+    pub original: String,
+    pub content: String,
+}
+
+/// The move of `project_dir/<module>` to `project_dir/src/<module>`, worked
+/// out before anything is touched so the files it rewrites can be backed up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrcMigration {
+    pub src_move: SrcMove,
+    pub rewrites: Vec<Rewrite>,
+    /// Package-like literals in the test files that were not rewritten
+    pub warnings: Vec<String>,
+}
+
+impl SrcMigration {
+    /// Plan the move, collecting the Python files under `tests/` whose
+    /// package paths need updating
+    pub fn plan(project_dir: &Path, module: &str) -> Result<Self> {
+        // The move is recorded for undo and restore, which may run from elsewhere
+        let project_dir = &std::path::absolute(project_dir)
+            .with_context(|| format!("Failed to resolve {}", project_dir.display()))?;
+        let target = project_dir.join("src").join(module);
+        if target.exists() {
+            return Err(anyhow::anyhow!("{} already exists", target.display()));
+        }
+
+        let mut rewrites = Vec::new();
+        let mut warnings = Vec::new();
+        let tests = project_dir.join("tests");
+        if tests.is_dir() {
+            for entry in WalkBuilder::new(&tests).build() {
+                let entry = entry.with_context(|| format!("Failed to walk {}", tests.display()))?;
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "py") || !path.is_file() {
+                    continue;
+                }
+                let original = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read file: {}", path.display()))?;
+                let (content, skipped) = rewrite_package_paths(&original, module);
+                warnings.extend(skipped.into_iter().map(|line| {
+                    format!(
+                        "{}:{line}: left \"{module}/...\" as is; update it if it is a path into the package",
+                        path.display()
+                    )
+                }));
+                if let Some(content) = content {
+                    rewrites.push(Rewrite {
+                        path: path.to_path_buf(),
+                        original,
+                        content,
+                    });
+                }
+            }
+        }
+        rewrites.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            src_move: SrcMove {
+                from: project_dir.join(module),
+                to: target,
+                rewritten: rewrites.iter().map(|r| r.path.clone()).collect(),
+            },
+            rewrites,
+            warnings,
+        })
+    }
+
+    /// Move the package, then write the rewritten test files
+    pub fn apply(&self) -> Result<()> {
+        let SrcMove { from, to, .. } = &self.src_move;
+        if let Some(src) = to.parent() {
+            fs::create_dir_all(src)
+                .with_context(|| format!("Failed to create directory: {}", src.display()))?;
+        }
+        fs::rename(from, to)
+            .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+        log::info!("Moved {} to {}", from.display(), to.display());
+
+        for rewrite in &self.rewrites {
+            write_file(&rewrite.path, &rewrite.content, OverwritePolicy::Force)?;
+            log::info!("Updated package paths in {}", rewrite.path.display());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rewrite_package_paths() {
+        assert_eq!(
+            rewrite_package_paths(
+                "DATA = Path(\"pkg/data.json\")\nOTHER = open( './pkg/x')\nKEEP = \"subpkg/y\"\n",
+                "pkg"
+            ),
+            (
+                Some(
+                    "DATA = Path(\"src/pkg/data.json\")\nOTHER = open( './src/pkg/x')\nKEEP = \"subpkg/y\"\n"
+                        .to_string()
+                ),
+                vec![]
+            )
+        );
+        // Routes, messages and bare strings may not be paths at all
+        assert_eq!(
+            rewrite_package_paths(
+                "r = client.get(\"pkg/items\")\nassert msg == 'pkg/missing'\n",
+                "pkg"
+            ),
+            (None, vec![1, 2])
+        );
+        assert_eq!(rewrite_package_paths("import pkg\n", "pkg"), (None, vec![]));
+    }
+
+    #[test]
+    fn test_add_version_import() {
+        assert_eq!(
+            add_version_import("", "_version").as_deref(),
+            Some("from ._version import __version__\n")
+        );
+        assert_eq!(
+            add_version_import(
+                "\"\"\"My package.\n\nLonger text.\n\"\"\"\nfrom __future__ import annotations\n\nimport os\n",
+                "_version"
+            )
+            .as_deref(),
+            Some(
+                "\"\"\"My package.\n\nLonger text.\n\"\"\"\nfrom __future__ import annotations\n\nfrom ._version import __version__\n\nimport os\n"
+            )
+        );
+        assert_eq!(
+            add_version_import("'''One line.'''\n", "_version").as_deref(),
+            Some("'''One line.'''\n\nfrom ._version import __version__\n")
+        );
+        assert_eq!(
+            add_version_import("__version__ = \"1.0\"\n", "_version"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_namespace_packages() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        fs::create_dir_all(project.join("acme").join("tools"))?;
+        fs::write(project.join("acme/tools/__init__.py"), "")?;
+        fs::create_dir_all(project.join("tests"))?;
+        fs::write(project.join("tests").join("test_tools.py"), "")?;
+        fs::create_dir_all(project.join("assets"))?;
+        fs::write(project.join("assets").join("logo.svg"), "")?;
+        assert_eq!(namespace_packages(project, "acme_tools"), vec!["acme"]);
+
+        // Under src/, several top-level packages
+        fs::create_dir_all(project.join("src"))?;
+        fs::rename(project.join("acme"), project.join("src").join("acme"))?;
+        fs::create_dir_all(project.join("src").join("acme_plugins"))?;
+        fs::write(project.join("src/acme_plugins/__init__.py"), "")?;
+        assert_eq!(
+            namespace_packages(project, "acme_tools"),
+            vec!["src/acme", "src/acme_plugins"]
+        );
+
+        // hatchling finds packages named after the project
+        assert!(namespace_packages(project, "acme_plugins").is_empty());
+        fs::write(project.join("src").join("acme_tools.py"), "")?;
+        assert!(namespace_packages(project, "acme_tools").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_to_src() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project = temp_dir.path();
+        fs::create_dir_all(project.join("pkg"))?;
+        fs::write(project.join("pkg").join("__init__.py"), "")?;
+        fs::create_dir_all(project.join("tests"))?;
+        fs::write(
+            project.join("tests").join("test_data.py"),
+            "import pkg\n\nDATA = Path(\"pkg/data.json\")\nROUTE = \"pkg/items\"\n",
+        )?;
+        fs::write(project.join("tests").join("test_plain.py"), "import pkg\n")?;
+
+        assert!(has_flat_package(project, "pkg"));
+        assert!(!has_flat_package(project, "other"));
+        assert_eq!(package_dir(project, "pkg"), Some(project.join("pkg")));
+
+        let migration = SrcMigration::plan(project, "pkg")?;
+        assert_eq!(
+            migration.src_move.rewritten,
+            vec![project.join("tests").join("test_data.py")]
+        );
+        assert_eq!(migration.warnings.len(), 1);
+        assert!(migration.warnings[0].ends_with(
+            "test_data.py:4: left \"pkg/...\" as is; update it if it is a path into the package"
+        ));
+        // Planning touches nothing
+        assert!(project.join("pkg").is_dir());
+        assert_eq!(
+            migration.rewrites[0].original,
+            "import pkg\n\nDATA = Path(\"pkg/data.json\")\nROUTE = \"pkg/items\"\n"
+        );
+
+        migration.apply()?;
+        assert!(migration.src_move.is_in_place());
+        assert!(project.join("src/pkg/__init__.py").is_file());
+        assert!(!project.join("pkg").exists());
+        assert_eq!(
+            fs::read_to_string(project.join("tests").join("test_data.py"))?,
+            "import pkg\n\nDATA = Path(\"src/pkg/data.json\")\nROUTE = \"pkg/items\"\n"
+        );
+
+        // Already moved
+        assert!(!has_flat_package(project, "pkg"));
+        assert_eq!(
+            package_dir(project, "pkg"),
+            Some(project.join("src").join("pkg"))
+        );
+        assert!(SrcMigration::plan(project, "pkg").is_err());
+
+        migration.src_move.revert()?;
+        assert!(project.join("pkg/__init__.py").is_file());
+        assert!(!project.join("src").exists());
+        Ok(())
+    }
+}
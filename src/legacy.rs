@@ -0,0 +1,498 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Packaging metadata read from a legacy `setup.cfg` or `setup.py`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub name: Option<String>,
+    /// Only literal versions; `attr:` and `file:` directives are left out
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub requires_python: Option<String>,
+    pub dependencies: Vec<String>,
+    /// Extras, as extra name -> requirements
+    pub optional_dependencies: BTreeMap<String, Vec<String>>,
+    /// Entry points, as group -> (name, object reference); includes
+    /// `console_scripts` and `gui_scripts`
+    pub entry_points: BTreeMap<String, Vec<(String, String)>>,
+}
+
+impl Metadata {
+    /// Fields of `other` that are set replace those of `self`, as `setup()`
+    /// arguments override setup.cfg
+    fn overlay(mut self, other: Metadata) -> Self {
+        self.name = other.name.or(self.name);
+        self.version = other.version.or(self.version);
+        self.description = other.description.or(self.description);
+        self.requires_python = other.requires_python.or(self.requires_python);
+        if !other.dependencies.is_empty() {
+            self.dependencies = other.dependencies;
+        }
+        self.optional_dependencies
+            .extend(other.optional_dependencies);
+        self.entry_points.extend(other.entry_points);
+        self
+    }
+}
+
+/// Metadata from `setup.cfg` and `setup.py` in `project_dir`, or `None` when
+/// neither exists or neither names the project
+pub fn read(project_dir: &Path) -> Option<Metadata> {
+    let cfg = fs::read_to_string(project_dir.join("setup.cfg"))
+        .ok()
+        .map(|content| parse_setup_cfg(&content));
+    let py = fs::read_to_string(project_dir.join("setup.py"))
+        .ok()
+        .and_then(|content| parse_setup_py(&content));
+    let metadata = match (cfg, py) {
+        (None, None) => return None,
+        (cfg, py) => cfg.unwrap_or_default().overlay(py.unwrap_or_default()),
+    };
+    metadata.name.is_some().then_some(metadata)
+}
+
+/// `name = value` pairs of each setup.cfg section, with indented continuation
+/// lines joined by newlines
+fn ini_sections(content: &str) -> BTreeMap<String, Vec<(String, String)>> {
+    let mut sections: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    let mut section = String::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if let Some((_, value)) = sections
+                .get_mut(&section)
+                .and_then(|entries| entries.last_mut())
+            {
+                value.push('\n');
+                value.push_str(trimmed);
+            }
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            section = name.trim().to_string();
+            continue;
+        }
+        if let Some((key, value)) = trimmed.split_once(['=', ':']) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .push((key.trim().replace('-', "_"), value.trim().to_string()));
+        }
+    }
+    sections
+}
+
+/// Non-empty lines of a multi-line setup.cfg value; `;`-separated on one line
+/// works too
+fn list_value(value: &str) -> Vec<String> {
+    value
+        .split(['\n', ';'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `name = object` lines of an entry point group
+fn entry_point_lines<I: IntoIterator<Item = S>, S: AsRef<str>>(lines: I) -> Vec<(String, String)> {
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let (name, target) = line.as_ref().split_once('=')?;
+            Some((name.trim().to_string(), target.trim().to_string()))
+        })
+        .collect()
+}
+
+pub fn parse_setup_cfg(content: &str) -> Metadata {
+    let sections = ini_sections(content);
+    let get = |section: &str, key: &str| {
+        sections
+            .get(section)
+            .and_then(|entries| entries.iter().rev().find(|(k, _)| k == key))
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.is_empty())
+    };
+    let directive = |value: &String| value.starts_with("attr:") || value.starts_with("file:");
+
+    let mut metadata = Metadata {
+        name: get("metadata", "name"),
+        version: get("metadata", "version").filter(|v| !directive(v)),
+        description: get("metadata", "description").filter(|v| !directive(v)),
+        requires_python: get("options", "python_requires"),
+        dependencies: get("options", "install_requires")
+            .filter(|v| !directive(v))
+            .map(|value| list_value(&value))
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    for (extra, value) in sections.get("options.extras_require").into_iter().flatten() {
+        metadata
+            .optional_dependencies
+            .insert(extra.clone(), list_value(value));
+    }
+    for (group, value) in sections.get("options.entry_points").into_iter().flatten() {
+        metadata
+            .entry_points
+            .insert(group.clone(), entry_point_lines(value.lines()));
+    }
+    metadata
+}
+
+/// A Python expression in a `setup()` call, as far as it is a literal
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Str(String),
+    List(Vec<Literal>),
+    Dict(Vec<(Literal, Literal)>),
+    /// Anything computed, like `find_packages()` or a variable
+    Other,
+}
+
+impl Literal {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Literal::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The strings of a list, or the lines of a multi-line string
+    fn strings(&self) -> Vec<String> {
+        match self {
+            Literal::List(items) => items
+                .iter()
+                .filter_map(Literal::as_str)
+                .map(str::to_string)
+                .collect(),
+            Literal::Str(s) => list_value(s),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Just enough of a Python tokenizer to read literal `setup()` arguments
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_space(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '#' {
+                while self.chars.next_if(|&c| c != '\n').is_some() {}
+            } else if c.is_whitespace() || c == '\\' {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut ident = String::new();
+        while let Some(c) = self.chars.next_if(|&c| c.is_alphanumeric() || c == '_') {
+            ident.push(c);
+        }
+        ident
+    }
+
+    /// A string literal whose opening quote is next, without a prefix
+    fn string(&mut self) -> Option<String> {
+        let quote = self.chars.next()?;
+        let triple = {
+            let mut ahead = self.chars.clone();
+            ahead.next() == Some(quote) && ahead.next() == Some(quote)
+        };
+        if triple {
+            self.chars.next();
+            self.chars.next();
+        } else if self.chars.peek() == Some(&quote) {
+            self.chars.next();
+            return Some(String::new());
+        }
+
+        let mut value = String::new();
+        loop {
+            let c = self.chars.next()?;
+            match c {
+                '\\' => match self.chars.next()? {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    '\n' => {}
+                    other => value.push(other),
+                },
+                c if c == quote && !triple => return Some(value),
+                c if c == quote => {
+                    let mut ahead = self.chars.clone();
+                    if ahead.next() == Some(quote) && ahead.next() == Some(quote) {
+                        self.chars.next();
+                        self.chars.next();
+                        return Some(value);
+                    }
+                    value.push(c);
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    /// Skip a non-literal expression up to the `,` or closing bracket ending it
+    fn skip_expression(&mut self) {
+        let mut depth = 0usize;
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' if depth == 0 => return,
+                ')' | ']' | '}' => depth -= 1,
+                ',' | ':' if depth == 0 => return,
+                '\'' | '"' => {
+                    self.string();
+                    continue;
+                }
+                _ => {}
+            }
+            self.chars.next();
+        }
+    }
+
+    /// Items up to `close`, each read by `item`
+    fn items<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_space();
+            if self.chars.next_if_eq(&close).is_some() {
+                return Some(items);
+            }
+            items.push(item(self)?);
+            self.skip_space();
+            if self.chars.next_if_eq(&',').is_none() {
+                self.skip_space();
+                return self.chars.next_if_eq(&close).map(|_| items);
+            }
+        }
+    }
+
+    fn literal(&mut self) -> Option<Literal> {
+        self.skip_space();
+        let literal = match *self.chars.peek()? {
+            '\'' | '"' => {
+                // Adjacent strings concatenate
+                let mut value = self.string()?;
+                loop {
+                    self.skip_space();
+                    match self.chars.peek() {
+                        Some('\'' | '"') => value.push_str(&self.string()?),
+                        _ => break Literal::Str(value),
+                    }
+                }
+            }
+            '[' | '(' => {
+                let close = if self.chars.next()? == '[' { ']' } else { ')' };
+                Literal::List(self.items(close, Self::literal)?)
+            }
+            '{' => {
+                self.chars.next();
+                Literal::Dict(self.items('}', |parser| {
+                    let key = parser.literal()?;
+                    parser.skip_space();
+                    parser.chars.next_if_eq(&':')?;
+                    Some((key, parser.literal()?))
+                })?)
+            }
+            c if c.is_alphabetic() => {
+                // String prefixes like r"..." or u'...'
+                let mut ahead = self.chars.clone();
+                let prefix: String =
+                    std::iter::from_fn(|| ahead.next_if(|c| c.is_alphabetic())).collect();
+                if prefix.len() <= 2
+                    && prefix.chars().all(|c| "rRuU".contains(c))
+                    && matches!(ahead.peek(), Some('\'' | '"'))
+                {
+                    self.identifier();
+                    return self.literal();
+                }
+                self.skip_expression();
+                Literal::Other
+            }
+            _ => {
+                self.skip_expression();
+                Literal::Other
+            }
+        };
+        Some(literal)
+    }
+
+    /// Keyword arguments of a call whose `(` was just consumed
+    fn keyword_arguments(&mut self) -> Option<Vec<(String, Literal)>> {
+        self.items(')', |parser| {
+            parser.skip_space();
+            let mut ahead = parser.chars.clone();
+            let name: String =
+                std::iter::from_fn(|| ahead.next_if(|c| c.is_alphanumeric() || *c == '_'))
+                    .collect();
+            while ahead.next_if(|c| c.is_whitespace()).is_some() {}
+            if !name.is_empty() && ahead.peek() == Some(&'=') && ahead.clone().nth(1) != Some('=') {
+                parser.identifier();
+                parser.skip_space();
+                parser.chars.next();
+                Some((name, parser.literal()?))
+            } else {
+                // Positional arguments and **kwargs carry nothing readable
+                parser.chars.next_if(|&c| c == '*');
+                parser.chars.next_if(|&c| c == '*');
+                parser.literal().map(|_| (String::new(), Literal::Other))
+            }
+        })
+    }
+}
+
+/// Metadata from the literal arguments of the `setup()` call in a setup.py,
+/// or `None` when there is no call to read
+pub fn parse_setup_py(content: &str) -> Option<Metadata> {
+    let start = content.match_indices("setup(").find_map(|(index, _)| {
+        let before = content[..index].chars().next_back();
+        (!before.is_some_and(|c| c.is_alphanumeric() || c == '_')).then_some(index + "setup(".len())
+    })?;
+    let mut parser = Parser {
+        chars: content[start..].chars().peekable(),
+    };
+    let arguments: BTreeMap<String, Literal> = parser.keyword_arguments()?.into_iter().collect();
+    let string = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(Literal::as_str)
+            .map(str::to_string)
+    };
+
+    let mut metadata = Metadata {
+        name: string("name"),
+        version: string("version"),
+        description: string("description"),
+        requires_python: string("python_requires"),
+        dependencies: arguments
+            .get("install_requires")
+            .map(Literal::strings)
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    if let Some(Literal::Dict(extras)) = arguments.get("extras_require") {
+        for (extra, requirements) in extras {
+            if let Some(extra) = extra.as_str() {
+                metadata
+                    .optional_dependencies
+                    .insert(extra.to_string(), requirements.strings());
+            }
+        }
+    }
+    if let Some(Literal::Dict(groups)) = arguments.get("entry_points") {
+        for (group, entries) in groups {
+            if let Some(group) = group.as_str() {
+                metadata
+                    .entry_points
+                    .insert(group.to_string(), entry_point_lines(entries.strings()));
+            }
+        }
+    }
+    Some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_setup_cfg() {
+        let metadata = parse_setup_cfg(
+            "[metadata]\nname = legacy-app\nversion = attr: legacy_app.__version__\ndescription = A legacy app\n\n[options]\npython_requires = >=3.8\ninstall_requires =\n    requests>=2\n    click  \n\n[options.extras_require]\ntest =\n    pytest\n\n[options.entry_points]\nconsole_scripts =\n    legacy = legacy_app.cli:main\n",
+        );
+        assert_eq!(metadata.name.as_deref(), Some("legacy-app"));
+        assert_eq!(metadata.version, None);
+        assert_eq!(metadata.description.as_deref(), Some("A legacy app"));
+        assert_eq!(metadata.requires_python.as_deref(), Some(">=3.8"));
+        assert_eq!(metadata.dependencies, vec!["requests>=2", "click"]);
+        assert_eq!(
+            metadata.optional_dependencies["test"],
+            vec!["pytest".to_string()]
+        );
+        assert_eq!(
+            metadata.entry_points["console_scripts"],
+            vec![("legacy".to_string(), "legacy_app.cli:main".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_setup_py() {
+        let metadata = parse_setup_py(
+            r#"from setuptools import setup, find_packages
+
+setup(
+    name="legacy-app",  # the distribution name
+    version='1.2.0',
+    description="A " "legacy app",
+    packages=find_packages(exclude=["tests"]),
+    install_requires=["requests>=2", "click"],
+    extras_require={"test": ["pytest"]},
+    entry_points={
+        "console_scripts": ["legacy = legacy_app.cli:main"],
+    },
+    **extra_kwargs,
+)
+"#,
+        )
+        .unwrap_or_default();
+        assert_eq!(metadata.name.as_deref(), Some("legacy-app"));
+        assert_eq!(metadata.version.as_deref(), Some("1.2.0"));
+        assert_eq!(metadata.description.as_deref(), Some("A legacy app"));
+        assert_eq!(metadata.dependencies, vec!["requests>=2", "click"]);
+        assert_eq!(
+            metadata.optional_dependencies["test"],
+            vec!["pytest".to_string()]
+        );
+        assert_eq!(
+            metadata.entry_points["console_scripts"],
+            vec![("legacy".to_string(), "legacy_app.cli:main".to_string())]
+        );
+
+        assert_eq!(parse_setup_py("print('no packaging here')\n"), None);
+    }
+
+    #[test]
+    fn test_read_overlays_setup_py_on_setup_cfg() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert_eq!(read(temp_dir.path()), None);
+
+        fs::write(
+            temp_dir.path().join("setup.cfg"),
+            "[metadata]\nname = app\nversion = 0.1.0\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("setup.py"),
+            "import setuptools\nsetuptools.setup(version=\"0.2.0\")\n",
+        )?;
+        let metadata = read(temp_dir.path()).unwrap_or_default();
+        assert_eq!(metadata.name.as_deref(), Some("app"));
+        assert_eq!(metadata.version.as_deref(), Some("0.2.0"));
+
+        // A shim setup.py alone names no project
+        fs::remove_file(temp_dir.path().join("setup.cfg"))?;
+        fs::write(
+            temp_dir.path().join("setup.py"),
+            "import setuptools\nsetuptools.setup()\n",
+        )?;
+        assert_eq!(read(temp_dir.path()), None);
+        Ok(())
+    }
+}
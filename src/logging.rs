@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Sends records to stderr at the `-v` level (or per `RUST_LOG`) and, with
+/// `--log-file`, every record to that file with a timestamp
+struct Logger {
+    console: env_logger::Logger,
+    file: Option<Mutex<File>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.file.is_some() || self.console.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.console.matches(record) {
+            self.console.log(record);
+        }
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = writeln!(
+                file,
+                "{} {:<5} {}",
+                chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+                record.level(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Console log level for `-v` repeated `verbose` times
+///
+/// Progress lines already cover the default level, so records only reach the
+/// console once asked for.
+fn console_level(verbose: u8) -> LevelFilter {
+    match verbose {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+fn open_log_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create log directory: {}", parent.display()))?;
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file: {}", path.display()))
+}
+
+/// Install the global logger; `RUST_LOG` overrides the console level from `-v`
+pub fn init(log_file: Option<&Path>, verbose: u8) -> Result<()> {
+    let console = env_logger::Builder::new()
+        .filter_level(console_level(verbose))
+        .parse_env(env_logger::Env::default())
+        .format(|buf, record| writeln!(buf, "{:<5} {}", record.level(), record.args()))
+        .build();
+    let file = log_file.map(open_log_file).transpose()?.map(Mutex::new);
+
+    let max_level = if file.is_some() {
+        console.filter().max(LevelFilter::Debug)
+    } else {
+        console.filter()
+    };
+    log::set_boxed_logger(Box::new(Logger { console, file }))
+        .with_context(|| "Failed to initialize logger")?;
+    log::set_max_level(max_level);
+
+    Ok(())
+}
@@ -1,9 +1,25 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use std::io::Write;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
+mod annotations;
+mod backups;
 mod commands;
 mod config;
+mod files;
+mod git;
+mod infer;
+mod journal;
+mod layout;
+mod legacy;
+mod logging;
+mod output;
+mod report;
+mod scan_cache;
+mod templates;
+mod watch;
 
 #[derive(Parser)]
 #[command(name = "post-init")]
@@ -12,60 +28,532 @@ mod config;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    #[command(flatten)]
+    overwrite: files::OverwriteArgs,
+    #[command(flatten)]
+    config: config::ConfigArgs,
+    /// Output format; json prints one document to stdout (uvinit and config)
+    #[arg(
+        long,
+        visible_alias = "output",
+        global = true,
+        value_enum,
+        default_value_t = output::OutputFormat::Human
+    )]
+    format: output::OutputFormat,
+    /// Show more detail, such as why each directory or file was skipped (repeat for more)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Only print errors, plus the closing summary of uvinit
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also write a timestamped log of every action to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    /// Also print warnings and errors as CI annotations (default: github when GITHUB_ACTIONS=true)
+    #[arg(long, global = true, value_enum)]
+    annotations: Option<annotations::AnnotationFormat>,
 }
 
+// Parsed once per process, so the size of the uvinit variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize UV Python project with VCS versioning
+    #[command(
+        after_help = "Settings precedence: command-line flags, then the --profile table, then ~/.config/post-init.toml, then built-in defaults."
+    )]
     Uvinit {
-        /// Target directory to search for pyproject.toml files
+        /// Directory to search for pyproject.toml files, or a single .toml file to process
         #[arg(short, long, default_value = ".")]
         path: PathBuf,
+        /// Several directories or files to process, in place of --path
+        #[arg(value_name = "PATH", conflicts_with = "path")]
+        paths: Vec<PathBuf>,
+        /// Process only the pyproject.toml files named in this newline-separated
+        /// list, or in stdin with `-` (e.g. `git diff --name-only | post-init uvinit -y --files -`)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["path", "paths"])]
+        files: Option<PathBuf>,
         /// Skip confirmation prompts
         #[arg(short, long)]
         yes: bool,
+        /// Write results next to each file with this suffix instead of modifying it
+        #[arg(long, value_name = "SUFFIX", num_args = 0..=1, default_missing_value = ".proposed")]
+        out_suffix: Option<String>,
+        /// Group results by directory with per-directory subtotals
+        #[arg(long)]
+        tree: bool,
+        /// After the run, keep watching for new pyproject.toml files and
+        /// process them without confirmation until interrupted
+        #[arg(long, conflicts_with_all = ["files", "check"])]
+        watch: bool,
+        /// Write a Markdown summary of processed files and changes to this path
+        #[arg(long, value_name = "PATH")]
+        report_markdown: Option<PathBuf>,
+        /// Write the errors of the run, grouped by file, as JSON to this path
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>,
+        /// Only fill in missing build-system/tool pieces for files that already
+        /// have project.dynamic, leaving [project] untouched
+        #[arg(long)]
+        fix_requires_only: bool,
+        /// Stage modified files with `git add` after processing
+        #[arg(long)]
+        git_add: bool,
+        /// Stage and commit modified files after a run without failures, using
+        /// the configured git_commit_message
+        #[arg(long, conflicts_with = "out_suffix")]
+        git_commit: bool,
+        /// Tag HEAD with the old static version (or initial_version) in
+        /// repositories switched to git versions that have no version tag yet
+        #[arg(long, conflicts_with = "out_suffix")]
+        tag: bool,
+        /// Write a GitHub Actions workflow that publishes to PyPI on version tags
+        #[arg(long, conflicts_with = "out_suffix")]
+        publish_workflow: bool,
+        /// Write a multi-stage uv Dockerfile next to each modified project
+        #[arg(long, conflicts_with = "out_suffix")]
+        dockerfile: bool,
+        /// Write .devcontainer/devcontainer.json next to each modified project
+        #[arg(long, conflicts_with = "out_suffix")]
+        devcontainer: bool,
+        /// Move a flat-layout package into src/<package> and update the wheel
+        /// packages, pytest pythonpath and Path(...)/open(...) package paths in tests
+        #[arg(long, conflicts_with_all = ["out_suffix", "check"])]
+        migrate_src_layout: bool,
+        /// Check each modified project with uv (default from config)
+        #[arg(long, value_enum, value_name = "MODE", conflicts_with_all = ["out_suffix", "dry_run", "check"])]
+        validate: Option<config::Validation>,
+        /// Add a py.typed marker to the package and ship it in wheels
+        #[arg(long, conflicts_with = "out_suffix")]
+        py_typed: bool,
+        /// Merge requirements.txt and requirements-dev.txt next to each
+        /// pyproject.toml into its dependencies
+        #[arg(long)]
+        import_requirements: bool,
+        /// Fill [project] from setup.cfg and setup.py, creating pyproject.toml
+        /// next to legacy packaging files that lack one
+        #[arg(long)]
+        migrate_setup: bool,
+        /// Point dependencies on other uv workspace members at the workspace
+        #[arg(long)]
+        workspace_sources: bool,
+        /// Configure a type checker, with its version from requires-python
+        #[arg(long, value_enum, value_name = "CHECKER")]
+        type_checker: Option<config::TypeChecker>,
+        /// Configure a dependency update bot at each repository root
+        #[arg(long, value_enum, value_name = "BOT", conflicts_with = "out_suffix")]
+        dependency_updates: Option<config::DependencyUpdates>,
+        /// Write .python-version next to each modified project
+        #[arg(long, conflicts_with = "out_suffix")]
+        python_version_file: bool,
+        /// Version for .python-version instead of the requires-python lower
+        /// bound; implies --python-version-file
+        #[arg(long, value_name = "VERSION", conflicts_with = "out_suffix")]
+        python_version: Option<String>,
+        /// Replace an existing non-vcs tool.hatch.version.source (with
+        /// --reprocess, also in files that already have project.dynamic)
+        #[arg(long)]
+        override_version_source: bool,
+        /// Process files that already have project.dynamic again, and files
+        /// whose version comes from another backend; --force only decides
+        /// whether files written next to them are overwritten
+        #[arg(long)]
+        reprocess: bool,
+        /// What to do with a static project.version (default from config)
+        #[arg(long, value_enum, value_name = "ACTION")]
+        version_key_action: Option<config::VersionKeyAction>,
+        /// Build backend that derives the version from git (default from config)
+        #[arg(long, value_enum, value_name = "BACKEND")]
+        version_backend: Option<config::VersionBackend>,
+        /// Shorthand for --version-key-action keep
+        #[arg(long, conflicts_with = "version_key_action")]
+        keep_version_key: bool,
+        /// Walk the directory tree on multiple threads (the default unless the
+        /// config sets parallel_walk = false)
+        #[arg(long)]
+        parallel_walk: bool,
+        /// Walk the directory tree on a single thread
+        #[arg(long, conflicts_with = "parallel_walk")]
+        sequential_walk: bool,
+        /// Also search directories excluded by .gitignore and .ignore files
+        #[arg(long)]
+        no_ignore: bool,
+        /// Parse every file, ignoring and not updating the scan cache
+        #[arg(long)]
+        no_cache: bool,
+        /// Search at most this many directory levels below the path (0 = only the path itself)
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+        /// Descend into symlinked directories (the default unless the config
+        /// sets follow_symlinks = false)
+        #[arg(long)]
+        follow_symlinks: bool,
+        /// Never descend into symlinked directories
+        #[arg(long, conflicts_with = "follow_symlinks")]
+        no_follow_symlinks: bool,
+        /// Only process files matching this glob relative to the path, e.g.
+        /// 'packages/**/pyproject.toml' (repeatable; replaces the configured list)
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Leave out files and directories matching this glob relative to the
+        /// path, e.g. 'examples/**' (repeatable; replaces the configured list)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Only process pyproject.toml files with uncommitted changes (git status)
+        #[arg(long)]
+        dirty: bool,
+        /// Print a checklist of follow-up tasks based on what changed
+        #[arg(long)]
+        next_steps: bool,
+        /// Print a unified diff of each change instead of writing files
+        #[arg(long, conflicts_with_all = ["out_suffix", "git_add", "git_commit", "tag", "publish_workflow"])]
+        dry_run: bool,
+        /// Change nothing; exit 0 if every file is configured, 1 if any needs
+        /// changes and 2 on errors
+        #[arg(long, conflicts_with_all = ["out_suffix", "git_add", "git_commit", "tag", "publish_workflow", "dry_run"])]
+        check: bool,
+        #[command(flatten)]
+        features: Box<config::UvinitFeatureArgs>,
+    },
+    /// Restore pyproject.toml files from the backups uvinit and revert keep
+    Restore {
+        /// Directory to search for files with backups
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        /// Restore only this file
+        #[arg(long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// List every stored backup and exit
+        #[arg(long, conflicts_with = "file")]
+        list: bool,
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Revert the files the last uvinit run wrote, using the run journal
+    Undo {
+        /// Undo this run (see --list) instead of the latest one
+        #[arg(long, value_name = "ID")]
+        run: Option<String>,
+        /// List every recorded run and exit
+        #[arg(long, conflicts_with = "run")]
+        list: bool,
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Remove the settings uvinit added from pyproject.toml files
+    Revert {
+        /// Directory to search for pyproject.toml files
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        /// Skip confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Create a uv Python package with the uvinit transformations applied
+    Uvnew {
+        /// Project name
+        #[arg(required_unless_present = "infer")]
+        name: Option<String>,
+        /// Infer the name and description from the git remote or current directory
+        #[arg(long)]
+        infer: bool,
     },
     /// Initialize Cargo Rust project
     Cargonew {
         /// Project name
-        name: String,
-        /// Project template
-        #[arg(short, long, default_value = "bin")]
-        template: String,
+        #[arg(required_unless_present = "infer")]
+        name: Option<String>,
+        /// Infer the name and description from the git remote or current directory
+        #[arg(long)]
+        infer: bool,
+        /// Project template, `bin` or `lib` (default from config)
+        #[arg(short, long)]
+        template: Option<String>,
+        /// .gitignore template to write, e.g. `rust` or `rust+vscode` (default from config)
+        #[arg(long, visible_alias = "vcs-ignore-template", value_name = "NAME")]
+        gitignore_template: Option<String>,
     },
     /// Initialize Tauri project
     Tuarinew {
         /// Project name
-        name: String,
-        /// Frontend framework
-        #[arg(short, long, default_value = "vanilla")]
-        frontend: String,
+        #[arg(required_unless_present = "infer")]
+        name: Option<String>,
+        /// Infer the name and description from the git remote or current directory
+        #[arg(long)]
+        infer: bool,
+        /// Frontend framework: vanilla, react, vue, svelte or solid (default from config)
+        #[arg(short, long)]
+        frontend: Option<String>,
+    },
+    /// Print a shell completion script to stdout
+    #[command(after_help = "Install paths:
+  bash        post-init completions bash > ~/.local/share/bash-completion/completions/post-init
+  zsh         post-init completions zsh > ~/.zfunc/_post-init  (with ~/.zfunc in $fpath)
+  fish        post-init completions fish > ~/.config/fish/completions/post-init.fish
+  powershell  post-init completions powershell >> $PROFILE
+  elvish      post-init completions elvish >> ~/.config/elvish/rc.elv")]
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
     },
     /// Show current configuration
     Config {
         /// Show config file path
         #[arg(short, long)]
         show_path: bool,
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print a single value, e.g. `uvinit.skip_dirs`
+    Get {
+        /// Dotted key
+        key: String,
+    },
+    /// Change a single value in the config file, or in `[profiles.<NAME>]` with --profile
+    Set {
+        /// Dotted key, e.g. `uvinit.add_hatch_vcs`
+        key: String,
+        /// New value; lists take comma-separated items unless --append/--remove is given
+        value: String,
+        /// Add the value to a list
+        #[arg(long, conflicts_with = "remove")]
+        append: bool,
+        /// Remove the value from a list
+        #[arg(long)]
+        remove: bool,
     },
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
+    logging::init(cli.log_file.as_deref(), cli.verbose)?;
+    annotations::init(cli.annotations);
+    output::init(cli.format, cli.quiet);
+    log::info!(
+        "post-init {}",
+        std::env::args().skip(1).collect::<Vec<_>>().join(" ")
+    );
+
+    let check = matches!(cli.command, Commands::Uvinit { check: true, .. });
+    let result = run(cli);
+    if let Err(e) = &result {
+        log::error!("{e:#}");
+        annotations::error(None, &format!("{e:#}"));
+        // --check keeps exit status 1 for "needs changes"
+        if check {
+            eprintln!("Error: {e:?}");
+            return Ok(ExitCode::from(commands::uvinit::CHECK_FAILED));
+        }
+    }
+    result
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let policy = cli.overwrite.policy();
 
     match cli.command {
-        Commands::Uvinit { path, yes } => {
-            commands::uvinit::run_uvinit(&path, yes)?;
+        Commands::Uvinit {
+            path,
+            paths,
+            files,
+            yes,
+            out_suffix,
+            tree,
+            watch,
+            report_markdown,
+            report,
+            fix_requires_only,
+            git_add,
+            git_commit,
+            tag,
+            publish_workflow,
+            dockerfile,
+            devcontainer,
+            migrate_src_layout,
+            py_typed,
+            import_requirements,
+            migrate_setup,
+            workspace_sources,
+            type_checker,
+            dependency_updates,
+            python_version_file,
+            python_version,
+            validate,
+            override_version_source,
+            reprocess,
+            version_key_action,
+            version_backend,
+            keep_version_key,
+            parallel_walk,
+            sequential_walk,
+            no_ignore,
+            no_cache,
+            max_depth,
+            follow_symlinks,
+            no_follow_symlinks,
+            include,
+            exclude,
+            dirty,
+            next_steps,
+            dry_run,
+            check,
+            features,
+        } => {
+            let options = commands::uvinit::UvinitOptions {
+                yes,
+                out_suffix,
+                policy,
+                tree,
+                report_markdown,
+                report,
+                fix_requires_only,
+                git_add,
+                git_commit,
+                tag,
+                publish_workflow,
+                dockerfile,
+                devcontainer,
+                migrate_src_layout,
+                py_typed,
+                import_requirements,
+                migrate_setup,
+                workspace_sources,
+                type_checker,
+                dependency_updates,
+                python_version_file,
+                python_version,
+                validate,
+                override_version_source,
+                reprocess,
+                version_key_action: if keep_version_key {
+                    Some(config::VersionKeyAction::Keep)
+                } else {
+                    version_key_action
+                },
+                version_backend,
+                parallel_walk: match (parallel_walk, sequential_walk) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+                no_ignore,
+                no_cache,
+                max_depth,
+                follow_symlinks: match (follow_symlinks, no_follow_symlinks) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+                include,
+                exclude,
+                dirty,
+                next_steps,
+                dry_run: dry_run || check,
+                check,
+                features: *features,
+            };
+            let targets = match files {
+                // The confirmation prompt would read the end of the list
+                Some(list) if list.as_os_str() == "-" && !(yes || dry_run || check) => {
+                    anyhow::bail!("--files - reads stdin, so it needs --yes, --dry-run or --check")
+                }
+                Some(list) => commands::uvinit::read_file_list(&list)?,
+                None if paths.is_empty() => vec![path],
+                None => paths,
+            };
+            if watch {
+                return commands::uvinit::watch_uvinit(&targets, options, &cli.config);
+            }
+            return commands::uvinit::run_uvinit(&targets, &options, &cli.config);
+        }
+        Commands::Restore {
+            path,
+            file,
+            list,
+            yes,
+        } => {
+            let options = commands::restore::RestoreOptions {
+                file,
+                list,
+                yes,
+                policy,
+            };
+            commands::restore::run_restore(&path, &options, &cli.config)?;
+        }
+        Commands::Undo { run, list, yes } => {
+            let options = commands::undo::UndoOptions {
+                run,
+                list,
+                yes,
+                policy,
+            };
+            commands::undo::run_undo(&options)?;
+        }
+        Commands::Revert { path, yes } => {
+            commands::revert::run_revert(&path, yes, &cli.config)?;
+        }
+        Commands::Uvnew { name, infer } => {
+            let project = infer::resolve_project_info(name.as_deref(), infer)?;
+            commands::uvnew::run_uvnew(&project, &cli.config)?;
         }
-        Commands::Cargonew { name, template } => {
-            commands::cargonew::run_cargonew(&name, &template)?;
+        Commands::Cargonew {
+            name,
+            infer,
+            template,
+            gitignore_template,
+        } => {
+            let project = infer::resolve_project_info(name.as_deref(), infer)?;
+            let options = commands::cargonew::CargonewOptions {
+                template,
+                gitignore_template,
+            };
+            commands::cargonew::run_cargonew(&project, &options, &cli.config)?;
         }
-        Commands::Tuarinew { name, frontend } => {
-            commands::tuarinew::run_tuarinew(&name, &frontend)?;
+        Commands::Tuarinew {
+            name,
+            infer,
+            frontend,
+        } => {
+            let project = infer::resolve_project_info(name.as_deref(), infer)?;
+            commands::tuarinew::run_tuarinew(&project, frontend.as_deref(), &cli.config)?;
         }
-        Commands::Config { show_path } => {
-            commands::config::show_config(show_path)?;
+        Commands::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut command, name, &mut script);
+            std::io::stdout()
+                .write_all(&script)
+                .with_context(|| "Failed to write completion script")?;
         }
+        Commands::Config { show_path, action } => match action {
+            None => commands::config::show_config(show_path, &cli.config)?,
+            Some(ConfigAction::Get { key }) => commands::config::get_config(&key, &cli.config)?,
+            Some(ConfigAction::Set {
+                key,
+                value,
+                append,
+                remove,
+            }) => {
+                let edit = if append {
+                    commands::config::ConfigEdit::Append
+                } else if remove {
+                    commands::config::ConfigEdit::Remove
+                } else {
+                    commands::config::ConfigEdit::Replace
+                };
+                commands::config::set_config(&key, &value, edit, &cli.config)?;
+            }
+        },
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
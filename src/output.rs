@@ -0,0 +1,84 @@
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Version of the `--format json` document layout; bumped on breaking changes
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// How command results are written to stdout
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Progress lines for people
+    #[default]
+    Human,
+    /// A single JSON document; progress lines move to stderr
+    Json,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static QUIET: OnceLock<bool> = OnceLock::new();
+
+pub fn init(format: OutputFormat, quiet: bool) {
+    let _ = FORMAT.set(format);
+    let _ = QUIET.set(quiet);
+}
+
+pub fn is_json() -> bool {
+    FORMAT.get() == Some(&OutputFormat::Json)
+}
+
+/// Whether `-q` silenced progress output
+pub fn is_quiet() -> bool {
+    QUIET.get() == Some(&true)
+}
+
+/// Progress bar on stderr for a phase of `len` steps, or a spinner when the
+/// length is unknown; hidden with `-q` or when stderr is not a terminal
+pub fn progress(len: Option<u64>, message: &str) -> ProgressBar {
+    if is_quiet() || !std::io::stderr().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    let bar = match len {
+        Some(len) => ProgressBar::new(len).with_style(
+            ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len} ({eta})")
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+        ),
+        None => {
+            let spinner = ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {msg} ({elapsed})")
+                    .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+            );
+            spinner.enable_steady_tick(Duration::from_millis(100));
+            spinner
+        }
+    };
+    bar.with_message(message.to_string())
+}
+
+/// `println!` for human-readable progress, sent to stderr in JSON mode so
+/// stdout stays parseable, and dropped entirely with `-q`
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::output::is_quiet() {
+        } else if $crate::output::is_json() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+pub(crate) use say;
+
+/// Ask `question` on stderr and read a y/N answer from stdin
+///
+/// Unlike `say!`, this prints even with `-q`, since the run waits for the answer.
+pub fn confirm(question: &str) -> std::io::Result<bool> {
+    eprintln!("{question} (y/N)");
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase().starts_with('y'))
+}
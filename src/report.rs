@@ -0,0 +1,767 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::journal;
+use crate::layout::SrcMove;
+use crate::output::JSON_SCHEMA_VERSION;
+
+/// Final state of a discovered pyproject.toml after a run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    /// Already has project.dynamic, left untouched
+    AlreadyConfigured,
+    /// Modified in place
+    Modified,
+    /// Would be modified, but this was a dry run
+    WouldChange,
+    /// Transformed content written to a sibling file
+    Proposed(PathBuf),
+    /// Sibling file already existed and was left untouched
+    ProposalSkipped(PathBuf),
+    /// Not processed in this mode, with the reason
+    Skipped(String),
+    /// Checking or processing failed
+    Failed(String),
+}
+
+impl FileStatus {
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            FileStatus::AlreadyConfigured => "✅",
+            FileStatus::Modified | FileStatus::Proposed(_) => "🔧",
+            FileStatus::WouldChange => "📝",
+            FileStatus::ProposalSkipped(_) | FileStatus::Skipped(_) => "⏭️ ",
+            FileStatus::Failed(_) => "❌",
+        }
+    }
+
+    /// Stable machine-readable name, used in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FileStatus::AlreadyConfigured => "already_configured",
+            FileStatus::Modified => "modified",
+            FileStatus::WouldChange => "would_change",
+            FileStatus::Proposed(_) => "proposed",
+            FileStatus::ProposalSkipped(_) => "proposal_skipped",
+            FileStatus::Skipped(_) => "skipped",
+            FileStatus::Failed(_) => "failed",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            FileStatus::AlreadyConfigured => "already configured".to_string(),
+            FileStatus::Modified => "modified".to_string(),
+            FileStatus::WouldChange => "would change".to_string(),
+            FileStatus::Proposed(out) => format!("proposed -> {}", out.display()),
+            FileStatus::ProposalSkipped(out) => format!("{} already exists", out.display()),
+            FileStatus::Skipped(reason) => format!("skipped: {reason}"),
+            FileStatus::Failed(e) => format!("error: {e}"),
+        }
+    }
+}
+
+/// A single edit applied to a pyproject.toml, keyed by dotted TOML path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Set {
+        key: String,
+        value: String,
+    },
+    Append {
+        key: String,
+        value: String,
+    },
+    Replace {
+        key: String,
+        old: String,
+        value: String,
+    },
+    Remove {
+        key: String,
+    },
+}
+
+impl Change {
+    pub fn set(key: &str, value: &str) -> Self {
+        Change::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn append(key: &str, value: &str) -> Self {
+        Change::Append {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn replace(key: &str, old: &str, value: &str) -> Self {
+        Change::Replace {
+            key: key.to_string(),
+            old: old.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn remove(key: &str) -> Self {
+        Change::Remove {
+            key: key.to_string(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Change::Set { key, value } => json!({ "op": "set", "key": key, "value": value }),
+            Change::Append { key, value } => json!({ "op": "append", "key": key, "value": value }),
+            Change::Replace { key, old, value } => {
+                json!({ "op": "replace", "key": key, "old": old, "value": value })
+            }
+            Change::Remove { key } => json!({ "op": "remove", "key": key }),
+        }
+    }
+
+    /// Dotted TOML path the change applies to
+    pub fn key(&self) -> &str {
+        match self {
+            Change::Set { key, .. }
+            | Change::Append { key, .. }
+            | Change::Replace { key, .. }
+            | Change::Remove { key } => key,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Set { key, value } => write!(f, "{key} = {value}"),
+            Change::Append { key, value } => write!(f, "{key} += {value}"),
+            Change::Replace { key, old, value } => write!(f, "{key}: {old} -> {value}"),
+            Change::Remove { key } => write!(f, "removed {key}"),
+        }
+    }
+}
+
+/// Outcome for a single pyproject.toml
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub status: FileStatus,
+    pub changes: Vec<Change>,
+    pub warnings: Vec<String>,
+    /// Unified diff of the pending edit, only set in dry runs
+    pub diff: Option<String>,
+    /// Package moved into `src/` along with the edit
+    pub moved: Option<SrcMove>,
+    /// Test files rewritten for `moved`, as journal entries
+    pub rewritten: Vec<journal::Entry>,
+}
+
+impl FileReport {
+    pub fn new(path: &Path, status: FileStatus) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            status,
+            changes: Vec::new(),
+            warnings: Vec::new(),
+            diff: None,
+            moved: None,
+            rewritten: Vec::new(),
+        }
+    }
+
+    /// Whether the file's version now comes from git tags
+    pub fn enables_vcs_versioning(&self) -> bool {
+        const VERSION_SOURCE_KEYS: &[&str] = &[
+            "project.dynamic",
+            "tool.hatch.version.source",
+            "tool.poetry-dynamic-versioning.enable",
+            "tool.setuptools_scm",
+            "tool.pdm.version.source",
+        ];
+        self.changes
+            .iter()
+            .any(|c| VERSION_SOURCE_KEYS.contains(&c.key()))
+    }
+}
+
+/// Follow-up tasks implied by the changes in `reports`, most important first
+pub fn next_steps(reports: &[FileReport]) -> Vec<String> {
+    let changed = |key: &str| {
+        reports
+            .iter()
+            .flat_map(|r| &r.changes)
+            .any(|c| c.key() == key)
+    };
+    let mut steps = Vec::new();
+
+    if reports.iter().any(FileReport::enables_vcs_versioning) {
+        steps.push(
+            "Create a version tag (e.g. `git tag v0.1.0`) so the version can be derived from git"
+                .to_string(),
+        );
+        steps.push(
+            "Make CI check out full history and tags (`fetch-depth: 0` for actions/checkout)"
+                .to_string(),
+        );
+    }
+    if changed("tool.poetry-dynamic-versioning.enable") {
+        steps.push(
+            "Install the plugin for local builds: `poetry self add \"poetry-dynamic-versioning[plugin]\"`"
+                .to_string(),
+        );
+    }
+    if changed("tool.setuptools_scm.fallback_version") {
+        steps.push(
+            "Check that tool.setuptools_scm.fallback_version is only used outside git checkouts"
+                .to_string(),
+        );
+    }
+    if changed("build-system.requires") {
+        steps.push("Run `uv sync` to rebuild with the new build requirements".to_string());
+    }
+    if changed("tool.pytest.ini_options.asyncio_mode") {
+        steps.push("Add pytest-asyncio to your dev dependencies".to_string());
+    }
+    if changed("tool.mypy.strict") || changed("tool.mypy.python_version") {
+        steps.push("Add mypy to your dev dependencies and run it in CI".to_string());
+    }
+    if changed("tool.coverage.run.source") || changed("tool.coverage.run.omit") {
+        steps.push("Add coverage (or pytest-cov) to your dev dependencies".to_string());
+    }
+    if reports
+        .iter()
+        .any(|r| matches!(r.status, FileStatus::Proposed(_)))
+    {
+        steps.push("Review the proposed files and move them over pyproject.toml".to_string());
+    }
+
+    steps
+}
+
+#[derive(Default)]
+struct TreeNode {
+    files: Vec<(String, FileStatus)>,
+    children: BTreeMap<String, TreeNode>,
+}
+
+#[derive(Default)]
+struct Totals {
+    files: usize,
+    changed: usize,
+    failed: usize,
+}
+
+impl TreeNode {
+    fn totals(&self) -> Totals {
+        let mut totals = Totals::default();
+        for (_, status) in &self.files {
+            totals.files += 1;
+            match status {
+                FileStatus::Modified | FileStatus::Proposed(_) | FileStatus::WouldChange => {
+                    totals.changed += 1
+                }
+                FileStatus::Failed(_) => totals.failed += 1,
+                _ => {}
+            }
+        }
+        for child in self.children.values() {
+            let child_totals = child.totals();
+            totals.files += child_totals.files;
+            totals.changed += child_totals.changed;
+            totals.failed += child_totals.failed;
+        }
+        totals
+    }
+
+    fn render(&self, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        for (name, status) in &self.files {
+            out.push_str(&format!(
+                "{indent}{} {name} ({})\n",
+                status.glyph(),
+                status.describe()
+            ));
+        }
+        for (name, child) in &self.children {
+            let totals = child.totals();
+            out.push_str(&format!(
+                "{indent}📁 {name}/ ({} file(s), {} changed, {} failed)\n",
+                totals.files, totals.changed, totals.failed
+            ));
+            child.render(depth + 1, out);
+        }
+    }
+}
+
+/// Render reports as a directory tree relative to `root`, with per-directory subtotals
+pub fn render_tree(root: &Path, reports: &[FileReport]) -> String {
+    let mut tree = TreeNode::default();
+
+    for report in reports {
+        let relative = report.path.strip_prefix(root).unwrap_or(&report.path);
+        let mut node = &mut tree;
+        if let Some(parent) = relative.parent() {
+            for component in parent.components() {
+                let name = component.as_os_str().to_string_lossy().into_owned();
+                node = node.children.entry(name).or_default();
+            }
+        }
+        let file_name = relative
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        node.files.push((file_name, report.status.clone()));
+    }
+
+    let totals = tree.totals();
+    let mut out = format!(
+        "📁 {} ({} file(s), {} changed, {} failed)\n",
+        root.display(),
+        totals.files,
+        totals.changed,
+        totals.failed
+    );
+    tree.render(1, &mut out);
+    out
+}
+
+/// One-line totals printed at the end of a run, even with `-q`
+pub fn render_summary(reports: &[FileReport]) -> String {
+    let tree = TreeNode {
+        files: reports
+            .iter()
+            .map(|r| (String::new(), r.status.clone()))
+            .collect(),
+        ..Default::default()
+    };
+    let totals = tree.totals();
+    format!(
+        "📊 {} file(s), {} changed, {} failed",
+        totals.files, totals.changed, totals.failed
+    )
+}
+
+/// A discovered pyproject.toml in the plan shown before confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanRow {
+    /// Root of the git repository holding the file; `None` outside git
+    pub repo: Option<PathBuf>,
+    /// Project name and directory relative to the repository root
+    pub package: String,
+    pub state: String,
+    /// What the run will do to the file; empty when it is left alone
+    pub actions: Vec<String>,
+}
+
+/// Render the plan as an aligned table grouped by repository, naming each
+/// repository only on its first row
+pub fn render_plan(rows: &[PlanRow]) -> String {
+    let mut groups: BTreeMap<String, Vec<&PlanRow>> = BTreeMap::new();
+    for row in rows {
+        let repo = row.repo.as_ref().map_or_else(
+            || "(no repository)".to_string(),
+            |r| r.display().to_string(),
+        );
+        groups.entry(repo).or_default().push(row);
+    }
+
+    let mut lines = vec![[
+        "Repository".to_string(),
+        "Package".to_string(),
+        "State".to_string(),
+        "Planned actions".to_string(),
+    ]];
+    for (repo, rows) in groups {
+        for (i, row) in rows.into_iter().enumerate() {
+            let actions = match row.actions.is_empty() {
+                true => "-".to_string(),
+                false => row.actions.join(", "),
+            };
+            let repo = if i == 0 { repo.clone() } else { String::new() };
+            lines.push([repo, row.package.clone(), row.state.clone(), actions]);
+        }
+    }
+
+    let mut widths = [0; 4];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let rule = widths.map(|width| "-".repeat(width));
+    lines.insert(1, rule);
+
+    let mut out = String::new();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str("  ");
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Render reports as a GitHub-flavored Markdown summary, e.g. for a PR description
+pub fn render_markdown(root: &Path, reports: &[FileReport]) -> String {
+    let mut out = String::from("## post-init uvinit report\n\n");
+    out.push_str("| File | Status | Changes |\n");
+    out.push_str("| --- | --- | --- |\n");
+
+    for report in reports {
+        let relative = report.path.strip_prefix(root).unwrap_or(&report.path);
+        let changes = if report.changes.is_empty() {
+            "-".to_string()
+        } else {
+            report
+                .changes
+                .iter()
+                .map(|c| format!("`{}`", c.to_string().replace('|', "\\|")))
+                .collect::<Vec<_>>()
+                .join("<br>")
+        };
+        out.push_str(&format!(
+            "| `{}` | {} {} | {} |\n",
+            relative.display(),
+            report.status.glyph().trim(),
+            report.status.describe().replace('|', "\\|"),
+            changes
+        ));
+    }
+
+    let changed = reports
+        .iter()
+        .filter(|r| {
+            matches!(
+                r.status,
+                FileStatus::Modified | FileStatus::Proposed(_) | FileStatus::WouldChange
+            )
+        })
+        .count();
+    let failed = reports
+        .iter()
+        .filter(|r| matches!(r.status, FileStatus::Failed(_)))
+        .count();
+    let total_changes: usize = reports.iter().map(|r| r.changes.len()).sum();
+    out.push_str(&format!(
+        "\n**Totals:** {} file(s), {} changed, {} failed, {} change(s) applied\n",
+        reports.len(),
+        changed,
+        failed,
+        total_changes
+    ));
+
+    out
+}
+
+/// Render reports as the `--format json` document
+pub fn render_json(root: &Path, reports: &[FileReport]) -> Value {
+    let files: Vec<Value> = reports
+        .iter()
+        .map(|report| {
+            let (reason, output) = match &report.status {
+                FileStatus::Skipped(reason) | FileStatus::Failed(reason) => (Some(reason), None),
+                FileStatus::Proposed(out) | FileStatus::ProposalSkipped(out) => (None, Some(out)),
+                _ => (None, None),
+            };
+            json!({
+                "path": report.path,
+                "status": report.status.kind(),
+                "reason": reason,
+                "output": output,
+                "changes": report.changes.iter().map(Change::to_json).collect::<Vec<_>>(),
+                "warnings": report.warnings,
+                "diff": report.diff,
+            })
+        })
+        .collect();
+
+    let tree = TreeNode {
+        files: reports
+            .iter()
+            .map(|r| (String::new(), r.status.clone()))
+            .collect(),
+        ..Default::default()
+    };
+    let totals = tree.totals();
+
+    json!({
+        "version": JSON_SCHEMA_VERSION,
+        "command": "uvinit",
+        "root": root,
+        "files": files,
+        "totals": {
+            "files": totals.files,
+            "changed": totals.changed,
+            "failed": totals.failed,
+        },
+    })
+}
+
+/// Render the errors of a run as a JSON document grouped by file, with
+/// errors outside any file under a `null` path
+pub fn render_error_report(
+    root: &Path,
+    reports: &[FileReport],
+    errors: &[(Option<PathBuf>, String)],
+) -> Value {
+    let mut by_file: BTreeMap<Option<&Path>, Vec<&str>> = BTreeMap::new();
+    for (file, message) in errors {
+        let messages = by_file.entry(file.as_deref()).or_default();
+        if !messages.contains(&message.as_str()) {
+            messages.push(message);
+        }
+    }
+    let files: Vec<Value> = by_file
+        .into_iter()
+        .map(|(file, messages)| {
+            let status = file.and_then(|file| {
+                reports
+                    .iter()
+                    .find(|report| report.path == file)
+                    .map(|report| report.status.kind())
+            });
+            json!({ "path": file, "status": status, "errors": messages })
+        })
+        .collect();
+
+    let tree = TreeNode {
+        files: reports
+            .iter()
+            .map(|r| (String::new(), r.status.clone()))
+            .collect(),
+        ..Default::default()
+    };
+    let totals = tree.totals();
+
+    json!({
+        "version": JSON_SCHEMA_VERSION,
+        "command": "uvinit",
+        "root": root,
+        "totals": {
+            "files": totals.files,
+            "changed": totals.changed,
+            "failed": totals.failed,
+            "errors": errors.len(),
+        },
+        "files": files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_tree() {
+        let root = Path::new("repo");
+        let reports = vec![
+            FileReport::new(&root.join("pyproject.toml"), FileStatus::AlreadyConfigured),
+            FileReport::new(
+                &root.join("packages/a/pyproject.toml"),
+                FileStatus::Modified,
+            ),
+            FileReport::new(
+                &root.join("packages/b/pyproject.toml"),
+                FileStatus::Failed("bad toml".to_string()),
+            ),
+        ];
+
+        let tree = render_tree(root, &reports);
+
+        assert_eq!(
+            tree,
+            "📁 repo (3 file(s), 1 changed, 1 failed)\n\
+             \x20 ✅ pyproject.toml (already configured)\n\
+             \x20 📁 packages/ (2 file(s), 1 changed, 1 failed)\n\
+             \x20   📁 a/ (1 file(s), 1 changed, 0 failed)\n\
+             \x20     🔧 pyproject.toml (modified)\n\
+             \x20   📁 b/ (1 file(s), 0 changed, 1 failed)\n\
+             \x20     ❌ pyproject.toml (error: bad toml)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_error_report() {
+        let root = Path::new("repo");
+        let reports = vec![
+            FileReport::new(&root.join("a/pyproject.toml"), FileStatus::Modified),
+            FileReport::new(
+                &root.join("b/pyproject.toml"),
+                FileStatus::Failed("bad toml".to_string()),
+            ),
+        ];
+        let errors = vec![
+            (
+                Some(root.join("b/pyproject.toml")),
+                "Failed to check: bad toml".to_string(),
+            ),
+            (
+                Some(root.join("a/py.typed")),
+                "Failed to write file".to_string(),
+            ),
+            (None, "git commit failed".to_string()),
+            (
+                Some(root.join("b/pyproject.toml")),
+                "Failed to check: bad toml".to_string(),
+            ),
+        ];
+
+        let document = render_error_report(root, &reports, &errors);
+        assert_eq!(
+            document["totals"],
+            json!({ "files": 2, "changed": 1, "failed": 1, "errors": 4 })
+        );
+        assert_eq!(
+            document["files"],
+            json!([
+                { "path": null, "status": null, "errors": ["git commit failed"] },
+                { "path": "repo/a/py.typed", "status": null, "errors": ["Failed to write file"] },
+                {
+                    "path": "repo/b/pyproject.toml",
+                    "status": "failed",
+                    "errors": ["Failed to check: bad toml"],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let reports = vec![
+            FileReport::new(Path::new("a/pyproject.toml"), FileStatus::Modified),
+            FileReport::new(Path::new("b/pyproject.toml"), FileStatus::AlreadyConfigured),
+            FileReport::new(
+                Path::new("c/pyproject.toml"),
+                FileStatus::Failed("bad toml".to_string()),
+            ),
+        ];
+        assert_eq!(
+            render_summary(&reports),
+            "📊 3 file(s), 1 changed, 1 failed"
+        );
+    }
+
+    #[test]
+    fn test_render_plan() {
+        let row = |repo: Option<&str>, package: &str, state: &str, actions: &[&str]| PlanRow {
+            repo: repo.map(PathBuf::from),
+            package: package.to_string(),
+            state: state.to_string(),
+            actions: actions.iter().map(|a| a.to_string()).collect(),
+        };
+        let rows = vec![
+            row(
+                Some("/b"),
+                "api (packages/api)",
+                "no project.dynamic",
+                &["dynamic version", "ruff"],
+            ),
+            row(None, "tool (.)", "skipped: opted out", &[]),
+            row(Some("/b"), "b (.)", "already configured", &[]),
+            row(Some("/a"), "a (.)", "has project.dynamic", &["reprocess"]),
+        ];
+        assert_eq!(
+            render_plan(&rows),
+            "  Repository       Package             State                Planned actions\n\
+             \x20 ---------------  ------------------  -------------------  ---------------------\n\
+             \x20 (no repository)  tool (.)            skipped: opted out   -\n\
+             \x20 /a               a (.)               has project.dynamic  reprocess\n\
+             \x20 /b               api (packages/api)  no project.dynamic   dynamic version, ruff\n\
+             \x20                  b (.)               already configured   -\n"
+        );
+    }
+
+    #[test]
+    fn test_next_steps() {
+        let mut migrated = FileReport::new(Path::new("a/pyproject.toml"), FileStatus::Modified);
+        migrated.changes = vec![
+            Change::set("project.dynamic", r#"["version"]"#),
+            Change::append("build-system.requires", "hatch-vcs"),
+        ];
+        let steps = next_steps(&[migrated]);
+        assert_eq!(steps.len(), 3);
+        assert!(steps[1].contains("fetch-depth: 0"));
+        assert!(steps[2].contains("uv sync"));
+
+        let mut bandit_only = FileReport::new(Path::new("b/pyproject.toml"), FileStatus::Modified);
+        bandit_only.changes = vec![Change::append("tool.bandit.skips", "B101")];
+        assert!(next_steps(&[bandit_only]).is_empty());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let root = Path::new("repo");
+        let mut modified = FileReport::new(&root.join("a/pyproject.toml"), FileStatus::Modified);
+        modified.changes = vec![
+            Change::remove("project.version"),
+            Change::replace("build-system.requires", "hatch-vcs", "hatch-vcs==0.3"),
+        ];
+        let reports = vec![
+            modified,
+            FileReport::new(
+                &root.join("b/pyproject.toml"),
+                FileStatus::Failed("bad toml".to_string()),
+            ),
+        ];
+
+        let document = render_json(root, &reports);
+
+        assert_eq!(document["version"], JSON_SCHEMA_VERSION);
+        assert_eq!(document["files"][0]["status"], "modified");
+        assert_eq!(
+            document["files"][0]["changes"][0],
+            json!({ "op": "remove", "key": "project.version" })
+        );
+        assert_eq!(
+            document["files"][0]["changes"][1],
+            json!({
+                "op": "replace",
+                "key": "build-system.requires",
+                "old": "hatch-vcs",
+                "value": "hatch-vcs==0.3",
+            })
+        );
+        assert_eq!(document["files"][1]["status"], "failed");
+        assert_eq!(document["files"][1]["reason"], "bad toml");
+        assert_eq!(
+            document["totals"],
+            json!({ "files": 2, "changed": 1, "failed": 1 })
+        );
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let root = Path::new("repo");
+        let mut modified = FileReport::new(&root.join("a/pyproject.toml"), FileStatus::Modified);
+        modified.changes = vec![
+            Change::remove("project.version"),
+            Change::append("build-system.requires", "hatch-vcs"),
+        ];
+        let reports = vec![
+            modified,
+            FileReport::new(
+                &root.join("b/pyproject.toml"),
+                FileStatus::AlreadyConfigured,
+            ),
+        ];
+
+        let markdown = render_markdown(root, &reports);
+
+        assert!(markdown.contains(
+            "| `a/pyproject.toml` | 🔧 modified | `removed project.version`<br>`build-system.requires += hatch-vcs` |"
+        ));
+        assert!(markdown.contains("| `b/pyproject.toml` | ✅ already configured | - |"));
+        assert!(
+            markdown.contains("**Totals:** 2 file(s), 1 changed, 0 failed, 2 change(s) applied")
+        );
+    }
+}
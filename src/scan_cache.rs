@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::files::write_atomic;
+
+/// Environment variable naming the scan cache directory instead of the cache directory
+pub const CACHE_DIR_ENV_VAR: &str = "POST_INIT_CACHE_DIR";
+
+/// Bumped whenever what "already configured" means changes, dropping old caches
+const CACHE_VERSION: u32 = 3;
+
+/// What a pyproject.toml looked like when it was last found already configured
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Entry {
+    len: u64,
+    /// Modification time as nanoseconds since the epoch
+    modified: u64,
+    sha256: String,
+}
+
+impl Entry {
+    fn of(file: &Path) -> Option<Self> {
+        let content = fs::read(file).ok()?;
+        Some(Self {
+            len: content.len() as u64,
+            modified: modified(file)?,
+            sha256: sha256(&content),
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Contents {
+    version: u32,
+    /// Fingerprint of the settings the files were found configured under
+    settings: String,
+    /// Keyed by canonical path
+    files: BTreeMap<PathBuf, Entry>,
+}
+
+fn sha256(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn modified(file: &Path) -> Option<u64> {
+    let modified = fs::metadata(file).ok()?.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    u64::try_from(nanos).ok()
+}
+
+/// Files of one repository that were already configured, so later scans
+/// can skip parsing them while they stay unchanged
+#[derive(Debug)]
+pub struct ScanCache {
+    path: PathBuf,
+    contents: Contents,
+}
+
+impl ScanCache {
+    /// The cache for the repository at `repo_root` in POST_INIT_CACHE_DIR,
+    /// or `~/.cache/post-init/scan`
+    pub fn open(repo_root: &Path, settings: &str) -> Result<Self> {
+        let root = match std::env::var_os(CACHE_DIR_ENV_VAR).filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::cache_dir()
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Could not find a cache directory for the scan cache; set {CACHE_DIR_ENV_VAR}"
+                    )
+                })?
+                .join("post-init")
+                .join("scan"),
+        };
+        Ok(Self::in_dir(&root, repo_root, settings))
+    }
+
+    /// The cache for the repository at `repo_root` in `cache_root`
+    pub fn in_dir(cache_root: &Path, repo_root: &Path, settings: &str) -> Self {
+        let repo_root = fs::canonicalize(repo_root).unwrap_or_else(|_| repo_root.to_path_buf());
+        let digest = sha256(repo_root.to_string_lossy().as_bytes());
+        Self::load(cache_root.join(format!("{}.json", &digest[..16])), settings)
+    }
+
+    /// The cache at `path`; empty when missing, outdated, unreadable or
+    /// written under other `settings`
+    fn load(path: PathBuf, settings: &str) -> Self {
+        let contents = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Contents>(&json).ok())
+            .filter(|contents| contents.version == CACHE_VERSION && contents.settings == settings)
+            .unwrap_or_else(|| Contents {
+                version: CACHE_VERSION,
+                settings: settings.to_string(),
+                ..Default::default()
+            });
+        Self { path, contents }
+    }
+
+    fn key(file: &Path) -> PathBuf {
+        fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())
+    }
+
+    /// Whether `file` was already configured and has not changed since; an
+    /// unchanged size and modification time are trusted, anything else is
+    /// settled by the content hash
+    pub fn is_configured(&self, file: &Path) -> bool {
+        let Some(entry) = self.contents.files.get(&Self::key(file)) else {
+            return false;
+        };
+        let Ok(metadata) = fs::metadata(file) else {
+            return false;
+        };
+        if metadata.len() != entry.len {
+            return false;
+        }
+        modified(file) == Some(entry.modified)
+            || fs::read(file).is_ok_and(|content| sha256(&content) == entry.sha256)
+    }
+
+    /// Remember `file` as already configured in its current state
+    pub fn mark_configured(&mut self, file: &Path) {
+        match Entry::of(file) {
+            Some(entry) => {
+                self.contents.files.insert(Self::key(file), entry);
+            }
+            None => self.forget(file),
+        }
+    }
+
+    pub fn forget(&mut self, file: &Path) {
+        self.contents.files.remove(&Self::key(file));
+    }
+
+    /// Write the cache back, dropping files that no longer exist
+    pub fn save(&mut self) -> Result<()> {
+        self.contents.files.retain(|file, _| file.is_file());
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.contents)
+            .with_context(|| "Failed to serialize scan cache")?;
+        write_atomic(&self.path, &json)
+            .with_context(|| format!("Failed to write scan cache: {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache_path = temp_dir.path().join("cache").join("repo.json");
+        let file = temp_dir.path().join("pyproject.toml");
+        fs::write(&file, "[project]\ndynamic = [\"version\"]\n")?;
+
+        let mut cache = ScanCache::load(cache_path.clone(), "a");
+        assert!(!cache.is_configured(&file));
+        cache.mark_configured(&file);
+        assert!(cache.is_configured(&file));
+        cache.save()?;
+
+        let mut cache = ScanCache::load(cache_path.clone(), "a");
+        assert!(cache.is_configured(&file));
+
+        // Touched but unchanged
+        let touch = |file: &Path, secs: u64| -> Result<()> {
+            fs::File::options()
+                .write(true)
+                .open(file)?
+                .set_modified(UNIX_EPOCH + Duration::from_secs(secs))?;
+            Ok(())
+        };
+        touch(&file, 1)?;
+        assert!(cache.is_configured(&file));
+
+        // Same size, new content
+        fs::write(&file, "[project]\ndynamic = [\"VERSION\"]\n")?;
+        touch(&file, 2)?;
+        assert!(!cache.is_configured(&file));
+        cache.forget(&file);
+        cache.save()?;
+
+        // A cache written under other settings is ignored
+        fs::write(&file, "[project]\ndynamic = [\"version\"]\n")?;
+        cache.mark_configured(&file);
+        cache.save()?;
+        assert!(ScanCache::load(cache_path.clone(), "a").is_configured(&file));
+        assert!(!ScanCache::load(cache_path.clone(), "b").is_configured(&file));
+
+        // A cache from another version is ignored
+        fs::write(
+            &cache_path,
+            "{\"version\": 0, \"settings\": \"a\", \"files\": {}}",
+        )?;
+        assert_eq!(
+            ScanCache::load(cache_path, "a").contents.version,
+            CACHE_VERSION
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+use anyhow::Result;
+
+/// .gitignore fragments embedded in the binary, keyed by ecosystem name
+const GITIGNORE_TEMPLATES: &[(&str, &str)] = &[
+    ("rust", include_str!("templates/gitignore/rust.gitignore")),
+    (
+        "python",
+        include_str!("templates/gitignore/python.gitignore"),
+    ),
+    ("node", include_str!("templates/gitignore/node.gitignore")),
+    (
+        "vscode",
+        include_str!("templates/gitignore/vscode.gitignore"),
+    ),
+    (
+        "jetbrains",
+        include_str!("templates/gitignore/jetbrains.gitignore"),
+    ),
+    ("macos", include_str!("templates/gitignore/macos.gitignore")),
+];
+
+/// LICENSE texts embedded in the binary, keyed by SPDX identifier; `{{year}}`
+/// and `{{author}}` fill in the copyright line where the license has one
+const LICENSE_TEMPLATES: &[(&str, &str)] = &[
+    ("MIT", include_str!("templates/licenses/MIT.txt")),
+    (
+        "Apache-2.0",
+        include_str!("templates/licenses/Apache-2.0.txt"),
+    ),
+    (
+        "BSD-2-Clause",
+        include_str!("templates/licenses/BSD-2-Clause.txt"),
+    ),
+    (
+        "BSD-3-Clause",
+        include_str!("templates/licenses/BSD-3-Clause.txt"),
+    ),
+    ("ISC", include_str!("templates/licenses/ISC.txt")),
+    ("MPL-2.0", include_str!("templates/licenses/MPL-2.0.txt")),
+    (
+        "Unlicense",
+        include_str!("templates/licenses/Unlicense.txt"),
+    ),
+];
+
+/// The LICENSE text for the SPDX identifier `spdx`, or `None` for expressions
+/// and licenses without an embedded text
+pub fn license(spdx: &str) -> Option<&'static str> {
+    LICENSE_TEMPLATES
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(spdx.trim()))
+        .map(|(_, text)| *text)
+}
+
+/// GitHub Actions workflow that tests on every supported Python and publishes
+/// to PyPI with trusted publishing when a version tag is pushed
+pub const PUBLISH_WORKFLOW: &str = include_str!("templates/workflows/publish.yml");
+
+/// Dependabot config updating pip requirements in `{{directories}}` and
+/// GitHub Actions weekly
+pub const DEPENDABOT: &str = include_str!("templates/dependencies/dependabot.yml");
+
+/// Renovate config updating pyproject.toml files matching `{{file_patterns}}`,
+/// their uv.lock files and GitHub Actions
+pub const RENOVATE: &str = include_str!("templates/dependencies/renovate.json");
+
+/// Multi-stage Dockerfile that installs the project with `uv sync --frozen`
+/// and runs `{{command}}` on a slim `python:{{python_version}}` image
+pub const DOCKERFILE: &str = include_str!("templates/docker/Dockerfile");
+
+/// Dev container on a `{{python_version}}` Python image with uv, running
+/// `uv sync` once created and recommending `{{extensions}}` (a JSON array)
+pub const DEVCONTAINER: &str = include_str!("templates/devcontainer/devcontainer.json");
+
+/// `__main__.py` for a package whose console script has no module to run yet
+pub const MAIN_MODULE: &str = include_str!("templates/python/__main__.py");
+
+/// Replace each `{{key}}` in `template` with its value
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |out, (key, value)| {
+            out.replace(&format!("{{{{{key}}}}}"), value)
+        })
+}
+
+/// pre-commit hooks uvinit knows how to configure, as (hook id, repository, rev);
+/// `pre-commit autoupdate` moves the revs forward
+const PRECOMMIT_HOOKS: &[(&str, &str, &str)] = &[
+    (
+        "ruff",
+        "https://github.com/astral-sh/ruff-pre-commit",
+        "v0.14.0",
+    ),
+    (
+        "ruff-format",
+        "https://github.com/astral-sh/ruff-pre-commit",
+        "v0.14.0",
+    ),
+    (
+        "uv-lock",
+        "https://github.com/astral-sh/uv-pre-commit",
+        "0.9.2",
+    ),
+    (
+        "trailing-whitespace",
+        "https://github.com/pre-commit/pre-commit-hooks",
+        "v6.0.0",
+    ),
+    (
+        "end-of-file-fixer",
+        "https://github.com/pre-commit/pre-commit-hooks",
+        "v6.0.0",
+    ),
+    (
+        "check-toml",
+        "https://github.com/pre-commit/pre-commit-hooks",
+        "v6.0.0",
+    ),
+    (
+        "check-yaml",
+        "https://github.com/pre-commit/pre-commit-hooks",
+        "v6.0.0",
+    ),
+    (
+        "commitizen",
+        "https://github.com/commitizen-tools/commitizen",
+        "v4.9.1",
+    ),
+];
+
+/// Build a .pre-commit-config.yaml running `hooks`, grouped by repository in
+/// the order they are first listed
+pub fn precommit_config(hooks: &[String]) -> Result<String> {
+    let mut repos: Vec<(&str, &str, Vec<&str>)> = Vec::new();
+
+    for hook in hooks {
+        let (id, repo, rev) = PRECOMMIT_HOOKS
+            .iter()
+            .find(|(id, _, _)| id == hook)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown pre-commit hook '{}'. Available: {}",
+                    hook,
+                    PRECOMMIT_HOOKS
+                        .iter()
+                        .map(|(id, _, _)| *id)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+        match repos.iter_mut().find(|(url, _, _)| url == repo) {
+            Some((_, _, ids)) if ids.contains(id) => {}
+            Some((_, _, ids)) => ids.push(id),
+            None => repos.push((repo, rev, vec![id])),
+        }
+    }
+
+    let mut out = String::new();
+    // commitizen checks messages in the commit-msg stage, which `pre-commit
+    // install` only sets up when asked to
+    if repos.iter().any(|(_, _, ids)| ids.contains(&"commitizen")) {
+        out.push_str("default_install_hook_types: [pre-commit, commit-msg]\n");
+    }
+    out.push_str("repos:\n");
+    for (repo, rev, ids) in repos {
+        out.push_str(&format!("  - repo: {repo}\n    rev: {rev}\n    hooks:\n"));
+        for id in ids {
+            out.push_str(&format!("      - id: {id}\n"));
+        }
+    }
+    Ok(out)
+}
+
+/// Build a .gitignore from a template name; `+` combines fragments, e.g. `rust+vscode`
+pub fn gitignore(name: &str) -> Result<String> {
+    let mut sections = Vec::new();
+
+    for part in name.split('+').map(str::trim) {
+        let template = GITIGNORE_TEMPLATES
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(part))
+            .map(|(_, content)| content.trim_end())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown .gitignore template '{}'. Available: {}",
+                    part,
+                    GITIGNORE_TEMPLATES
+                        .iter()
+                        .map(|(key, _)| *key)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+        if !sections.contains(&template) {
+            sections.push(template);
+        }
+    }
+
+    Ok(sections.join("\n\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_templates() -> Result<()> {
+        let rust = gitignore("rust")?;
+        assert!(rust.starts_with("# Rust\n/target/"));
+
+        let combined = gitignore("rust+VSCode")?;
+        assert!(combined.contains("/target/"));
+        assert!(combined.contains("\n\n# VS Code\n.vscode/*"));
+        assert!(combined.ends_with("*.code-workspace\n"));
+
+        assert_eq!(gitignore("rust+rust")?, rust);
+        assert!(gitignore("rust+cobol").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_license_templates() {
+        let mit = render(
+            license("mit").unwrap(),
+            &[("year", "2026"), ("author", "Ada Lovelace")],
+        );
+        assert!(mit.starts_with("MIT License\n\nCopyright (c) 2026 Ada Lovelace\n"));
+        assert!(
+            license("Apache-2.0")
+                .unwrap()
+                .contains("Version 2.0, January 2004")
+        );
+        assert_eq!(license("MIT OR Apache-2.0"), None);
+        assert_eq!(license("GPL-3.0-only"), None);
+    }
+
+    #[test]
+    fn test_render_publish_workflow() {
+        let workflow = render(
+            PUBLISH_WORKFLOW,
+            &[
+                ("name", "demo"),
+                ("tag_pattern", "v*"),
+                ("python_versions", "\"3.12\", \"3.13\""),
+                ("working_directory", "."),
+            ],
+        );
+        assert!(workflow.starts_with("name: Publish demo\n"));
+        assert!(workflow.contains("python-version: [\"3.12\", \"3.13\"]"));
+        // GitHub expressions are left alone
+        assert!(workflow.contains("${{ matrix.python-version }}"));
+        assert!(!workflow.contains("{{name}}"));
+    }
+
+    #[test]
+    fn test_precommit_config() -> Result<()> {
+        let hooks = ["ruff", "uv-lock", "ruff-format", "ruff"].map(String::from);
+        assert_eq!(
+            precommit_config(&hooks)?,
+            "repos:\n\
+             \x20 - repo: https://github.com/astral-sh/ruff-pre-commit\n\
+             \x20   rev: v0.14.0\n\
+             \x20   hooks:\n\
+             \x20     - id: ruff\n\
+             \x20     - id: ruff-format\n\
+             \x20 - repo: https://github.com/astral-sh/uv-pre-commit\n\
+             \x20   rev: 0.9.2\n\
+             \x20   hooks:\n\
+             \x20     - id: uv-lock\n"
+        );
+
+        assert!(
+            precommit_config(&["ruff".to_string(), "commitizen".to_string()])?
+                .starts_with("default_install_hook_types: [pre-commit, commit-msg]\nrepos:\n")
+        );
+
+        let error = precommit_config(&["black".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("ruff, ruff-format, uv-lock"));
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+"""Command-line entry point for {{name}}."""
+
+
+def {{function}}() -> None:
+    print("Hello from {{name}}!")
+
+
+if __name__ == "__main__":
+    {{function}}()
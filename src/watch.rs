@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Paths that `event` creates or moves into place
+fn arrived_paths(event: &Event) -> &[PathBuf] {
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => &event.paths,
+        // Only the destination of a rename arrived
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => event
+            .paths
+            .last()
+            .map(std::slice::from_ref)
+            .unwrap_or_default(),
+        _ => &[],
+    }
+}
+
+/// Files named `file_name` among `paths`, or below those that are directories
+fn arrived_files(paths: &[PathBuf], file_name: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = paths
+        .iter()
+        .flat_map(|path| {
+            if path.is_dir() {
+                files_below(path, file_name)
+            } else if path.is_file() && path.file_name().is_some_and(|name| name == file_name) {
+                vec![path.clone()]
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Files named `file_name` anywhere below `dir`, which may have filled up
+/// before the watcher saw it
+fn files_below(dir: &Path, file_name: &str) -> Vec<PathBuf> {
+    WalkBuilder::new(dir)
+        .standard_filters(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| entry.file_name() == file_name)
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Watch `roots` until the process is stopped, calling `on_batch` with the
+/// files named `file_name` that arrive below them
+///
+/// A batch closes once no event came in for `settle`, so files that are
+/// still being written get a chance to be complete.
+pub fn watch(
+    roots: &[PathBuf],
+    file_name: &str,
+    settle: Duration,
+    mut on_batch: impl FnMut(Vec<PathBuf>),
+) -> Result<()> {
+    let (sender, events) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).with_context(|| "Failed to start file watcher")?;
+    for root in roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", root.display()))?;
+    }
+
+    loop {
+        let first = events
+            .recv()
+            .with_context(|| "File watcher stopped unexpectedly")?;
+        let mut arrived = Vec::new();
+        let mut collect = |event: notify::Result<Event>| match event {
+            Ok(event) => arrived.extend_from_slice(arrived_paths(&event)),
+            Err(e) => log::warn!("File watcher error: {e}"),
+        };
+        collect(first);
+        while let Ok(event) = events.recv_timeout(settle) {
+            collect(event);
+        }
+
+        // Directories are searched only now, once whatever filled them is done
+        let batch = arrived_files(&arrived, file_name);
+        if !batch.is_empty() {
+            on_batch(batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::CreateKind;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_arrived_files() -> Result<()> {
+        let event = |kind: EventKind, paths: &[&str]| Event {
+            kind,
+            paths: paths.iter().map(PathBuf::from).collect(),
+            attrs: Default::default(),
+        };
+        assert_eq!(
+            arrived_paths(&event(EventKind::Create(CreateKind::File), &["a"])),
+            [PathBuf::from("a")]
+        );
+        assert_eq!(
+            arrived_paths(&event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["a.swp", "a"]
+            )),
+            [PathBuf::from("a")]
+        );
+        assert!(
+            arrived_paths(&event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                &["a"]
+            ))
+            .is_empty()
+        );
+
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        let nested = root.join("new").join("lib");
+        fs::create_dir_all(&nested)?;
+        fs::write(nested.join("pyproject.toml"), "")?;
+        fs::write(root.join("pyproject.toml"), "")?;
+        fs::write(root.join("setup.py"), "")?;
+        // A directory that arrived with a project already inside
+        assert_eq!(
+            arrived_files(
+                &[
+                    root.join("new"),
+                    root.join("pyproject.toml"),
+                    root.join("setup.py"),
+                    root.join("gone").join("pyproject.toml"),
+                    nested.join("pyproject.toml"),
+                ],
+                "pyproject.toml"
+            ),
+            vec![nested.join("pyproject.toml"), root.join("pyproject.toml")]
+        );
+        Ok(())
+    }
+}